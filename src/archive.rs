@@ -0,0 +1,539 @@
+use std::{
+    fs::{self, File},
+    io::{self, BufRead, BufReader, ErrorKind, Read, Seek, SeekFrom, Write},
+    path::{Component, Path, PathBuf},
+    sync::atomic::Ordering,
+};
+
+use bevy::prelude::*;
+use miniz_oxide::{
+    inflate::stream::{inflate, InflateState},
+    DataFormat, MZError, MZFlush, MZStatus,
+};
+
+use crate::world::{CopyProgress, WORLD_META_FILE};
+
+// Worlds backed up or shared as a single file, which are extracted before they're played
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum ArchiveKind {
+    Zip,
+    TarGz,
+}
+
+const ARCHIVE_EXTENSIONS: [(&str, ArchiveKind); 3] = [
+    (".zip", ArchiveKind::Zip),
+    (".tar.gz", ArchiveKind::TarGz),
+    (".tgz", ArchiveKind::TarGz),
+];
+
+impl ArchiveKind {
+    pub fn of(path: &Path) -> Option<Self> {
+        let name = path.file_name()?.to_string_lossy().to_lowercase();
+        ARCHIVE_EXTENSIONS
+            .iter()
+            .find(|(extension, _)| name.ends_with(extension))
+            .map(|(_, kind)| *kind)
+    }
+}
+
+// The name the extracted world gets
+pub fn archive_stem(path: &Path) -> String {
+    let name = path
+        .file_name()
+        .map_or_else(String::new, |name| name.to_string_lossy().into_owned());
+    let lowercase = name.to_lowercase();
+    match ARCHIVE_EXTENSIONS
+        .iter()
+        .find(|(extension, _)| lowercase.ends_with(extension))
+    {
+        Some((extension, _)) => name[..name.len() - extension.len()].to_string(),
+        None => name,
+    }
+}
+
+fn invalid(message: impl Into<String>) -> io::Error {
+    io::Error::new(ErrorKind::InvalidData, message.into())
+}
+
+fn le16(bytes: &[u8]) -> u16 {
+    u16::from_le_bytes([bytes[0], bytes[1]])
+}
+
+fn le32(bytes: &[u8]) -> u32 {
+    u32::from_le_bytes([bytes[0], bytes[1], bytes[2], bytes[3]])
+}
+
+// Where `extract_world` looks for it, at the top or in a single folder
+fn is_world_meta(name: &str) -> bool {
+    let path = Path::new(name);
+    path.file_name() == Some(WORLD_META_FILE.as_ref())
+        && path
+            .components()
+            .filter(|component| matches!(component, Component::Normal(_)))
+            .count()
+            <= 2
+}
+
+// Only reads as far as it has to, since every archive is checked each time the list is built
+pub fn check_archive(archive: &Path) -> io::Result<()> {
+    let has_meta = match ArchiveKind::of(archive) {
+        Some(ArchiveKind::Zip) => zip_entries(&mut File::open(archive)?)?
+            .iter()
+            .any(|entry| is_world_meta(&entry.name)),
+        Some(ArchiveKind::TarGz) => {
+            let mut reader = BufReader::new(File::open(archive)?);
+            read_gzip_header(&mut reader)?;
+            let mut has_meta = false;
+            read_tar(&mut Inflater::new(reader), |name, _, _| {
+                has_meta = is_world_meta(&name);
+                Ok(!has_meta)
+            })?;
+            has_meta
+        }
+        None => return Err(invalid("It isn't an archive")),
+    };
+
+    if !has_meta {
+        return Err(invalid(format!("There's no {} in it", WORLD_META_FILE)));
+    }
+    Ok(())
+}
+
+// Extracts into `partial` first, so a world never shows up half extracted. Archives often hold
+// the world's folder rather than its contents, in which case that folder becomes the world.
+pub fn extract_world(
+    archive: &Path,
+    partial: &Path,
+    world: &Path,
+    progress: &CopyProgress,
+) -> io::Result<()> {
+    let mut file = File::open(archive)?;
+    progress
+        .total_bytes
+        .store(file.metadata()?.len(), Ordering::Relaxed);
+
+    fs::create_dir(partial)?;
+    match ArchiveKind::of(archive) {
+        Some(ArchiveKind::Zip) => extract_zip(&mut file, partial, progress)?,
+        Some(ArchiveKind::TarGz) => extract_tar_gz(file, partial, progress)?,
+        None => return Err(invalid("It isn't an archive")),
+    }
+
+    let root = if partial.join(WORLD_META_FILE).is_file() {
+        partial.to_path_buf()
+    } else {
+        let mut entries = fs::read_dir(partial)?.collect::<io::Result<Vec<_>>>()?;
+        match (entries.pop(), entries.is_empty()) {
+            (Some(entry), true) if entry.path().join(WORLD_META_FILE).is_file() => entry.path(),
+            _ => return Err(invalid(format!("There's no {} in it", WORLD_META_FILE))),
+        }
+    };
+    fs::rename(&root, world)?;
+    if root != partial {
+        fs::remove_dir_all(partial)?;
+    }
+    Ok(())
+}
+
+// Entries that would land outside the world, like `../`, are left out
+fn entry_path(name: &str) -> Option<PathBuf> {
+    let path = Path::new(name);
+    path.components()
+        .all(|component| matches!(component, Component::Normal(_) | Component::CurDir))
+        .then(|| path.to_path_buf())
+        .filter(|path| path.components().next().is_some())
+}
+
+fn skip_entry(name: &str, reason: &str, progress: &CopyProgress) {
+    warn!("Skipping {} in the archive, {}", name, reason);
+    progress.skipped.lock().unwrap().push(PathBuf::from(name));
+}
+
+const EXTRACT_CHUNK_SIZE: usize = 64 * 1024;
+
+fn write_entry(reader: &mut impl Read, to: &Path, progress: &CopyProgress) -> io::Result<u32> {
+    if let Some(parent) = to.parent() {
+        fs::create_dir_all(parent)?;
+    }
+    let mut writer = File::create(to)?;
+    let mut hasher = crc32fast::Hasher::new();
+    let mut chunk = vec![0; EXTRACT_CHUNK_SIZE];
+
+    loop {
+        progress.check_cancel()?;
+
+        let read = match reader.read(&mut chunk) {
+            Ok(0) => break,
+            Ok(read) => read,
+            Err(err) if err.kind() == ErrorKind::Interrupted => continue,
+            Err(err) => return Err(err),
+        };
+        writer.write_all(&chunk[..read])?;
+        hasher.update(&chunk[..read]);
+    }
+
+    progress.files.fetch_add(1, Ordering::Relaxed);
+    Ok(hasher.finalize())
+}
+
+// Counts what's been read of the archive itself, since it's the one size known upfront
+struct Counted<'a, R> {
+    reader: R,
+    progress: &'a CopyProgress,
+}
+
+impl<R: Read> Read for Counted<'_, R> {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        let read = self.reader.read(buf)?;
+        self.progress
+            .copied_bytes
+            .fetch_add(read as u64, Ordering::Relaxed);
+        Ok(read)
+    }
+}
+
+// Raw deflate, as both zip entries and gzip streams hold it
+struct Inflater<R> {
+    reader: R,
+    state: Box<InflateState>,
+    input: Vec<u8>,
+    start: usize,
+    end: usize,
+    eof: bool,
+    done: bool,
+}
+
+impl<R: Read> Inflater<R> {
+    fn new(reader: R) -> Self {
+        Self {
+            reader,
+            state: InflateState::new_boxed(DataFormat::Raw),
+            input: vec![0; EXTRACT_CHUNK_SIZE],
+            start: 0,
+            end: 0,
+            eof: false,
+            done: false,
+        }
+    }
+}
+
+impl<R: Read> Read for Inflater<R> {
+    fn read(&mut self, out: &mut [u8]) -> io::Result<usize> {
+        if self.done || out.is_empty() {
+            return Ok(0);
+        }
+
+        loop {
+            if self.start == self.end && !self.eof {
+                self.end = self.reader.read(&mut self.input)?;
+                self.start = 0;
+                self.eof = self.end == 0;
+            }
+
+            let result = inflate(
+                &mut self.state,
+                &self.input[self.start..self.end],
+                out,
+                MZFlush::None,
+            );
+            self.start += result.bytes_consumed;
+            let stuck = result.bytes_consumed == 0 && self.start < self.end;
+
+            match result.status {
+                Ok(MZStatus::StreamEnd) => {
+                    self.done = true;
+                    return Ok(result.bytes_written);
+                }
+                Ok(_) | Err(MZError::Buf) if result.bytes_written > 0 => {
+                    return Ok(result.bytes_written)
+                }
+                Ok(_) | Err(MZError::Buf) if self.eof => {
+                    return Err(io::Error::new(ErrorKind::UnexpectedEof, "It ends early"))
+                }
+                Ok(_) | Err(MZError::Buf) if !stuck => (),
+                _ => return Err(invalid("It's corrupt")),
+            }
+        }
+    }
+}
+
+const ZIP_LOCAL_HEADER: u32 = 0x04034b50;
+const ZIP_CENTRAL_HEADER: u32 = 0x02014b50;
+const ZIP_END_OF_DIRECTORY: u32 = 0x06054b50;
+const ZIP_END_OF_DIRECTORY_SIZE: usize = 22;
+const ZIP_CENTRAL_HEADER_SIZE: usize = 46;
+const ZIP_LOCAL_HEADER_SIZE: usize = 30;
+// The end of directory record may be followed by a comment up to this long
+const ZIP_MAX_COMMENT: usize = u16::MAX as usize;
+
+const ZIP_STORED: u16 = 0;
+const ZIP_DEFLATED: u16 = 8;
+const ZIP_ENCRYPTED: u16 = 1;
+
+struct ZipEntry {
+    name: String,
+    flags: u16,
+    method: u16,
+    crc: u32,
+    compressed_size: u64,
+    offset: u64,
+}
+
+fn zip_entries(file: &mut File) -> io::Result<Vec<ZipEntry>> {
+    let len = file.seek(SeekFrom::End(0))?;
+    let tail_len = len.min((ZIP_END_OF_DIRECTORY_SIZE + ZIP_MAX_COMMENT) as u64);
+    file.seek(SeekFrom::Start(len - tail_len))?;
+    let mut tail = vec![0; tail_len as usize];
+    file.read_exact(&mut tail)?;
+
+    let end = (0..=tail.len().saturating_sub(ZIP_END_OF_DIRECTORY_SIZE))
+        .rev()
+        .filter(|&i| i + ZIP_END_OF_DIRECTORY_SIZE <= tail.len())
+        .find(|&i| le32(&tail[i..]) == ZIP_END_OF_DIRECTORY)
+        .map(|i| &tail[i..])
+        .ok_or_else(|| invalid("It isn't a zip file"))?;
+    let count = le16(&end[10..]);
+    let directory_size = le32(&end[12..]);
+    let directory_offset = le32(&end[16..]);
+    if count == u16::MAX || directory_offset == u32::MAX {
+        return Err(invalid("Zip64 archives aren't supported"));
+    }
+    // Checked before anything's allocated, since the sizes come straight from the file
+    if directory_offset as u64 + directory_size as u64 > len
+        || count as usize * ZIP_CENTRAL_HEADER_SIZE > directory_size as usize
+    {
+        return Err(invalid("Its directory is corrupt"));
+    }
+
+    file.seek(SeekFrom::Start(directory_offset as u64))?;
+    let mut directory = vec![0; directory_size as usize];
+    file.read_exact(&mut directory)?;
+
+    let mut entries = Vec::with_capacity(count as usize);
+    let mut header = &directory[..];
+    for _ in 0..count {
+        if header.len() < ZIP_CENTRAL_HEADER_SIZE || le32(header) != ZIP_CENTRAL_HEADER {
+            return Err(invalid("Its directory is corrupt"));
+        }
+        let name_len = le16(&header[28..]) as usize;
+        let extra_len = le16(&header[30..]) as usize;
+        let comment_len = le16(&header[32..]) as usize;
+        let header_len = ZIP_CENTRAL_HEADER_SIZE + name_len + extra_len + comment_len;
+        if header.len() < header_len {
+            return Err(invalid("Its directory is corrupt"));
+        }
+
+        entries.push(ZipEntry {
+            name: String::from_utf8_lossy(
+                &header[ZIP_CENTRAL_HEADER_SIZE..ZIP_CENTRAL_HEADER_SIZE + name_len],
+            )
+            .into_owned(),
+            flags: le16(&header[8..]),
+            method: le16(&header[10..]),
+            crc: le32(&header[16..]),
+            compressed_size: le32(&header[20..]) as u64,
+            offset: le32(&header[42..]) as u64,
+        });
+        header = &header[header_len..];
+    }
+    Ok(entries)
+}
+
+fn extract_zip(file: &mut File, to: &Path, progress: &CopyProgress) -> io::Result<()> {
+    for entry in zip_entries(file)? {
+        progress.check_cancel()?;
+
+        let path = match entry_path(&entry.name) {
+            Some(path) => to.join(path),
+            None => {
+                skip_entry(&entry.name, "which would land outside the world", progress);
+                continue;
+            }
+        };
+        if entry.name.ends_with('/') {
+            fs::create_dir_all(&path)?;
+            continue;
+        }
+        if entry.flags & ZIP_ENCRYPTED != 0 {
+            skip_entry(&entry.name, "which is encrypted", progress);
+            continue;
+        }
+
+        file.seek(SeekFrom::Start(entry.offset))?;
+        let mut header = [0; ZIP_LOCAL_HEADER_SIZE];
+        file.read_exact(&mut header)?;
+        if le32(&header) != ZIP_LOCAL_HEADER {
+            return Err(invalid(format!("{} is corrupt", entry.name)));
+        }
+        let data_offset = le16(&header[26..]) as i64 + le16(&header[28..]) as i64;
+        file.seek(SeekFrom::Current(data_offset))?;
+
+        let mut data = Counted {
+            reader: (&*file).take(entry.compressed_size),
+            progress,
+        };
+        let crc = match entry.method {
+            ZIP_STORED => write_entry(&mut data, &path, progress)?,
+            ZIP_DEFLATED => write_entry(&mut Inflater::new(data), &path, progress)?,
+            method => {
+                skip_entry(
+                    &entry.name,
+                    &format!("which uses unsupported compression {}", method),
+                    progress,
+                );
+                continue;
+            }
+        };
+        if crc != entry.crc {
+            return Err(invalid(format!("{} is corrupt", entry.name)));
+        }
+    }
+    Ok(())
+}
+
+const GZIP_MAGIC: [u8; 3] = [0x1f, 0x8b, 8];
+const GZIP_HEADER_CRC: u8 = 1 << 1;
+const GZIP_EXTRA: u8 = 1 << 2;
+const GZIP_NAME: u8 = 1 << 3;
+const GZIP_COMMENT: u8 = 1 << 4;
+
+fn read_gzip_header(reader: &mut impl BufRead) -> io::Result<()> {
+    let mut header = [0; 10];
+    reader.read_exact(&mut header)?;
+    if header[..3] != GZIP_MAGIC {
+        return Err(invalid("It isn't a gzip file"));
+    }
+
+    let flags = header[3];
+    if flags & GZIP_EXTRA != 0 {
+        let mut len = [0; 2];
+        reader.read_exact(&mut len)?;
+        io::copy(&mut reader.take(le16(&len) as u64), &mut io::sink())?;
+    }
+    for flag in [GZIP_NAME, GZIP_COMMENT] {
+        if flags & flag != 0 {
+            reader.read_until(0, &mut Vec::new())?;
+        }
+    }
+    if flags & GZIP_HEADER_CRC != 0 {
+        reader.read_exact(&mut [0; 2])?;
+    }
+    Ok(())
+}
+
+const TAR_BLOCK_SIZE: usize = 512;
+
+struct TarHeader {
+    name: String,
+    size: u64,
+    kind: u8,
+}
+
+fn parse_octal(field: &[u8]) -> io::Result<u64> {
+    let field = String::from_utf8_lossy(field);
+    let field = field.trim_matches(|c: char| c == '\0' || c == ' ');
+    if field.is_empty() {
+        return Ok(0);
+    }
+    u64::from_str_radix(field, 8).map_err(|_| invalid("A tar header is corrupt"))
+}
+
+fn tar_string(field: &[u8]) -> String {
+    let len = field.iter().position(|&b| b == 0).unwrap_or(field.len());
+    String::from_utf8_lossy(&field[..len]).into_owned()
+}
+
+impl TarHeader {
+    // `None` for the zeroed blocks that end the archive
+    fn parse(block: &[u8; TAR_BLOCK_SIZE]) -> io::Result<Option<Self>> {
+        if block.iter().all(|&b| b == 0) {
+            return Ok(None);
+        }
+
+        let checksum = block
+            .iter()
+            .enumerate()
+            .map(|(i, &b)| if (148..156).contains(&i) { b' ' } else { b } as u64)
+            .sum::<u64>();
+        if checksum != parse_octal(&block[148..156])? {
+            return Err(invalid("A tar header is corrupt"));
+        }
+
+        let mut name = tar_string(&block[..100]);
+        let prefix = tar_string(&block[345..500]);
+        if &block[257..262] == b"ustar" && !prefix.is_empty() {
+            name = format!("{}/{}", prefix, name);
+        }
+        Ok(Some(Self {
+            name,
+            size: parse_octal(&block[124..136])?,
+            kind: block[156],
+        }))
+    }
+}
+
+// Calls `entry` with each entry's name, header and data, until it returns `false` or the archive
+// ends. Whatever of the data it leaves unread is skipped.
+fn read_tar<R: Read>(
+    tar: &mut R,
+    mut entry: impl FnMut(String, &TarHeader, &mut io::Take<&mut R>) -> io::Result<bool>,
+) -> io::Result<()> {
+    let mut long_name = None;
+    let mut block = [0; TAR_BLOCK_SIZE];
+    loop {
+        tar.read_exact(&mut block)?;
+        let header = match TarHeader::parse(&block)? {
+            Some(header) => header,
+            None => break,
+        };
+        let name = long_name.take().unwrap_or_else(|| header.name.clone());
+        let mut data = (&mut *tar).take(header.size);
+
+        // GNU tar's names too long for the header, which belong to the next entry
+        if header.kind == b'L' {
+            let mut name = Vec::new();
+            data.read_to_end(&mut name)?;
+            long_name = Some(tar_string(&name));
+        } else if !entry(name, &header, &mut data)? {
+            return Ok(());
+        }
+
+        io::copy(&mut data, &mut io::sink())?;
+        if data.limit() > 0 {
+            return Err(io::Error::new(ErrorKind::UnexpectedEof, "It ends early"));
+        }
+        let padding = header.size.next_multiple_of(TAR_BLOCK_SIZE as u64) - header.size;
+        io::copy(&mut (&mut *tar).take(padding), &mut io::sink())?;
+    }
+
+    // The gzip trailer goes unread, as the tar headers have checksums of their own
+    Ok(())
+}
+
+fn extract_tar_gz(file: File, to: &Path, progress: &CopyProgress) -> io::Result<()> {
+    let mut reader = BufReader::new(Counted {
+        reader: file,
+        progress,
+    });
+    read_gzip_header(&mut reader)?;
+
+    read_tar(&mut Inflater::new(reader), |name, header, data| {
+        progress.check_cancel()?;
+
+        match header.kind {
+            b'0' | 0 | b'5' => match entry_path(&name) {
+                Some(path) if header.kind == b'5' || name.ends_with('/') => {
+                    fs::create_dir_all(to.join(path))?;
+                }
+                Some(path) => {
+                    write_entry(data, &to.join(path), progress)?;
+                }
+                None => skip_entry(&name, "which would land outside the world", progress),
+            },
+            // Extended attributes, which worlds don't need
+            b'x' | b'g' => (),
+            _ => skip_entry(&name, "which isn't a file or folder", progress),
+        }
+        Ok(true)
+    })
+}
@@ -4,20 +4,26 @@ use bevy::{input::mouse::MouseMotion, prelude::*, render::camera::Camera3d};
 
 use crate::state::GameState;
 
-use super::player::ChunkPos;
+use super::{player::ChunkPos, GameEntity};
 
 pub struct CamPlugin;
 
 impl Plugin for CamPlugin {
     fn build(&self, app: &mut App) {
-        app.add_system_set(SystemSet::on_enter(GameState::Game).with_system(init_cam))
-            .add_system_set(
-                SystemSet::on_update(GameState::Game)
-                    .with_system(toggle_cursor)
-                    .with_system(look_cam)
-                    .with_system(move_cam),
-            )
-            .add_system_set(SystemSet::on_exit(GameState::Game).with_system(exit_cam));
+        app.add_system_set(
+            SystemSet::on_enter(GameState::Game)
+                .with_system(init_cam)
+                .with_system(lock_cursor),
+        )
+        .add_system_set(SystemSet::on_resume(GameState::Game).with_system(lock_cursor))
+        .add_system_set(SystemSet::on_pause(GameState::Game).with_system(exit_cam))
+        .add_system_set(
+            SystemSet::on_update(GameState::Game)
+                .with_system(toggle_cursor)
+                .with_system(look_cam)
+                .with_system(move_cam),
+        )
+        .add_system_set(SystemSet::on_exit(GameState::Game).with_system(exit_cam));
     }
 }
 
@@ -27,12 +33,15 @@ struct Rotation {
     yaw: f32,
 }
 
-fn init_cam(mut commands: Commands, mut windows: ResMut<Windows>) {
+fn init_cam(mut commands: Commands) {
     commands
         .spawn_bundle(PerspectiveCameraBundle::default())
         .insert(Rotation::default())
-        .insert(ChunkPos::default());
+        .insert(ChunkPos::default())
+        .insert(GameEntity);
+}
 
+fn lock_cursor(mut windows: ResMut<Windows>) {
     let window = windows.primary_mut();
     window.set_cursor_lock_mode(true);
     window.set_cursor_visibility(false);
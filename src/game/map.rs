@@ -9,7 +9,7 @@ use bevy::{
 
 use crate::state::GameState;
 
-use super::{chunk::Chunk, player::ChunkPos, render::RemovedChunks, DespawnQueue};
+use super::{chunk::Chunk, player::ChunkPos, render::RemovedChunks, DespawnQueue, GameEntity};
 
 pub struct MapPlugin;
 
@@ -69,6 +69,7 @@ impl Map {
                     commands
                         .spawn()
                         .insert(thread_pool.spawn(async move { Chunk::generate(chunk_pos) }))
+                        .insert(GameEntity)
                         .id(),
                 );
             }
@@ -6,9 +6,13 @@ mod render;
 mod vox;
 mod vox_buffer;
 
+use std::path::Path;
+
 use bevy::prelude::*;
+use futures_lite::future::block_on;
+use serde::Deserialize;
 
-use crate::state::GameState;
+use crate::{settings::Settings, state::GameState};
 
 use self::{
     cam::CamPlugin,
@@ -18,6 +22,33 @@ use self::{
     render::RenderPlugin,
 };
 
+pub const GAME_MANIFEST_FILE: &str = "game.ron";
+
+#[derive(Default, Deserialize)]
+pub struct GameManifest {
+    #[serde(default)]
+    pub name: Option<String>,
+    #[serde(default)]
+    pub author: Option<String>,
+    #[serde(default)]
+    pub version: Option<String>,
+    #[serde(default)]
+    pub description: Option<String>,
+}
+
+impl GameManifest {
+    pub fn load(asset_server: &AssetServer, game: &Path) -> Result<Self, String> {
+        let manifest = block_on(
+            asset_server
+                .asset_io()
+                .load_path(&game.join(GAME_MANIFEST_FILE)),
+        )
+        .map_err(|err| format!("Couldn't read game manifest: {}", err))?;
+        ron::de::from_bytes(&manifest)
+            .map_err(|err| format!("Couldn't parse game manifest: {}", err))
+    }
+}
+
 pub struct GamePlugin;
 
 impl Plugin for GamePlugin {
@@ -29,10 +60,16 @@ impl Plugin for GamePlugin {
             .add_plugin(RenderPlugin)
             .init_resource::<DespawnQueue>()
             .add_system_to_stage(CoreStage::PostUpdate, despawn)
-            .add_system_set(SystemSet::on_update(GameState::Game).with_system(exit_game));
+            .add_system_set(SystemSet::on_update(GameState::Game).with_system(pause_game))
+            .add_system_set(SystemSet::on_exit(GameState::Game).with_system(term_game));
     }
 }
 
+// Marks what the game spawns, to be despawned when it ends. Anything spawned into the game from
+// outside this module should carry it too.
+#[derive(Component)]
+pub struct GameEntity;
+
 #[derive(Default, Deref, DerefMut)]
 pub struct DespawnQueue(Vec<Entity>);
 
@@ -42,19 +79,23 @@ fn despawn(mut commands: Commands, mut despawn_queue: ResMut<DespawnQueue>) {
     }
 }
 
-fn exit_game(
-    mut commands: Commands,
-    chunks: Query<Entity>,
+fn pause_game(
     keys: Res<Input<KeyCode>>,
+    settings: Res<Settings>,
     mut state: ResMut<State<GameState>>,
 ) {
-    if keys.just_pressed(KeyCode::Escape) {
-        for chunk_e in chunks.iter() {
-            commands.entity(chunk_e).despawn();
+    if keys.just_pressed(settings.key_bindings.back) {
+        if let Err(err) = state.push(GameState::Paused) {
+            warn!("Couldn't pause the game: {}", err);
         }
+    }
+}
 
-        commands.remove_resource::<Map>();
-
-        state.set(GameState::MainMenu).unwrap();
+// Despawns everything the game owns, however it's left
+fn term_game(mut commands: Commands, game_entities: Query<Entity, With<GameEntity>>) {
+    for game_e in game_entities.iter() {
+        commands.entity(game_e).despawn_recursive();
     }
+
+    commands.remove_resource::<Map>();
 }
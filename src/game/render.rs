@@ -158,7 +158,7 @@ impl<const I: usize> EntityRenderCommand for SetGpuVoxesBindGroup<I> {
         gpu_voxes_bind_groups: SystemParamItem<'w, '_, Self::Param>,
         pass: &mut TrackedRenderPass<'w>,
     ) -> RenderCommandResult {
-        pass.set_bind_group(I, &gpu_voxes_bind_groups.get_inner(item).unwrap(), &[]);
+        pass.set_bind_group(I, gpu_voxes_bind_groups.get_inner(item).unwrap(), &[]);
 
         RenderCommandResult::Success
     }
@@ -182,7 +182,7 @@ impl EntityRenderCommand for DrawVertexPulledVoxes {
             0,
             IndexFormat::Uint32,
         );
-        pass.draw_indexed(0..gpu_voxes.i_count as u32, 0, 0..1);
+        pass.draw_indexed(0..gpu_voxes.i_count, 0, 0..1);
 
         RenderCommandResult::Success
     }
@@ -511,9 +511,7 @@ fn prepare_voxes(
             }),
         );
 
-        gpu_voxes
-            .insts
-            .write_buffer(&*render_device, &*render_queue);
+        gpu_voxes.insts.write_buffer(&render_device, &render_queue);
     }
 }
 
@@ -84,7 +84,7 @@ impl VoxBuffer {
             let mut offset = 0;
             for (_, voxes) in &self.voxes {
                 let range = 0..self.vox_size * voxes.len();
-                let bytes: &[u8] = cast_slice(&voxes);
+                let bytes: &[u8] = cast_slice(voxes);
                 queue.write_buffer(buffer, offset, &bytes[range]);
                 offset += (self.vox_size * voxes.len()) as u64;
             }
@@ -0,0 +1,7 @@
+pub mod archive;
+pub mod game;
+pub mod locale;
+pub mod menu;
+pub mod settings;
+pub mod state;
+pub mod world;
@@ -0,0 +1,108 @@
+use std::{collections::HashMap, path::Path};
+
+use bevy::prelude::*;
+use futures_lite::future::block_on;
+
+use crate::settings::Settings;
+
+const LOCALES_PATH: &str = "locales";
+// Keys that configure the locale instead of translating text
+const FONT_KEY: &str = "locale.font";
+const DIRECTION_KEY: &str = "locale.direction";
+
+pub struct LocalePlugin;
+
+impl Plugin for LocalePlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<Locale>().add_system(load_locale);
+    }
+}
+
+#[derive(Default)]
+pub struct Locale {
+    language: String,
+    languages: Vec<String>,
+    strings: HashMap<String, String>,
+}
+
+impl Locale {
+    pub fn language(&self) -> &str {
+        &self.language
+    }
+
+    pub fn get(&self, key: &str) -> String {
+        self.strings
+            .get(key)
+            .cloned()
+            .unwrap_or_else(|| key.to_string())
+    }
+
+    // The singular or plural form of `key`, stored under `key.one` and `key.other`, with `{count}`
+    // filled in
+    pub fn count(&self, key: &str, count: usize) -> String {
+        let form = if count == 1 { "one" } else { "other" };
+        self.get(&format!("{}.{}", key, form))
+            .replace("{count}", &count.to_string())
+    }
+
+    // A font from the menu's fonts folder, by file stem, for scripts the default font lacks
+    pub fn font(&self) -> Option<&str> {
+        self.strings.get(FONT_KEY).map(String::as_str)
+    }
+
+    pub fn rtl(&self) -> bool {
+        self.strings
+            .get(DIRECTION_KEY)
+            .is_some_and(|direction| direction == "rtl")
+    }
+
+    pub fn next_language(&self) -> Option<&String> {
+        let current = self
+            .languages
+            .iter()
+            .position(|language| *language == self.language);
+        self.languages
+            .get(current.map_or(0, |current| (current + 1) % self.languages.len()))
+    }
+}
+
+fn load_locale(
+    settings: Res<Settings>,
+    asset_server: Res<AssetServer>,
+    mut locale: ResMut<Locale>,
+) {
+    if !settings.is_changed() || locale.language == settings.language {
+        return;
+    }
+
+    let asset_io = asset_server.asset_io();
+    let mut languages = asset_io
+        .read_directory(Path::new(LOCALES_PATH))
+        .map(|paths| {
+            paths
+                .filter(|path| path.extension().is_some_and(|extension| extension == "ron"))
+                .filter_map(|path| Some(path.file_stem()?.to_string_lossy().into_owned()))
+                .collect::<Vec<_>>()
+        })
+        .unwrap_or_default();
+    languages.sort();
+
+    let path = Path::new(LOCALES_PATH).join(format!("{}.ron", settings.language));
+    let strings = block_on(asset_io.load_path(&path))
+        .map_err(|err| err.to_string())
+        .and_then(|bytes| ron::de::from_bytes(&bytes).map_err(|err| err.to_string()))
+        .unwrap_or_else(|err| {
+            warn!(
+                "Couldn't load {}, falling back to keys: {}",
+                path.display(),
+                err
+            );
+            default()
+        });
+
+    *locale = Locale {
+        language: settings.language.clone(),
+        languages,
+        strings,
+    };
+}
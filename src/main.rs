@@ -1,21 +1,23 @@
-mod game;
-mod menu;
-mod state;
-
 use bevy::{app::AppExit, prelude::*};
 use bevy_asset_loader::AssetLoader;
 #[cfg(feature = "inspector")]
 use bevy_inspector_egui::WorldInspectorPlugin;
-use game::GamePlugin;
-use menu::{Fonts, MenuPlugin};
-use state::{GameState, OpeningGame, StatePlugin};
+use voxmod::{
+    game::GamePlugin,
+    locale::LocalePlugin,
+    menu::{MenuImages, MenuPlugin, MenuSounds},
+    settings::SettingsPlugin,
+    state::{GameState, OpeningGame, StatePlugin},
+    world::WorldPlugin,
+};
 
 fn main() {
     let mut app = App::new();
 
     AssetLoader::new(GameState::Loading)
         .continue_to_state(GameState::MainMenu)
-        .with_collection::<Fonts>()
+        .with_collection::<MenuSounds>()
+        .with_collection::<MenuImages>()
         .build(&mut app);
 
     app.insert_resource(WindowDescriptor {
@@ -24,8 +26,11 @@ fn main() {
     })
     .add_plugins(DefaultPlugins)
     .add_plugin(GamePlugin)
-    .add_plugin(MenuPlugin)
+    .add_plugin(LocalePlugin)
+    .add_plugin(MenuPlugin::default())
+    .add_plugin(SettingsPlugin)
     .add_plugin(StatePlugin)
+    .add_plugin(WorldPlugin)
     .insert_resource(ClearColor(Color::BLACK))
     .add_system_set(SystemSet::on_resume(GameState::MainMenu).with_system(exit));
 
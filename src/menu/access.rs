@@ -0,0 +1,66 @@
+use bevy::prelude::*;
+
+use crate::state::GameState;
+
+use super::focus::Focused;
+
+// Bevy 0.7 predates AccessKit, so the menus label their widgets and announce them as events,
+// for the host app to pass on to a screen reader
+pub struct AccessPlugin;
+
+impl Plugin for AccessPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_event::<MenuAnnouncement>().add_system_set(
+            SystemSet::on_update(GameState::Menu)
+                .with_system(announce_titles)
+                .with_system(announce_focus),
+        );
+    }
+}
+
+#[derive(Clone, Copy, Debug)]
+pub enum AccessRole {
+    Heading,
+    Button,
+}
+
+#[derive(Clone, Component)]
+pub struct AccessibleLabel {
+    pub role: AccessRole,
+    pub name: String,
+}
+
+#[derive(Clone, Debug)]
+pub struct MenuAnnouncement {
+    pub role: AccessRole,
+    pub name: String,
+}
+
+impl From<&AccessibleLabel> for MenuAnnouncement {
+    fn from(label: &AccessibleLabel) -> Self {
+        Self {
+            role: label.role,
+            name: label.name.clone(),
+        }
+    }
+}
+
+fn announce_titles(
+    titles: Query<&AccessibleLabel, Added<AccessibleLabel>>,
+    mut announcements: EventWriter<MenuAnnouncement>,
+) {
+    for title in titles.iter() {
+        if let AccessRole::Heading = title.role {
+            announcements.send(title.into());
+        }
+    }
+}
+
+fn announce_focus(
+    focused: Query<&AccessibleLabel, Added<Focused>>,
+    mut announcements: EventWriter<MenuAnnouncement>,
+) {
+    for label in focused.iter() {
+        announcements.send(label.into());
+    }
+}
@@ -0,0 +1,745 @@
+use std::{
+    collections::{HashMap, HashSet},
+    sync::{atomic::Ordering, Arc},
+    time::SystemTime,
+};
+
+use bevy::{app::AppExit, ecs::system::SystemParam, prelude::*, tasks::AsyncComputeTaskPool};
+
+use crate::{
+    locale::Locale,
+    settings::{SavedSettings, Settings},
+    state::GameState,
+    world::parse_seed,
+};
+
+use super::{
+    asset_exists, asset_name,
+    asset_rows::{AssetList, AssetListSource, AssetListWait},
+    confirm_menu,
+    focus::{self, Disabled, Focused, NavInput},
+    in_menu, open_menu, open_world,
+    tabs::SelectTab,
+    text_input::{MenuTextInput, TextInputField},
+    toast::{MenuToast, ToastSeverity},
+    unsaved_settings_menu,
+    world_actions::{
+        copying_world_menu, create_world, delete_worlds_menu, duplicate_world, extract_world,
+        rename_world, restore_world, toggle_favorite, trash_world, WorldCopy, WorldSelection,
+    },
+    Action, KeyRebinding, MenuAction, MenuDismiss, MenuEs, MenuImageCache, MenuPaths, MenuScrim,
+    MenuSounds, MenuSource, Rebuild, ReturnToMenu, SettingButton, Shortcut,
+};
+
+// Runs whichever action a button, shortcut or confirmation prompt triggered
+pub struct ActionsPlugin;
+
+impl Plugin for ActionsPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<MenuActionHandlers>()
+            .init_resource::<MenuInputLock>()
+            .add_event::<TriggeredAction>()
+            .add_system_set(
+                SystemSet::on_update(GameState::Menu)
+                    .with_system(press_shortcuts.after(focus::read_nav_input))
+                    .with_system(lock_menu_input)
+                    .with_system(release_buttons.after(lock_menu_input))
+                    .with_system(release_confirmed_action)
+                    .with_system(play_hover_sound)
+                    .with_system(
+                        button_action
+                            .after(lock_menu_input)
+                            .after(focus::read_nav_input)
+                            .after(press_shortcuts)
+                            .after(release_buttons)
+                            .after(release_confirmed_action),
+                    ),
+            )
+            .add_system_set(SystemSet::on_resume(GameState::Menu).with_system(leave_settings));
+    }
+}
+
+pub type MenuActionHandler = Arc<dyn Fn(&mut World) + Send + Sync>;
+
+#[derive(Default)]
+pub struct MenuActionHandlers(HashMap<String, MenuActionHandler>);
+
+impl MenuActionHandlers {
+    pub fn register(
+        &mut self,
+        id: impl Into<String>,
+        handler: impl Fn(&mut World) + Send + Sync + 'static,
+    ) {
+        self.0.insert(id.into(), Arc::new(handler));
+    }
+}
+
+pub type MenuTelemetryHandler = Arc<dyn Fn(&'static str, SystemTime) + Send + Sync>;
+
+// Called with each activated action's variant name, for the host app to log or aggregate. Without
+// this resource, nothing is recorded.
+pub struct MenuTelemetry(MenuTelemetryHandler);
+
+impl MenuTelemetry {
+    pub fn new(handler: impl Fn(&'static str, SystemTime) + Send + Sync + 'static) -> Self {
+        Self(Arc::new(handler))
+    }
+}
+
+// Held until the confirmation prompt has closed, to run on the menu below it
+struct ConfirmedAction {
+    action: Action,
+    depth: usize,
+}
+
+fn release_confirmed_action(
+    mut commands: Commands,
+    confirmed: Option<Res<ConfirmedAction>>,
+    menu_es: Option<Res<MenuEs>>,
+    mut triggered: EventWriter<TriggeredAction>,
+) {
+    let confirmed = match confirmed {
+        Some(confirmed) => confirmed,
+        None => return,
+    };
+    if menu_es.is_some_and(|menu_es| menu_es.len() >= confirmed.depth) {
+        return;
+    }
+
+    triggered.send(TriggeredAction(confirmed.action.clone()));
+    commands.remove_resource::<ConfirmedAction>();
+}
+
+pub struct TriggeredAction(Action);
+
+type ButtonInteractions<'w, 's, 'a> = Query<
+    'w,
+    's,
+    (Entity, &'a Interaction, Option<&'a MenuScrim>),
+    (Changed<Interaction>, With<Button>, Without<Disabled>),
+>;
+
+type FocusedButtons<'w, 's, 'a> =
+    Query<'w, 's, (Entity, &'a Action), (With<Focused>, With<Button>, Without<Disabled>)>;
+
+type ShortcutButtons<'w, 's, 'a> =
+    Query<'w, 's, (Entity, &'a Shortcut, &'a Action), Without<Disabled>>;
+
+// Frames after a menu push or pop in which clicks and activation are ignored. Until the old menu's
+// `Display::None` takes effect, its buttons can still be hit, navigating twice.
+const MENU_INPUT_LOCK_FRAMES: u8 = 2;
+
+#[derive(Default)]
+pub struct MenuInputLock(u8);
+
+fn lock_menu_input(
+    menu_es: Option<Res<MenuEs>>,
+    mut lock: ResMut<MenuInputLock>,
+    mut last_stack: Local<Option<(usize, Option<Entity>)>>,
+) {
+    // `MenuEs` also changes when focus moves, so compare the stack itself
+    let stack = menu_es
+        .as_ref()
+        .map(|menu_es| (menu_es.len(), menu_es.top()));
+    if *last_stack != stack {
+        *last_stack = stack;
+        lock.0 = MENU_INPUT_LOCK_FRAMES;
+    } else {
+        lock.0 = lock.0.saturating_sub(1);
+    }
+}
+
+// Buttons activate on release, and only if the cursor is still over the pressed button
+#[allow(clippy::too_many_arguments)]
+fn release_buttons(
+    mouse_buttons: Res<Input<MouseButton>>,
+    touches: Res<Touches>,
+    windows: Res<Windows>,
+    lock: Res<MenuInputLock>,
+    interactions: ButtonInteractions,
+    buttons: Query<(&Node, &GlobalTransform, &Action), Without<Disabled>>,
+    mut pressed: Local<Option<Entity>>,
+    mut triggered: EventWriter<TriggeredAction>,
+) {
+    if lock.0 > 0 {
+        *pressed = None;
+        return;
+    }
+
+    for (button_e, interaction, _) in interactions.iter() {
+        if let Interaction::Clicked = interaction {
+            *pressed = Some(button_e);
+        }
+    }
+
+    if !mouse_buttons.just_released(MouseButton::Left) && !touches.just_released(0) {
+        return;
+    }
+
+    let (node, transform, action) = match pressed.take().and_then(|e| buttons.get(e).ok()) {
+        Some(button) => button,
+        None => return,
+    };
+
+    // Touches have no cursor to drag off with
+    let center = transform.translation.truncate();
+    let extents = node.size / 2.;
+    let over = windows
+        .get_primary()
+        .and_then(|window| window.cursor_position())
+        .is_none_or(|cursor| {
+            (center - extents).cmple(cursor).all() && cursor.cmplt(center + extents).all()
+        });
+    if over {
+        triggered.send(TriggeredAction(action.clone()));
+    }
+}
+
+#[allow(clippy::too_many_arguments)]
+pub fn press_shortcuts(
+    mut keys: ResMut<Input<KeyCode>>,
+    nav: Res<NavInput>,
+    settings: Res<Settings>,
+    rebinding: Res<KeyRebinding>,
+    menu_es: Option<Res<MenuEs>>,
+    dismisses: Query<&MenuDismiss>,
+    shortcuts: ShortcutButtons,
+    text_inputs: Query<(Entity, &MenuTextInput)>,
+    parents: Query<&Parent>,
+    mut triggered: EventWriter<TriggeredAction>,
+) {
+    let menu_e = match menu_es.as_ref().and_then(|menu_es| menu_es.top()) {
+        Some(menu_e) => menu_e,
+        None => return,
+    };
+
+    if rebinding.0.is_some() {
+        return;
+    }
+
+    // Keys typed into a text input aren't shortcuts
+    if text_inputs
+        .iter()
+        .any(|(input_e, input)| input.focused && in_menu(input_e, menu_e, &parents))
+    {
+        return;
+    }
+
+    for (button_e, shortcut, action) in shortcuts.iter() {
+        if keys.just_pressed(shortcut.0) && in_menu(button_e, menu_e, &parents) {
+            triggered.send(TriggeredAction(action.clone()));
+        }
+    }
+
+    if let (true, Ok(dismiss)) = (nav.back, dismisses.get(menu_e)) {
+        triggered.send(TriggeredAction(dismiss.0.clone()));
+        // Otherwise the game, resumed this frame, would read the same press and pause again
+        keys.clear_just_pressed(settings.key_bindings.back);
+    }
+}
+
+#[derive(SystemParam)]
+pub(super) struct ActionContext<'w, 's> {
+    rebuilds: EventWriter<'w, 's, Rebuild>,
+    toasts: EventWriter<'w, 's, MenuToast>,
+    menu_actions: EventWriter<'w, 's, MenuAction>,
+    returns: EventWriter<'w, 's, ReturnToMenu>,
+    handlers: Res<'w, MenuActionHandlers>,
+    paths: Res<'w, MenuPaths>,
+    saved_settings: ResMut<'w, SavedSettings>,
+    selection: ResMut<'w, WorldSelection>,
+    telemetry: Option<Res<'w, MenuTelemetry>>,
+    thread_pool: Res<'w, AsyncComputeTaskPool>,
+    world_copy: Option<Res<'w, WorldCopy>>,
+    image_cache: Res<'w, MenuImageCache>,
+    rebinding: ResMut<'w, KeyRebinding>,
+    tab_selects: EventWriter<'w, 's, SelectTab>,
+    exits: EventWriter<'w, 's, AppExit>,
+    menus: ActionMenus<'w, 's>,
+}
+
+// What actions look up in the open menus
+#[derive(SystemParam)]
+pub(super) struct ActionMenus<'w, 's> {
+    sources: Query<'w, 's, &'static MenuSource>,
+    listings: Query<'w, 's, (), With<AssetList>>,
+}
+
+// Only when the cursor moves onto a button, not when it's let go of over one
+fn play_hover_sound(
+    interactions: ButtonInteractions,
+    removed: RemovedComponents<Interaction>,
+    audio: Res<Audio>,
+    sounds: Res<MenuSounds>,
+    settings: Res<Settings>,
+    mut engaged: Local<HashSet<Entity>>,
+) {
+    for button_e in removed.iter() {
+        engaged.remove(&button_e);
+    }
+
+    for (button_e, interaction, scrim) in interactions.iter() {
+        match interaction {
+            Interaction::Hovered if engaged.insert(button_e) && scrim.is_none() => {
+                audio.play_with_settings(
+                    sounds.hover.clone(),
+                    PlaybackSettings::ONCE.with_volume(settings.master_volume),
+                );
+            }
+            Interaction::Clicked | Interaction::Hovered => {
+                engaged.insert(button_e);
+            }
+            Interaction::None => {
+                engaged.remove(&button_e);
+            }
+        }
+    }
+}
+
+#[allow(clippy::too_many_arguments)]
+pub fn button_action(
+    mut commands: Commands,
+    interactions: ButtonInteractions,
+    focused_buttons: FocusedButtons,
+    nav: Res<NavInput>,
+    lock: Res<MenuInputLock>,
+    mut text_inputs: Query<(Entity, &mut MenuTextInput)>,
+    parents: Query<&Parent>,
+    menu_es: Option<Res<MenuEs>>,
+    asset_server: Res<AssetServer>,
+    audio: Res<Audio>,
+    sounds: Res<MenuSounds>,
+    locale: Res<Locale>,
+    mut settings: ResMut<Settings>,
+    mut context: ActionContext,
+    mut triggered: EventReader<TriggeredAction>,
+    mut state: ResMut<State<GameState>>,
+) {
+    // Runs every frame in the menus, but there's usually nothing to do
+    if interactions.is_empty() && !nav.activate && triggered.is_empty() {
+        return;
+    }
+
+    let mut actions = Vec::new();
+    if nav.activate && lock.0 == 0 {
+        if let Some(menu_e) = menu_es.as_ref().and_then(|menu_es| menu_es.top()) {
+            actions.extend(
+                focused_buttons
+                    .iter()
+                    .filter(|(button_e, _)| in_menu(*button_e, menu_e, &parents))
+                    .map(|(_, action)| action.clone()),
+            );
+        }
+    }
+    actions.extend(triggered.iter().map(|triggered| triggered.0.clone()));
+
+    // Only one state change can be queued at a time, so later ones in the same frame are dropped
+    let mut changing_state = false;
+    for action in actions {
+        if action.changes_state() {
+            if changing_state {
+                continue;
+            }
+            changing_state = true;
+        }
+
+        let _span = info_span!(
+            "menu_action",
+            action = action.name(),
+            menu = menu_es
+                .as_ref()
+                .map_or("", |menu_es| menu_es.top_title(&context.menus.sources)),
+            depth = menu_es.as_ref().map_or(0, |menu_es| menu_es.len())
+        )
+        .entered();
+
+        if let Some(telemetry) = &context.telemetry {
+            (telemetry.0)(action.name(), SystemTime::now());
+        }
+
+        audio.play_with_settings(
+            sounds.click.clone(),
+            PlaybackSettings::ONCE.with_volume(settings.master_volume),
+        );
+
+        // Doing anything else gives up on the binding
+        context.rebinding.0 = None;
+
+        let state_change = match &action {
+            Action::Menu(menu) => open_menu(&mut commands, &mut state, menu.clone()),
+            // Only pops back to a previous menu, the bottom one has nothing to go back to
+            Action::Back if menu_es.as_ref().is_some_and(|menu_es| menu_es.len() > 1) => {
+                state.pop()
+            }
+            Action::Back => Ok(()),
+            Action::Quit if settings.confirm_on_quit => open_menu(
+                &mut commands,
+                &mut state,
+                confirm_menu("menu.confirm_quit", "menu.quit", Action::Quit),
+            ),
+            // Resuming `GameState::MainMenu` without a game opening exits the app
+            Action::Quit => {
+                context.menu_actions.send(MenuAction::Quit);
+                state.pop()
+            }
+            Action::Play(world) if !asset_exists(&asset_server, world) => {
+                context.toasts.send(MenuToast::new(
+                    ToastSeverity::Warning,
+                    locale
+                        .get("menu.missing")
+                        .replace("{name}", &asset_name(world)),
+                ));
+                context.rebuilds.send(Rebuild::Dynamic);
+                Ok(())
+            }
+            Action::Play(world) => {
+                context.menu_actions.send(MenuAction::Play(world.clone()));
+                open_world(&mut commands, &asset_server, world);
+                state.replace(GameState::LoadingWorld)
+            }
+            Action::Edit(world) if !asset_exists(&asset_server, world) => {
+                context.toasts.send(MenuToast::new(
+                    ToastSeverity::Warning,
+                    locale
+                        .get("menu.missing")
+                        .replace("{name}", &asset_name(world)),
+                ));
+                context.rebuilds.send(Rebuild::Dynamic);
+                Ok(())
+            }
+            Action::Edit(world) => {
+                context.menu_actions.send(MenuAction::Edit(world.clone()));
+                open_world(&mut commands, &asset_server, world);
+                state.replace(GameState::Edit)
+            }
+            Action::CreateWorld(game) if !asset_exists(&asset_server, game) => {
+                context.toasts.send(MenuToast::new(
+                    ToastSeverity::Warning,
+                    locale
+                        .get("menu.missing")
+                        .replace("{name}", &asset_name(game)),
+                ));
+                context.rebuilds.send(Rebuild::Dynamic);
+                state.pop()
+            }
+            Action::CreateWorld(game) => {
+                let menu_e = menu_es.as_ref().and_then(|menu_es| menu_es.top());
+                let seed = top_menu_input(&mut text_inputs, TextInputField::Seed, menu_e, &parents)
+                    .map_or_else(String::new, |input| input.value.clone());
+                let mut name_input = top_menu_input(
+                    &mut text_inputs,
+                    TextInputField::WorldName,
+                    menu_e,
+                    &parents,
+                );
+                let name = name_input
+                    .as_ref()
+                    .map_or_else(|| asset_name(game), |input| input.value.clone());
+
+                match create_world(
+                    &asset_server,
+                    &context.paths,
+                    game,
+                    &name,
+                    parse_seed(&seed),
+                    &locale,
+                ) {
+                    Ok(world) => {
+                        context.menu_actions.send(MenuAction::CreateWorld {
+                            game: game.clone(),
+                            world: world.clone(),
+                        });
+                        open_world(&mut commands, &asset_server, &world);
+                        state.replace(GameState::LoadingWorld)
+                    }
+                    Err(err) => {
+                        match &mut name_input {
+                            Some(input) => input.error = Some(err),
+                            None => context
+                                .toasts
+                                .send(MenuToast::new(ToastSeverity::Error, err)),
+                        }
+                        Ok(())
+                    }
+                }
+            }
+            Action::RenameWorld(world) => {
+                let menu_e = menu_es.as_ref().and_then(|menu_es| menu_es.top());
+                let mut name_input = top_menu_input(
+                    &mut text_inputs,
+                    TextInputField::WorldName,
+                    menu_e,
+                    &parents,
+                );
+                let name = name_input
+                    .as_ref()
+                    .map_or_else(|| asset_name(world), |input| input.value.clone());
+
+                match rename_world(&asset_server, world, &name, &locale) {
+                    Ok(renamed) => {
+                        context.image_cache.evict(world);
+                        context.menu_actions.send(MenuAction::RenameWorld {
+                            world: world.clone(),
+                            renamed,
+                        });
+                        context.rebuilds.send(Rebuild::Dynamic);
+                        state.pop()
+                    }
+                    Err(err) => {
+                        match &mut name_input {
+                            Some(input) => input.error = Some(err),
+                            None => context
+                                .toasts
+                                .send(MenuToast::new(ToastSeverity::Error, err)),
+                        }
+                        Ok(())
+                    }
+                }
+            }
+            Action::DuplicateWorld(_) | Action::ExtractWorld(_) if context.world_copy.is_some() => {
+                context.toasts.send(MenuToast::new(
+                    ToastSeverity::Warning,
+                    locale.get("menu.copy_in_progress"),
+                ));
+                Ok(())
+            }
+            Action::DuplicateWorld(world) => {
+                match duplicate_world(&asset_server, &context.paths, &context.thread_pool, world) {
+                    Ok(world_copy) => {
+                        commands.insert_resource(world_copy);
+                        open_menu(
+                            &mut commands,
+                            &mut state,
+                            copying_world_menu("menu.copying_world"),
+                        )
+                    }
+                    Err(err) => {
+                        context
+                            .toasts
+                            .send(MenuToast::new(ToastSeverity::Error, err));
+                        Ok(())
+                    }
+                }
+            }
+            Action::ExtractWorld(archive) => {
+                match extract_world(&asset_server, &context.paths, &context.thread_pool, archive) {
+                    Ok(world_copy) => {
+                        commands.insert_resource(world_copy);
+                        open_menu(
+                            &mut commands,
+                            &mut state,
+                            copying_world_menu("menu.extracting_world"),
+                        )
+                    }
+                    Err(err) => {
+                        context
+                            .toasts
+                            .send(MenuToast::new(ToastSeverity::Error, err));
+                        Ok(())
+                    }
+                }
+            }
+            Action::CancelCopy => {
+                if let Some(world_copy) = &context.world_copy {
+                    world_copy.progress.cancel.store(true, Ordering::Relaxed);
+                }
+                Ok(())
+            }
+            Action::CancelListing(list_e) => {
+                // It may have finished loading in the meantime
+                if context.menus.listings.get(*list_e).is_ok() {
+                    let mut list_commands = commands.entity(*list_e);
+                    list_commands.despawn_descendants();
+                    list_commands.remove_bundle::<(AssetListSource, AssetList, AssetListWait)>();
+                }
+                Ok(())
+            }
+            Action::SelectTab(tabs_e, index) => {
+                context.tab_selects.send(SelectTab {
+                    tabs: *tabs_e,
+                    index: *index,
+                });
+                Ok(())
+            }
+            Action::SelectWorld(world) => {
+                if !context.selection.0.remove(world) {
+                    context.selection.0.insert(world.clone());
+                }
+                Ok(())
+            }
+            Action::DeleteSelectedWorlds if context.selection.0.is_empty() => {
+                context.toasts.send(MenuToast::new(
+                    ToastSeverity::Warning,
+                    locale.get("menu.no_worlds_selected"),
+                ));
+                Ok(())
+            }
+            Action::DeleteSelectedWorlds => {
+                let mut worlds = context.selection.0.iter().cloned().collect::<Vec<_>>();
+                worlds.sort();
+                open_menu(&mut commands, &mut state, delete_worlds_menu(worlds))
+            }
+            Action::DeleteWorlds(worlds) => {
+                let mut deleted = Vec::new();
+                for world in worlds {
+                    match trash_world(&asset_server, &context.paths, world) {
+                        Ok(trashed) => {
+                            context.image_cache.evict(world);
+                            deleted.push((world.clone(), trashed));
+                        }
+                        Err(err) => context
+                            .toasts
+                            .send(MenuToast::new(ToastSeverity::Error, err)),
+                    }
+                }
+
+                if !deleted.is_empty() {
+                    context.toasts.send(
+                        MenuToast::new(
+                            ToastSeverity::Info,
+                            locale.count("menu.deleted_worlds", deleted.len()),
+                        )
+                        .with_action("menu.undo", Action::RestoreWorlds(deleted.clone())),
+                    );
+                    context.menu_actions.send(MenuAction::DeleteWorlds(
+                        deleted.into_iter().map(|(world, _)| world).collect(),
+                    ));
+                }
+                context.selection.0.clear();
+                context.rebuilds.send(Rebuild::Dynamic);
+                state.pop()
+            }
+            Action::RestoreWorlds(worlds) => {
+                let mut restored = Vec::new();
+                for (world, trashed) in worlds {
+                    match restore_world(&asset_server, world, trashed) {
+                        Ok(()) => restored.push(world.clone()),
+                        Err(err) => context
+                            .toasts
+                            .send(MenuToast::new(ToastSeverity::Error, err)),
+                    }
+                }
+
+                if !restored.is_empty() {
+                    context.toasts.send(MenuToast::new(
+                        ToastSeverity::Info,
+                        locale.count("menu.restored_worlds", restored.len()),
+                    ));
+                    context
+                        .menu_actions
+                        .send(MenuAction::RestoreWorlds(restored));
+                }
+                context.rebuilds.send(Rebuild::Dynamic);
+                Ok(())
+            }
+            Action::ToggleFavorite(world) => {
+                if let Err(err) = toggle_favorite(&asset_server, world) {
+                    context
+                        .toasts
+                        .send(MenuToast::new(ToastSeverity::Error, err));
+                }
+                context.rebuilds.send(Rebuild::Dynamic);
+                Ok(())
+            }
+            Action::CloseWorldManager => {
+                context.selection.0.clear();
+                state.pop()
+            }
+            // Going back to the menu below first would only ask again
+            Action::Confirmed(action) if matches!(**action, Action::Quit) => {
+                context.menu_actions.send(MenuAction::Quit);
+                context.exits.send(AppExit);
+                Ok(())
+            }
+            Action::Confirmed(action) => {
+                commands.insert_resource(ConfirmedAction {
+                    action: (**action).clone(),
+                    depth: menu_es.as_ref().map_or(0, |menu_es| menu_es.len()),
+                });
+                state.pop()
+            }
+            Action::Resume => state.pop(),
+            Action::QuitToMenu => {
+                context.returns.send(ReturnToMenu);
+                state.pop()
+            }
+            Action::Custom(id) => {
+                match context.handlers.0.get(id) {
+                    Some(handler) => {
+                        let handler = handler.clone();
+                        commands.add(move |world: &mut World| handler(world));
+                    }
+                    None => context.toasts.send(MenuToast::new(
+                        ToastSeverity::Warning,
+                        locale.get("menu.no_handler").replace("{id}", id),
+                    )),
+                }
+                Ok(())
+            }
+            Action::Setting(SettingButton::KeyBinding(key)) => {
+                context.rebinding.0 = Some(*key);
+                Ok(())
+            }
+            Action::Setting(setting) => {
+                setting.change(&mut settings, &locale);
+                Ok(())
+            }
+            Action::CloseSettings if *settings != context.saved_settings.0 => {
+                open_menu(&mut commands, &mut state, unsaved_settings_menu())
+            }
+            Action::CloseSettings => state.pop(),
+            Action::SaveSettings => {
+                settings.save();
+                context.saved_settings.0 = settings.clone();
+                commands.insert_resource(LeavingSettings);
+                state.pop()
+            }
+            Action::DiscardSettings => {
+                *settings = context.saved_settings.0.clone();
+                commands.insert_resource(LeavingSettings);
+                state.pop()
+            }
+        };
+
+        if let Err(err) = state_change {
+            warn!("Couldn't change menus: {}", err);
+            context.toasts.send(MenuToast::new(
+                ToastSeverity::Error,
+                locale
+                    .get("menu.change_failed")
+                    .replace("{error}", &err.to_string()),
+            ));
+        }
+    }
+}
+
+// Left by the unsaved settings prompt, which pops itself and then the settings below it
+struct LeavingSettings;
+
+fn leave_settings(
+    mut commands: Commands,
+    leaving: Option<Res<LeavingSettings>>,
+    mut state: ResMut<State<GameState>>,
+) {
+    if leaving.is_none() {
+        return;
+    }
+
+    commands.remove_resource::<LeavingSettings>();
+    if let Err(err) = state.pop() {
+        error!("Couldn't leave the settings: {}", err);
+    }
+}
+
+fn top_menu_input<'a>(
+    text_inputs: &'a mut Query<(Entity, &mut MenuTextInput)>,
+    field: TextInputField,
+    menu_e: Option<Entity>,
+    parents: &Query<&Parent>,
+) -> Option<Mut<'a, MenuTextInput>> {
+    let menu_e = menu_e?;
+    text_inputs
+        .iter_mut()
+        .find(|(input_e, input)| input.field == field && in_menu(*input_e, menu_e, parents))
+        .map(|(_, input)| input)
+}
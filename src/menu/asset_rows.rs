@@ -0,0 +1,920 @@
+use std::{
+    cmp::Reverse,
+    collections::HashSet,
+    ffi::OsString,
+    fs,
+    path::{Path, PathBuf},
+    time::{Duration, SystemTime},
+    vec,
+};
+
+use bevy::{
+    prelude::*,
+    tasks::{AsyncComputeTaskPool, Task, TaskPool},
+    ui::FocusPolicy,
+};
+use futures_lite::future::{block_on, poll_once};
+use serde::Deserialize;
+
+use crate::{
+    archive::{check_archive, ArchiveKind},
+    game::GAME_MANIFEST_FILE,
+    locale::Locale,
+    state::GameState,
+    world::{WorldMeta, WORLD_META_FILE, WORLD_THUMBNAIL_FILE},
+};
+
+use super::{
+    asset_fs_path, asset_name, format_date, format_play_time, in_menu, menu_root, read_asset_dir,
+    text_input::{MenuTextInput, TextInputField},
+    toast::{MenuToast, ToastSeverity},
+    world_actions::{new_world_menu, rename_world_menu},
+    Action, Fonts, MenuBuilder, MenuButton, MenuButtonRow, MenuButtonsBuilder, MenuEs, MenuGrid,
+    MenuImageCache, MenuImages, MenuPaths, MenuScroll, MenuSwap, MenuTheme, MenuTitle,
+    MenuTitleSize, ReplaceMenu, RowWidth, StagedMenu,
+};
+
+// Lists worlds and games into menus in the background, a page at a time
+pub struct AssetRowsPlugin;
+
+impl Plugin for AssetRowsPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_system_set(
+            SystemSet::on_update(GameState::Menu)
+                .with_system(resolve_asset_lists)
+                .with_system(page_asset_lists.after(resolve_asset_lists))
+                .with_system(spin_list_spinners)
+                .with_system(filter_asset_rows),
+        );
+    }
+}
+
+#[derive(Clone, Copy, Debug, Deserialize)]
+pub enum AssetButtonAction {
+    Play,
+    // A single button for the first world in the list
+    Continue,
+    // Worlds opened in the editor
+    Edit,
+    CreateWorld,
+    // Worlds picked for batch actions
+    Manage,
+}
+
+impl AssetButtonAction {
+    pub fn assets_path(self, paths: &MenuPaths) -> &Path {
+        match self {
+            AssetButtonAction::Play
+            | AssetButtonAction::Continue
+            | AssetButtonAction::Edit
+            | AssetButtonAction::Manage => &paths.worlds,
+            AssetButtonAction::CreateWorld => &paths.games,
+        }
+    }
+
+    // A directory of assets rather than an asset, listed as a sub-menu of them
+    pub fn list_dirs(self, category: Option<&Path>, paths: &MenuPaths) -> Vec<PathBuf> {
+        paths.overlay(&match category {
+            Some(category) => self.assets_path(paths).join(category),
+            None => self.assets_path(paths).to_owned(),
+        })
+    }
+
+    pub fn is_category(self, path: &Path, asset_server: &AssetServer) -> bool {
+        read_asset_dir(asset_server, path).iter().any(|entry| {
+            asset_fs_path(asset_server, entry)
+                .is_some_and(|entry| entry.join(self.marker_file()).is_file())
+        })
+    }
+
+    fn category_row(self, path: &Path, sort: AssetSort) -> MenuButtonRow {
+        let name = asset_name(path);
+        MenuButtonRow::new(vec![MenuButton::new(
+            name.clone(),
+            Action::Menu(MenuBuilder {
+                title: MenuTitle {
+                    text: name.clone(),
+                    size: MenuTitleSize::Heading,
+                    font: None,
+                },
+                buttons: vec![
+                    MenuButtonsBuilder::PerAsset {
+                        action: self,
+                        sort,
+                        page_size: None,
+                        categories: false,
+                        category: Some(name.into()),
+                    },
+                    MenuButtonsBuilder::Static(MenuButtonRow::new(vec![MenuButton::new(
+                        "menu.back",
+                        Action::Back,
+                    )])),
+                ],
+                default_focus: None,
+                layout: default(),
+                cache: default(),
+            }),
+        )])
+    }
+
+    fn marker_file(self) -> &'static str {
+        match self {
+            AssetButtonAction::Play
+            | AssetButtonAction::Continue
+            | AssetButtonAction::Edit
+            | AssetButtonAction::Manage => WORLD_META_FILE,
+            AssetButtonAction::CreateWorld => GAME_MANIFEST_FILE,
+        }
+    }
+
+    pub fn is_valid(self, path: &Path, asset_server: &AssetServer) -> bool {
+        self.is_archive(path)
+            || asset_fs_path(asset_server, path)
+                .is_none_or(|path| path.join(self.marker_file()).is_file())
+    }
+
+    // Archives are only listed to be played, after they're extracted
+    fn is_archive(self, path: &Path) -> bool {
+        matches!(self, AssetButtonAction::Play) && ArchiveKind::of(path).is_some()
+    }
+
+    // `Continue` goes by when worlds were played, so a favorite doesn't take its place
+    fn pins_favorites(self) -> bool {
+        matches!(
+            self,
+            AssetButtonAction::Play | AssetButtonAction::Edit | AssetButtonAction::Manage
+        )
+    }
+
+    fn action(self, path: PathBuf) -> Action {
+        match self {
+            AssetButtonAction::Play | AssetButtonAction::Continue => Action::Play(path),
+            AssetButtonAction::Edit => Action::Edit(path),
+            AssetButtonAction::Manage => Action::SelectWorld(path),
+            AssetButtonAction::CreateWorld => Action::Menu(new_world_menu(path)),
+        }
+    }
+
+    fn subtitle(self, path: &Path, meta: Option<&WorldMeta>, template: &str) -> Option<String> {
+        match self {
+            AssetButtonAction::Play | AssetButtonAction::Edit | AssetButtonAction::Manage => {
+                let meta = meta?;
+                Some(
+                    template
+                        .replace("{date}", &format_date(meta.last_played?))
+                        .replace("{play_time}", &format_play_time(meta.play_time)),
+                )
+            }
+            AssetButtonAction::Continue => Some(asset_name(path)),
+            AssetButtonAction::CreateWorld => None,
+        }
+    }
+
+    fn thumbnail(
+        self,
+        path: &Path,
+        asset_server: &AssetServer,
+        placeholder: &Handle<Image>,
+        image_cache: &MenuImageCache,
+    ) -> Option<Handle<Image>> {
+        if let AssetButtonAction::CreateWorld = self {
+            return None;
+        }
+
+        let thumbnail = path.join(WORLD_THUMBNAIL_FILE);
+        Some(
+            if asset_fs_path(asset_server, &thumbnail).is_none_or(|thumbnail| thumbnail.is_file()) {
+                image_cache.load(asset_server, thumbnail)
+            } else {
+                placeholder.clone()
+            },
+        )
+    }
+
+    fn row(
+        self,
+        path: PathBuf,
+        asset_server: &AssetServer,
+        subtitle: &str,
+        placeholder: &Handle<Image>,
+        image_cache: &MenuImageCache,
+    ) -> MenuButtonRow {
+        if self.is_archive(&path) {
+            return MenuButtonRow::new(vec![MenuButton {
+                icon: Some(placeholder.clone()),
+                confirm: Some("menu.confirm_extract".to_string()),
+                ..MenuButton::new(
+                    path.file_name()
+                        .map_or_else(String::new, |name| name.to_string_lossy().into_owned()),
+                    Action::ExtractWorld(path),
+                )
+            }]);
+        }
+
+        // Read once for both the subtitle and the favorite button
+        let meta = match self {
+            AssetButtonAction::Play | AssetButtonAction::Edit | AssetButtonAction::Manage => {
+                asset_fs_path(asset_server, &path).and_then(|path| WorldMeta::load(&path).ok())
+            }
+            AssetButtonAction::Continue | AssetButtonAction::CreateWorld => None,
+        };
+
+        let mut buttons = vec![MenuButton {
+            subtitle: self.subtitle(&path, meta.as_ref(), subtitle),
+            icon: self.thumbnail(&path, asset_server, placeholder, image_cache),
+            ..MenuButton::new(
+                match self {
+                    AssetButtonAction::Continue => "menu.continue".to_string(),
+                    _ => asset_name(&path),
+                },
+                self.action(path.clone()),
+            )
+        }];
+
+        if let AssetButtonAction::Play = self {
+            let favorite = meta.is_some_and(|meta| meta.favorite);
+            buttons.push(MenuButton {
+                small: true,
+                icon: Some(
+                    image_cache.load(
+                        asset_server,
+                        if favorite {
+                            FAVORITE_ICON_PATH
+                        } else {
+                            UNFAVORITE_ICON_PATH
+                        }
+                        .into(),
+                    ),
+                ),
+                ..MenuButton::new(
+                    if favorite { "menu.unpin" } else { "menu.pin" },
+                    Action::ToggleFavorite(path.clone()),
+                )
+            });
+            buttons.push(MenuButton {
+                small: true,
+                ..MenuButton::new("menu.rename", Action::Menu(rename_world_menu(path.clone())))
+            });
+            buttons.push(MenuButton {
+                small: true,
+                ..MenuButton::new("menu.duplicate", Action::DuplicateWorld(path))
+            });
+        }
+
+        MenuButtonRow::new(buttons)
+    }
+}
+
+#[derive(Clone, Copy, Debug, Deserialize)]
+pub enum AssetSort {
+    NameAscending,
+    // Entries without readable metadata go last, by name
+    ModifiedDescending,
+    LastPlayedDescending,
+}
+
+impl AssetSort {
+    // Favorites go first if they're pinned, each world's metadata read once for the whole key.
+    // Ties fall back to the name and then the path, so the order is the same every build.
+    pub fn sort(self, paths: &mut [PathBuf], pin_favorites: bool, asset_server: &AssetServer) {
+        paths.sort_by_cached_key(|path| {
+            let fs_path = asset_fs_path(asset_server, path);
+            let meta = match (self, pin_favorites) {
+                (AssetSort::LastPlayedDescending, _) | (_, true) => fs_path
+                    .as_ref()
+                    .and_then(|fs_path| WorldMeta::load(fs_path).ok()),
+                _ => None,
+            };
+            let favorite = pin_favorites && meta.as_ref().is_some_and(|meta| meta.favorite);
+            let recency = match self {
+                AssetSort::NameAscending => None,
+                AssetSort::ModifiedDescending => fs_path
+                    .and_then(|fs_path| fs::metadata(fs_path).ok())
+                    .and_then(|metadata| metadata.modified().ok())
+                    .and_then(|modified| modified.duration_since(SystemTime::UNIX_EPOCH).ok()),
+                AssetSort::LastPlayedDescending => meta
+                    .and_then(|meta| meta.last_played)
+                    .map(Duration::from_secs),
+            };
+            (
+                !favorite,
+                Reverse(recency),
+                asset_name(path).to_lowercase(),
+                path.clone(),
+            )
+        });
+    }
+}
+
+const FAVORITE_ICON_PATH: &str = "textures/star.png";
+const UNFAVORITE_ICON_PATH: &str = "textures/star_outline.png";
+
+pub const ASSET_PAGE_SIZE: usize = 16;
+// How far below the visible area the next page starts loading
+const ASSET_PAGE_PRELOAD: f32 = 200.;
+// Rows are built in this many tasks at most, so a large page doesn't open a file per world at once
+const ASSET_ROW_TASKS: usize = 8;
+
+#[derive(Clone, Component)]
+pub struct AssetListSource {
+    pub action: AssetButtonAction,
+    pub dirs: Vec<PathBuf>,
+    pub sort: AssetSort,
+    pub categories: bool,
+    pub subtitle: String,
+    pub page_size: usize,
+    pub columns: Option<usize>,
+}
+
+#[derive(Component)]
+pub struct AssetList(Task<AssetListing>);
+
+// Runs out when the list has loaded for `MenuTheme::slow_listing_secs`
+#[derive(Component)]
+pub struct AssetListWait(Timer);
+
+#[derive(Component)]
+struct ListSpinner;
+
+const LIST_SPINNER_SIZE: f32 = 20.;
+const LIST_SPINNER_GAP: f32 = 15.;
+// In radians per second
+const LIST_SPINNER_SPEED: f32 = std::f32::consts::TAU;
+
+pub type AssetRows = Vec<(String, MenuButtonRow)>;
+
+struct AssetListing {
+    rows: AssetRows,
+    remaining: vec::IntoIter<PathBuf>,
+    skipped: usize,
+    // Choosing from one game isn't a choice, so its menu skips to the step after
+    only_game: Option<PathBuf>,
+}
+
+// Paths whose rows haven't been built yet
+#[derive(Component)]
+struct AssetPages {
+    action: AssetButtonAction,
+    sort: AssetSort,
+    categories: bool,
+    subtitle: String,
+    page_size: usize,
+    columns: Option<usize>,
+    remaining: vec::IntoIter<PathBuf>,
+}
+
+#[derive(Component)]
+struct AssetPage(Task<AssetRows>);
+
+#[derive(Component)]
+struct AssetRow {
+    name: String,
+}
+
+#[allow(clippy::too_many_arguments)]
+pub fn build_asset_rows(
+    action: AssetButtonAction,
+    sort: AssetSort,
+    categories: bool,
+    paths: Vec<PathBuf>,
+    asset_server: &AssetServer,
+    subtitle: &str,
+    placeholder: &Handle<Image>,
+    image_cache: &MenuImageCache,
+    thread_pool: &TaskPool,
+) -> AssetRows {
+    let build_row = |path: PathBuf| {
+        let name = asset_name(&path).to_lowercase();
+        // Only categories made it into the listing without the marker file
+        let row = if categories && !action.is_valid(&path, asset_server) {
+            action.category_row(&path, sort)
+        } else {
+            action.row(path, asset_server, subtitle, placeholder, image_cache)
+        };
+        (name, row)
+    };
+
+    // `TaskPool::scope` waits on a lone task without helping run it, which never finishes if this is
+    // already running on the pool's only thread
+    if paths.len() <= 1 {
+        return paths.into_iter().map(build_row).collect();
+    }
+
+    // Each task builds a consecutive run of rows, and the scope returns them in the order they were
+    // spawned, so the rows keep their sorted order however the reads finish
+    let chunk_size = paths.len().div_ceil(ASSET_ROW_TASKS);
+    let mut paths = paths.into_iter();
+    thread_pool
+        .scope(|scope| {
+            while paths.len() > 0 {
+                let chunk = paths.by_ref().take(chunk_size).collect::<Vec<_>>();
+                scope.spawn(async move { chunk.into_iter().map(build_row).collect::<Vec<_>>() });
+            }
+        })
+        .into_iter()
+        .flatten()
+        .collect()
+}
+
+fn spawn_asset_rows(
+    commands: &mut Commands,
+    list_e: Entity,
+    rows: AssetRows,
+    columns: Option<usize>,
+    fonts: &Fonts,
+    theme: &MenuTheme,
+    locale: &Locale,
+) {
+    let mut row_es = Vec::new();
+    commands.entity(list_e).with_children(|parent| {
+        for (name, mut row) in rows {
+            let columns = match columns {
+                Some(columns) => columns,
+                None => {
+                    row_es.push((row.spawn(parent, fonts, theme, locale), name));
+                    continue;
+                }
+            };
+
+            row.layout.width = RowWidth::Fill;
+            let tile_e = parent
+                .spawn_bundle(NodeBundle {
+                    style: Style {
+                        flex_direction: FlexDirection::ColumnReverse,
+                        flex_shrink: 0.,
+                        size: Size::new(Val::Percent(100. / columns as f32), Val::Auto),
+                        ..default()
+                    },
+                    color: Color::NONE.into(),
+                    focus_policy: FocusPolicy::Pass,
+                    ..default()
+                })
+                .with_children(|parent| {
+                    row.spawn(parent, fonts, theme, locale);
+                })
+                .id();
+            row_es.push((tile_e, name));
+        }
+    });
+
+    for (row_e, name) in row_es {
+        commands.entity(row_e).insert(AssetRow { name });
+    }
+}
+
+impl AssetListSource {
+    pub fn spawn(
+        self,
+        parent: &mut ChildBuilder,
+        fonts: &Fonts,
+        theme: &MenuTheme,
+        locale: &Locale,
+    ) {
+        let columns = self.columns;
+        let mut list = parent.spawn_bundle(NodeBundle {
+            style: Style {
+                flex_direction: match columns {
+                    Some(_) => FlexDirection::Row,
+                    None => FlexDirection::ColumnReverse,
+                },
+                // Lines wrap upwards otherwise, since UI space is y-up
+                flex_wrap: match columns {
+                    Some(_) => FlexWrap::WrapReverse,
+                    None => FlexWrap::NoWrap,
+                },
+                align_items: AlignItems::Center,
+                flex_shrink: 0.,
+                size: Size::new(Val::Percent(100.), Val::Auto),
+                ..default()
+            },
+            color: Color::NONE.into(),
+            focus_policy: FocusPolicy::Pass,
+            ..default()
+        });
+        if let Some(columns) = columns {
+            list.insert(MenuGrid { columns });
+        }
+        list.insert(self).with_children(|parent| {
+            parent
+                .spawn_bundle(NodeBundle {
+                    style: Style {
+                        align_items: AlignItems::Center,
+                        margin: theme.item_margin,
+                        flex_shrink: 0.,
+                        ..default()
+                    },
+                    color: Color::NONE.into(),
+                    focus_policy: FocusPolicy::Pass,
+                    ..default()
+                })
+                .with_children(|parent| {
+                    parent
+                        .spawn_bundle(NodeBundle {
+                            style: Style {
+                                size: Size::new(
+                                    Val::Px(LIST_SPINNER_SIZE),
+                                    Val::Px(LIST_SPINNER_SIZE),
+                                ),
+                                margin: Rect {
+                                    right: Val::Px(LIST_SPINNER_GAP),
+                                    ..default()
+                                },
+                                ..default()
+                            },
+                            color: theme.title_color.into(),
+                            focus_policy: FocusPolicy::Pass,
+                            ..default()
+                        })
+                        .insert(ListSpinner);
+                    parent.spawn_bundle(TextBundle {
+                        text: Text::with_section(
+                            locale.get("menu.loading"),
+                            TextStyle {
+                                font: fonts.font.clone(),
+                                font_size: theme.button_text_size,
+                                color: theme.title_color,
+                            },
+                            default(),
+                        ),
+                        ..default()
+                    });
+                });
+        });
+    }
+}
+
+#[allow(clippy::too_many_arguments)]
+pub fn resolve_asset_lists(
+    mut commands: Commands,
+    sources: Query<(Entity, &AssetListSource), Added<AssetListSource>>,
+    mut lists: Query<(Entity, &AssetListSource, &mut AssetList, &mut AssetListWait)>,
+    asset_server: Res<AssetServer>,
+    thread_pool: Res<AsyncComputeTaskPool>,
+    fonts: Res<Fonts>,
+    theme: Res<MenuTheme>,
+    locale: Res<Locale>,
+    images: Res<MenuImages>,
+    image_cache: Res<MenuImageCache>,
+    mut toasts: EventWriter<MenuToast>,
+    menu_es: Option<Res<MenuEs>>,
+    staged: Query<(Entity, &StagedMenu)>,
+    parents: Query<&Parent>,
+    time: Res<Time>,
+    mut replaces: EventWriter<ReplaceMenu>,
+) {
+    for (source_e, source) in sources.iter() {
+        let asset_server = asset_server.clone();
+        let placeholder = images.placeholder.clone();
+        let image_cache = image_cache.clone();
+        let row_pool = (**thread_pool).clone();
+        let AssetListSource {
+            action,
+            dirs,
+            sort,
+            categories,
+            subtitle,
+            page_size,
+            ..
+        } = source.clone();
+
+        commands.entity(source_e).insert_bundle((
+            AssetListWait(Timer::from_seconds(theme.slow_listing_secs, false)),
+            AssetList(thread_pool.spawn(async move {
+                // Sorting needs every path, so they're all kept, but rows and what they load are
+                // only built a page at a time
+                let mut skipped = 0;
+                let mut paths = Vec::<PathBuf>::new();
+                let mut names = HashSet::<OsString>::new();
+                for dir in &dirs {
+                    debug!("Scanning {}", dir.display());
+                    for path in read_asset_dir(&asset_server, dir) {
+                        // Earlier directories shadow later ones, see `MenuPaths::overlay`
+                        let name = path.file_name().unwrap_or_default().to_owned();
+                        if names.contains(&name) {
+                            continue;
+                        }
+
+                        // A corrupt archive is skipped rather than failing when it's extracted
+                        if action.is_archive(&path) {
+                            if let Some(Err(err)) = asset_fs_path(&asset_server, &path)
+                                .map(|archive_path| check_archive(&archive_path))
+                            {
+                                warn!(
+                                    "Skipping {}, which can't be extracted: {}",
+                                    path.display(),
+                                    err
+                                );
+                                skipped += 1;
+                                continue;
+                            }
+                        }
+
+                        let listed = action.is_valid(&path, &asset_server)
+                            || categories && action.is_category(&path, &asset_server);
+                        if !listed {
+                            warn!(
+                                "Skipping {}, which has no {}",
+                                path.display(),
+                                action.marker_file()
+                            );
+                            skipped += 1;
+                            continue;
+                        }
+
+                        names.insert(name);
+                        paths.push(path);
+                    }
+                }
+
+                sort.sort(&mut paths, action.pins_favorites(), &asset_server);
+                if let AssetButtonAction::Continue = action {
+                    paths.truncate(1);
+                }
+                let only_game = match (action, paths.as_slice()) {
+                    (AssetButtonAction::CreateWorld, [game])
+                        if action.is_valid(game, &asset_server) =>
+                    {
+                        Some(game.clone())
+                    }
+                    _ => None,
+                };
+                let mut remaining = paths.into_iter();
+                let page = remaining.by_ref().take(page_size).collect();
+                AssetListing {
+                    rows: build_asset_rows(
+                        action,
+                        sort,
+                        categories,
+                        page,
+                        &asset_server,
+                        &subtitle,
+                        &placeholder,
+                        &image_cache,
+                        &row_pool,
+                    ),
+                    remaining,
+                    skipped,
+                    only_game,
+                }
+            })),
+        ));
+    }
+
+    for (list_e, source, mut list, mut wait) in lists.iter_mut() {
+        // Backing out mid-scan drops the scan, so it can't fill in the menu on its way out. Rebuilt
+        // menus aren't in the stack until they replace theirs.
+        let open = menu_es
+            .iter()
+            .flat_map(|menu_es| menu_es.iter())
+            .map(|entry| entry.entity);
+        let replacing = staged
+            .iter()
+            .filter_map(|(menu_e, staged)| match staged.swap {
+                MenuSwap::Replace { old } => {
+                    open.clone().any(|open_e| open_e == old).then_some(menu_e)
+                }
+                MenuSwap::Push => None,
+            });
+        if !open
+            .clone()
+            .chain(replacing)
+            .any(|menu_e| in_menu(list_e, menu_e, &parents))
+        {
+            commands
+                .entity(list_e)
+                .remove_bundle::<(AssetList, AssetListWait)>();
+            continue;
+        }
+
+        if wait.0.tick(time.delta()).just_finished() {
+            warn!(
+                "Listing {} is taking over {}s",
+                source
+                    .dirs
+                    .iter()
+                    .map(|dir| dir.display().to_string())
+                    .collect::<Vec<_>>()
+                    .join(", "),
+                theme.slow_listing_secs
+            );
+            commands.entity(list_e).with_children(|parent| {
+                parent.spawn_bundle(TextBundle {
+                    style: Style {
+                        align_self: AlignSelf::Center,
+                        flex_shrink: 0.,
+                        margin: theme.item_margin,
+                        size: Size::new(Val::Px(theme.paragraph_width), Val::Auto),
+                        ..default()
+                    },
+                    text: Text::with_section(
+                        locale.get("menu.slow_listing"),
+                        TextStyle {
+                            font: fonts.font.clone(),
+                            font_size: theme.paragraph_text_size,
+                            color: theme.title_color,
+                        },
+                        default(),
+                    ),
+                    ..default()
+                });
+                MenuButtonRow::new(vec![MenuButton::new(
+                    "menu.cancel",
+                    Action::CancelListing(list_e),
+                )])
+                .spawn(parent, &fonts, &theme, &locale);
+            });
+        }
+
+        if let Some(listing) = block_on(poll_once(&mut list.0)) {
+            // The menu stays loading until it's replaced
+            if let Some(game) = listing.only_game {
+                commands
+                    .entity(list_e)
+                    .remove_bundle::<(AssetList, AssetListWait)>();
+                replaces.send(ReplaceMenu {
+                    menu: menu_root(list_e, &parents),
+                    with: new_world_menu(game),
+                });
+                continue;
+            }
+
+            // The Play list reports the same worlds, so Continue stays quiet
+            if listing.skipped > 0 && !matches!(source.action, AssetButtonAction::Continue) {
+                toasts.send(MenuToast::new(
+                    ToastSeverity::Warning,
+                    locale
+                        .count("menu.skipped_entries", listing.skipped)
+                        .replace(
+                            "{dirs}",
+                            &source
+                                .dirs
+                                .iter()
+                                .map(|dir| dir.display().to_string())
+                                .collect::<Vec<_>>()
+                                .join(", "),
+                        ),
+                ));
+            }
+
+            let mut list_commands = commands.entity(list_e);
+            list_commands.despawn_descendants();
+            list_commands
+                .remove_bundle::<(AssetListSource, AssetList, AssetListWait)>()
+                .insert(AssetPages {
+                    action: source.action,
+                    sort: source.sort,
+                    categories: source.categories,
+                    subtitle: source.subtitle.clone(),
+                    page_size: source.page_size,
+                    columns: source.columns,
+                    remaining: listing.remaining,
+                });
+            spawn_asset_rows(
+                &mut commands,
+                list_e,
+                listing.rows,
+                source.columns,
+                &fonts,
+                &theme,
+                &locale,
+            );
+        }
+    }
+}
+
+fn spin_list_spinners(mut spinners: Query<&mut Transform, With<ListSpinner>>, time: Res<Time>) {
+    for mut transform in spinners.iter_mut() {
+        transform.rotate(Quat::from_rotation_z(
+            -LIST_SPINNER_SPEED * time.delta_seconds(),
+        ));
+    }
+}
+
+type PagedAssetLists<'w, 's, 'a> =
+    Query<'w, 's, (Entity, &'a mut AssetPages, &'a Node, &'a GlobalTransform), Without<AssetPage>>;
+
+// Builds the next page once the end of a list nears the visible area, or while it's searched
+#[allow(clippy::too_many_arguments)]
+fn page_asset_lists(
+    mut commands: Commands,
+    mut lists: PagedAssetLists,
+    mut pages: Query<(Entity, &AssetPages, &mut AssetPage)>,
+    inputs: Query<(Entity, &MenuTextInput)>,
+    parents: Query<&Parent>,
+    scrolls: Query<&Parent, With<MenuScroll>>,
+    containers: Query<(&Node, &GlobalTransform)>,
+    asset_server: Res<AssetServer>,
+    thread_pool: Res<AsyncComputeTaskPool>,
+    fonts: Res<Fonts>,
+    theme: Res<MenuTheme>,
+    locale: Res<Locale>,
+    images: Res<MenuImages>,
+    image_cache: Res<MenuImageCache>,
+) {
+    for (list_e, mut list, node, transform) in lists.iter_mut() {
+        if list.remaining.len() == 0 {
+            commands.entity(list_e).remove::<AssetPages>();
+            continue;
+        }
+
+        // Not laid out yet, or in a hidden menu
+        if node.size.y == 0. {
+            continue;
+        }
+
+        let menu_e = menu_root(list_e, &parents);
+        let searched = inputs.iter().any(|(input_e, input)| {
+            input.field == TextInputField::Search
+                && !input.value.is_empty()
+                && menu_root(input_e, &parents) == menu_e
+        });
+
+        let mut scroll_e = list_e;
+        let mut near_view = false;
+        while let Ok(parent) = parents.get(scroll_e) {
+            scroll_e = **parent;
+            if let Ok(container_e) = scrolls.get(scroll_e) {
+                let (container, container_transform) = containers.get(**container_e).unwrap();
+                near_view = transform.translation.y - node.size.y / 2.
+                    > container_transform.translation.y
+                        - container.size.y / 2.
+                        - ASSET_PAGE_PRELOAD;
+                break;
+            }
+        }
+
+        if !searched && !near_view {
+            continue;
+        }
+
+        let page_size = list.page_size;
+        let page = list.remaining.by_ref().take(page_size).collect();
+        let (action, sort, categories) = (list.action, list.sort, list.categories);
+        let subtitle = list.subtitle.clone();
+        let asset_server = asset_server.clone();
+        let placeholder = images.placeholder.clone();
+        let image_cache = image_cache.clone();
+        let row_pool = (**thread_pool).clone();
+        commands
+            .entity(list_e)
+            .insert(AssetPage(thread_pool.spawn(async move {
+                build_asset_rows(
+                    action,
+                    sort,
+                    categories,
+                    page,
+                    &asset_server,
+                    &subtitle,
+                    &placeholder,
+                    &image_cache,
+                    &row_pool,
+                )
+            })));
+    }
+
+    for (list_e, list, mut page) in pages.iter_mut() {
+        if let Some(rows) = block_on(poll_once(&mut page.0)) {
+            commands.entity(list_e).remove::<AssetPage>();
+            spawn_asset_rows(
+                &mut commands,
+                list_e,
+                rows,
+                list.columns,
+                &fonts,
+                &theme,
+                &locale,
+            );
+        }
+    }
+}
+
+fn filter_asset_rows(
+    inputs: Query<(Entity, &MenuTextInput, ChangeTrackers<MenuTextInput>)>,
+    mut rows: Query<(Entity, &AssetRow, &mut Style, ChangeTrackers<AssetRow>)>,
+    parents: Query<&Parent>,
+) {
+    for (input_e, input, input_tracker) in inputs.iter() {
+        if input.field != TextInputField::Search {
+            continue;
+        }
+
+        let menu_e = menu_root(input_e, &parents);
+        let query = input.value.to_lowercase();
+        for (row_e, row, mut style, row_tracker) in rows.iter_mut() {
+            if !input_tracker.is_changed() && !row_tracker.is_added()
+                || !in_menu(row_e, menu_e, &parents)
+            {
+                continue;
+            }
+
+            let display = if row.name.contains(&query) {
+                Display::Flex
+            } else {
+                Display::None
+            };
+            if style.display != display {
+                style.display = display;
+            }
+        }
+    }
+}
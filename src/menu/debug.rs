@@ -0,0 +1,172 @@
+use std::fmt::Write;
+
+use bevy::{prelude::*, ui::FocusPolicy};
+
+use crate::state::GameState;
+
+use super::{in_menu, Fonts, MenuEs, MenuSource, MenuTrail, StagedMenu};
+
+// Lists the menu stack in a corner, for menus that seem stuck or duplicated
+pub struct DebugMenuPlugin;
+
+impl Plugin for DebugMenuPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_system(toggle_menu_overlay)
+            .add_system(update_menu_overlay.after(toggle_menu_overlay));
+    }
+}
+
+const OVERLAY_KEY: KeyCode = KeyCode::F3;
+const OVERLAY_MARGIN: f32 = 10.;
+const OVERLAY_PADDING: f32 = 8.;
+const OVERLAY_TEXT_SIZE: f32 = 16.;
+
+#[derive(Component)]
+struct MenuOverlay {
+    text: Entity,
+}
+
+fn toggle_menu_overlay(
+    mut commands: Commands,
+    keys: Res<Input<KeyCode>>,
+    overlays: Query<Entity, With<MenuOverlay>>,
+    fonts: Res<Fonts>,
+) {
+    if !keys.just_pressed(OVERLAY_KEY) {
+        return;
+    }
+
+    if !overlays.is_empty() {
+        for overlay_e in overlays.iter() {
+            commands.entity(overlay_e).despawn_recursive();
+        }
+        return;
+    }
+
+    let text_e = commands
+        .spawn_bundle(TextBundle {
+            text: Text::with_section(
+                String::new(),
+                TextStyle {
+                    // Legible even if the locale's font is what's broken
+                    font: fonts.fallback.clone(),
+                    font_size: OVERLAY_TEXT_SIZE,
+                    color: Color::YELLOW,
+                },
+                default(),
+            ),
+            focus_policy: FocusPolicy::Pass,
+            ..default()
+        })
+        .id();
+    commands
+        .spawn_bundle(NodeBundle {
+            style: Style {
+                position_type: PositionType::Absolute,
+                position: Rect {
+                    left: Val::Px(OVERLAY_MARGIN),
+                    top: Val::Px(OVERLAY_MARGIN),
+                    ..default()
+                },
+                padding: Rect::all(Val::Px(OVERLAY_PADDING)),
+                ..default()
+            },
+            color: Color::rgba(0., 0., 0., 0.75).into(),
+            focus_policy: FocusPolicy::Pass,
+            ..default()
+        })
+        .insert(MenuOverlay { text: text_e })
+        .add_child(text_e);
+}
+
+type OverlayMenus<'w, 's, 'a> = Query<
+    'w,
+    's,
+    (
+        Entity,
+        &'a Style,
+        Option<&'a MenuTrail>,
+        Option<&'a StagedMenu>,
+    ),
+    With<MenuSource>,
+>;
+
+fn overlay_line(
+    menu_e: Entity,
+    menus: &OverlayMenus,
+    buttons: &Query<Entity, With<Button>>,
+    parents: &Query<&Parent>,
+) -> String {
+    let (style, trail, staged) = match menus.get(menu_e) {
+        Ok((_, style, trail, staged)) => (style, trail, staged),
+        Err(_) => return format!("{:?} despawned", menu_e),
+    };
+
+    let visibility = match (style.display, style.position.left) {
+        (Display::None, _) => "hidden".to_string(),
+        (_, Val::Percent(left)) if left != 0. => format!("sliding ({}%)", left),
+        _ => "shown".to_string(),
+    };
+    format!(
+        "{:?} \"{}\" {}{}, {} buttons",
+        menu_e,
+        trail.map_or("", |trail| &trail.title),
+        visibility,
+        if staged.is_some() { ", staged" } else { "" },
+        buttons
+            .iter()
+            .filter(|button_e| in_menu(*button_e, menu_e, parents))
+            .count()
+    )
+}
+
+fn update_menu_overlay(
+    overlays: Query<&MenuOverlay>,
+    mut texts: Query<&mut Text>,
+    menu_es: Option<Res<MenuEs>>,
+    menus: OverlayMenus,
+    buttons: Query<Entity, With<Button>>,
+    parents: Query<&Parent>,
+    state: Res<State<GameState>>,
+) {
+    let overlay = match overlays.iter().next() {
+        Some(overlay) => overlay,
+        None => return,
+    };
+
+    let mut info = format!("{:?}\n", state.current());
+    let stack = menu_es
+        .iter()
+        .flat_map(|menu_es| menu_es.iter())
+        .map(|entry| entry.entity)
+        .collect::<Vec<_>>();
+    if menu_es.is_none() {
+        info.push_str("No MenuEs\n");
+    }
+    for (i, menu_e) in stack.iter().enumerate() {
+        writeln!(
+            info,
+            "{}: {}",
+            i,
+            overlay_line(*menu_e, &menus, &buttons, &parents)
+        )
+        .unwrap();
+    }
+
+    // Menus outside the stack are on their way in, or leaked
+    for (menu_e, ..) in menus.iter().filter(|(menu_e, ..)| !stack.contains(menu_e)) {
+        writeln!(
+            info,
+            "untracked: {}",
+            overlay_line(menu_e, &menus, &buttons, &parents)
+        )
+        .unwrap();
+    }
+
+    if let Ok(mut text) = texts.get_mut(overlay.text) {
+        let section = &mut text.sections[0];
+        if section.value != info.trim_end() {
+            section.value = info.trim_end().to_string();
+        }
+    }
+}
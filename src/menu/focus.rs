@@ -0,0 +1,374 @@
+use bevy::{ecs::system::SystemParam, prelude::*};
+
+use crate::{settings::Settings, state::GameState};
+
+use super::{
+    asset_rows::AssetListSource, in_menu, KeyRebinding, MenuColumns, MenuEs, MenuGrid, MenuLayout,
+    MenuScroll, MenuSounds, MenuTheme,
+};
+
+pub struct FocusPlugin;
+
+impl Plugin for FocusPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<NavInput>().add_system_set(
+            SystemSet::on_update(GameState::Menu)
+                .with_system(read_nav_input)
+                .with_system(navigate_focus.after(read_nav_input)),
+        );
+    }
+}
+
+#[derive(Default)]
+pub struct NavInput {
+    pub up: bool,
+    pub down: bool,
+    pub left: bool,
+    pub right: bool,
+    pub first: bool,
+    pub last: bool,
+    pub page_up: bool,
+    pub page_down: bool,
+    pub activate: bool,
+    pub back: bool,
+}
+
+#[derive(Component)]
+pub struct Focusable;
+
+#[derive(Component)]
+pub struct Disabled;
+
+#[derive(Component)]
+pub struct Focused;
+
+pub fn read_nav_input(
+    keys: Res<Input<KeyCode>>,
+    gamepad_buttons: Res<Input<GamepadButton>>,
+    settings: Res<Settings>,
+    rebinding: Res<KeyRebinding>,
+    mut nav: ResMut<NavInput>,
+) {
+    // The key pressed is the new binding, not navigation
+    if rebinding.0.is_some() {
+        *nav = default();
+        return;
+    }
+
+    let bindings = &settings.key_bindings;
+    let pressed = |key, button| {
+        keys.just_pressed(key)
+            || gamepad_buttons
+                .get_just_pressed()
+                .any(|gamepad_button| gamepad_button.1 == button)
+    };
+
+    *nav = NavInput {
+        up: pressed(bindings.up, GamepadButtonType::DPadUp),
+        down: pressed(bindings.down, GamepadButtonType::DPadDown),
+        left: pressed(bindings.left, GamepadButtonType::DPadLeft),
+        right: pressed(bindings.right, GamepadButtonType::DPadRight),
+        first: keys.just_pressed(bindings.first),
+        last: keys.just_pressed(bindings.last),
+        page_up: pressed(bindings.page_up, GamepadButtonType::LeftTrigger),
+        page_down: pressed(bindings.page_down, GamepadButtonType::RightTrigger),
+        activate: pressed(bindings.activate, GamepadButtonType::South)
+            || keys.just_pressed(KeyCode::NumpadEnter),
+        back: pressed(bindings.back, GamepadButtonType::East),
+    };
+}
+
+type FocusTargets<'w, 's> = Query<'w, 's, (), (With<Focusable>, Without<Disabled>)>;
+
+type FocusHovers<'w, 's, 'a> =
+    Query<'w, 's, (Entity, &'a Interaction), (Changed<Interaction>, With<Focusable>)>;
+
+pub fn focus_order(
+    menu_e: Entity,
+    children: &Query<&Children>,
+    styles: &Query<&Style>,
+    targets: &FocusTargets,
+) -> Vec<Entity> {
+    let mut order = Vec::new();
+    let mut stack = vec![menu_e];
+    while let Some(entity) = stack.pop() {
+        if styles
+            .get(entity)
+            .is_ok_and(|style| style.display == Display::None)
+        {
+            continue;
+        }
+
+        if targets.get(entity).is_ok() {
+            order.push(entity);
+        }
+
+        if let Ok(entity_children) = children.get(entity) {
+            stack.extend(entity_children.iter().rev());
+        }
+    }
+    order
+}
+
+#[derive(SystemParam)]
+struct ScrollPanels<'w, 's> {
+    panels: Query<'w, 's, &'static Parent, With<MenuScroll>>,
+    nodes: Query<'w, 's, &'static Node>,
+}
+
+impl ScrollPanels<'_, '_> {
+    // How many buttons like the focused one fit in the scrolling panel around it
+    fn page_size(&self, focused_e: Entity, parents: &Query<&Parent>) -> Option<usize> {
+        let mut panel_e = focused_e;
+        let container_e = loop {
+            if let Ok(container_e) = self.panels.get(panel_e) {
+                break **container_e;
+            }
+            panel_e = **parents.get(panel_e).ok()?;
+        };
+
+        let item = self.nodes.get(focused_e).ok()?.size.y;
+        let container = self.nodes.get(container_e).ok()?.size.y;
+        Some(((container / item) as usize).max(1))
+    }
+}
+
+// Layouts where focus moves in two dimensions
+#[derive(SystemParam)]
+struct NavLayouts<'w, 's> {
+    grids: Query<'w, 's, (&'static MenuGrid, &'static Children)>,
+    column_sets: Query<'w, 's, &'static Children, With<MenuColumns>>,
+}
+
+#[allow(clippy::too_many_arguments)]
+fn navigate_focus(
+    mut commands: Commands,
+    nav: Res<NavInput>,
+    menu_es: Option<ResMut<MenuEs>>,
+    children: Query<&Children>,
+    parents: Query<&Parent>,
+    styles: Query<&Style>,
+    targets: FocusTargets,
+    hovers: FocusHovers,
+    focused: Query<Entity, With<Focused>>,
+    loading: Query<Entity, With<AssetListSource>>,
+    layouts: NavLayouts,
+    scroll_panels: ScrollPanels,
+    audio: Res<Audio>,
+    sounds: Res<MenuSounds>,
+    settings: Res<Settings>,
+    theme: Res<MenuTheme>,
+) {
+    let mut menu_es = match menu_es {
+        Some(menu_es) => menu_es,
+        None => return,
+    };
+    let menu = match menu_es.last_mut() {
+        Some(menu) => menu,
+        None => return,
+    };
+    let menu_e = menu.entity;
+
+    let order = focus_order(menu_e, &children, &styles, &targets);
+    let current = order.iter().position(|entity| focused.get(*entity).is_ok());
+
+    let hovered = hovers
+        .iter()
+        .find(|(entity, interaction)| {
+            !matches!(interaction, Interaction::None) && order.contains(entity)
+        })
+        .map(|(entity, _)| entity);
+    let next = if let Some(hovered) = hovered {
+        Some(hovered)
+    } else if order.is_empty() {
+        None
+    } else {
+        let step = match menu.layout {
+            MenuLayout::Column => nav.down as isize - nav.up as isize,
+            MenuLayout::Row => nav.right as isize - nav.left as isize,
+        };
+        let wrap = theme.wrap_navigation;
+        let index = match (current, menu.focus) {
+            (Some(_), _) if nav.first => 0,
+            (Some(_), _) if nav.last => order.len() - 1,
+            // Paging stops at the ends of the list instead of wrapping
+            (Some(current), _) if nav.page_up || nav.page_down => {
+                let page = scroll_panels
+                    .page_size(order[current], &parents)
+                    .unwrap_or(order.len()) as isize;
+                let step = if nav.page_down { page } else { -page };
+                step_index(current as isize + step, order.len(), false)
+            }
+            (Some(current), _) => {
+                match grid_step(
+                    order[current],
+                    &order,
+                    &nav,
+                    &layouts.grids,
+                    &parents,
+                    &styles,
+                    wrap,
+                ) {
+                    Some(GridStep::Tile(target)) => target,
+                    Some(GridStep::Linear(step)) => {
+                        step_index(current as isize + step, order.len(), wrap)
+                    }
+                    None => match column_step(
+                        order[current],
+                        &order,
+                        &nav,
+                        &layouts.column_sets,
+                        &children,
+                        &parents,
+                    ) {
+                        Some(target) => target,
+                        None => step_index(current as isize + step, order.len(), wrap),
+                    },
+                }
+            }
+            (None, Some(focus)) if focus < order.len() => focus,
+            // The remembered button may be in a list that hasn't loaded yet
+            (None, Some(_))
+                if loading
+                    .iter()
+                    .any(|list_e| in_menu(list_e, menu_e, &parents)) =>
+            {
+                return
+            }
+            (None, _) => 0,
+        };
+        Some(order[index])
+    };
+
+    let next = match next {
+        Some(next) if Some(next) != current.map(|current| order[current]) => next,
+        _ => return,
+    };
+
+    for focused_e in focused.iter() {
+        if in_menu(focused_e, menu_e, &parents) || targets.get(focused_e).is_err() {
+            commands.entity(focused_e).remove::<Focused>();
+        }
+    }
+    commands.entity(next).insert(Focused);
+    menu.focus = order.iter().position(|entity| *entity == next);
+
+    if current.is_some() && hovered.is_none() {
+        audio.play_with_settings(
+            sounds.hover.clone(),
+            PlaybackSettings::ONCE.with_volume(settings.master_volume),
+        );
+    }
+}
+
+// Past either end of the focus order, wraps around to the other or stays at the end
+fn step_index(index: isize, len: usize, wrap: bool) -> usize {
+    if wrap {
+        index.rem_euclid(len as isize) as usize
+    } else {
+        index.clamp(0, len as isize - 1) as usize
+    }
+}
+
+enum GridStep {
+    // Index into the focus order
+    Tile(usize),
+    Linear(isize),
+}
+
+// Left and right step through a grid's buttons, up and down skip a row of tiles
+fn grid_step(
+    focused_e: Entity,
+    order: &[Entity],
+    nav: &NavInput,
+    grids: &Query<(&MenuGrid, &Children)>,
+    parents: &Query<&Parent>,
+    styles: &Query<&Style>,
+    wrap: bool,
+) -> Option<GridStep> {
+    let mut tile_e = focused_e;
+    let (grid, tiles) = loop {
+        let parent_e = **parents.get(tile_e).ok()?;
+        if let Ok(grid) = grids.get(parent_e) {
+            break grid;
+        }
+        tile_e = parent_e;
+    };
+
+    let step_x = nav.right as isize - nav.left as isize;
+    let step_y = nav.down as isize - nav.up as isize;
+    if step_y == 0 {
+        return Some(GridStep::Linear(step_x));
+    }
+
+    let tiles = tiles
+        .iter()
+        .copied()
+        .filter(|tile_e| {
+            styles
+                .get(*tile_e)
+                .is_ok_and(|style| style.display != Display::None)
+        })
+        .collect::<Vec<_>>();
+    let tile = tiles.iter().position(|entity| *entity == tile_e)?;
+    let in_tile = |tile_e: Entity| move |entity: &Entity| in_menu(*entity, tile_e, parents);
+
+    let target = tile as isize + step_y * grid.columns as isize;
+    if let Some(target_e) = usize::try_from(target)
+        .ok()
+        .and_then(|target| tiles.get(target))
+    {
+        return order
+            .iter()
+            .position(in_tile(*target_e))
+            .map(GridStep::Tile);
+    }
+
+    // Off the edge of the grid, so leave it past its first or last tile
+    let edge = if step_y > 0 {
+        order.iter().rposition(in_tile(*tiles.last()?))? as isize + 1
+    } else {
+        order.iter().position(in_tile(tiles[0]))? as isize - 1
+    };
+    Some(GridStep::Tile(step_index(edge, order.len(), wrap)))
+}
+
+// Left and right move to the same row of the next column over, or its last row if it's shorter
+fn column_step(
+    focused_e: Entity,
+    order: &[Entity],
+    nav: &NavInput,
+    column_sets: &Query<&Children, With<MenuColumns>>,
+    children: &Query<&Children>,
+    parents: &Query<&Parent>,
+) -> Option<usize> {
+    let step = nav.right as isize - nav.left as isize;
+    if step == 0 {
+        return None;
+    }
+
+    let mut row_e = focused_e;
+    let (column_e, columns) = loop {
+        let column_e = **parents.get(row_e).ok()?;
+        if let Ok(columns) = parents
+            .get(column_e)
+            .and_then(|set_e| column_sets.get(**set_e))
+        {
+            break (column_e, columns);
+        }
+        row_e = column_e;
+    };
+
+    let column = columns.iter().position(|entity| *entity == column_e)?;
+    let row = children
+        .get(column_e)
+        .ok()?
+        .iter()
+        .position(|entity| *entity == row_e)?;
+    let target_e = *columns.get(usize::try_from(column as isize + step).ok()?)?;
+    let rows = children.get(target_e).ok()?;
+    let target_row_e = *rows.get(row.min(rows.len().checked_sub(1)?))?;
+    order
+        .iter()
+        .position(|entity| in_menu(*entity, target_row_e, parents))
+}
@@ -0,0 +1,127 @@
+use bevy::prelude::*;
+
+use crate::{
+    locale::Locale,
+    state::{EditMode, GameState},
+    world::{CurrentWorld, LoadProgress, OpenWorld},
+};
+
+use super::{
+    spawn_progress_bar,
+    toast::{MenuToast, ToastSeverity},
+    Fonts, MenuTheme,
+};
+
+pub struct LoadingPlugin;
+
+impl Plugin for LoadingPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_system_set(
+            SystemSet::on_enter(GameState::LoadingWorld).with_system(init_loading_screen),
+        )
+        .add_system_set(
+            SystemSet::on_update(GameState::LoadingWorld)
+                .with_system(update_loading_bar)
+                .with_system(finish_loading),
+        )
+        .add_system_set(
+            SystemSet::on_exit(GameState::LoadingWorld).with_system(term_loading_screen),
+        );
+    }
+}
+
+#[derive(Component)]
+struct LoadingScreen;
+
+#[derive(Component)]
+struct LoadingBar;
+
+fn init_loading_screen(
+    mut commands: Commands,
+    fonts: Res<Fonts>,
+    theme: Res<MenuTheme>,
+    locale: Res<Locale>,
+) {
+    commands
+        .spawn_bundle(NodeBundle {
+            style: Style {
+                flex_direction: FlexDirection::ColumnReverse,
+                align_items: AlignItems::Center,
+                justify_content: JustifyContent::Center,
+                position_type: PositionType::Absolute,
+                size: Size::new(Val::Percent(100.), Val::Percent(100.)),
+                ..default()
+            },
+            color: Color::NONE.into(),
+            ..default()
+        })
+        .insert(LoadingScreen)
+        .with_children(|parent| {
+            parent.spawn_bundle(TextBundle {
+                style: Style {
+                    margin: theme.item_margin,
+                    ..default()
+                },
+                text: Text::with_section(
+                    locale.get("menu.loading"),
+                    TextStyle {
+                        font: fonts.font.clone(),
+                        font_size: theme.button_text_size,
+                        color: theme.title_color,
+                    },
+                    default(),
+                ),
+                ..default()
+            });
+
+            spawn_progress_bar(parent, &theme, LoadingBar);
+        });
+}
+
+fn update_loading_bar(progress: Res<LoadProgress>, mut bars: Query<&mut Style, With<LoadingBar>>) {
+    if !progress.is_changed() {
+        return;
+    }
+
+    for mut style in bars.iter_mut() {
+        style.size.width = Val::Percent(progress.fraction() * 100.);
+    }
+}
+
+fn finish_loading(
+    mut commands: Commands,
+    progress: Res<LoadProgress>,
+    locale: Res<Locale>,
+    mut toasts: EventWriter<MenuToast>,
+    mut state: ResMut<State<GameState>>,
+) {
+    // Both are tried again next frame if another state change was queued first
+    if let Some(err) = &progress.error {
+        if let Err(state_err) = state.set(GameState::MainMenu) {
+            warn!("Couldn't return to the menu: {}", state_err);
+            return;
+        }
+
+        error!("Couldn't load world: {}", err);
+        toasts.send(MenuToast::new(
+            ToastSeverity::Error,
+            locale.get("menu.load_world_failed").replace("{error}", err),
+        ));
+        commands.remove_resource::<OpenWorld>();
+        commands.remove_resource::<CurrentWorld>();
+        commands.remove_resource::<EditMode>();
+        return;
+    }
+
+    if progress.is_complete() {
+        if let Err(err) = state.set(GameState::Game) {
+            warn!("Couldn't start the game: {}", err);
+        }
+    }
+}
+
+fn term_loading_screen(mut commands: Commands, screens: Query<Entity, With<LoadingScreen>>) {
+    for screen_e in screens.iter() {
+        commands.entity(screen_e).despawn_recursive();
+    }
+}
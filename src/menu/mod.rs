@@ -0,0 +1,3709 @@
+mod access;
+mod actions;
+mod asset_rows;
+#[cfg(any(feature = "debug_menu", debug_assertions))]
+mod debug;
+mod focus;
+mod loading;
+mod music;
+mod pause;
+mod scale;
+mod slider;
+mod tabs;
+#[cfg(test)]
+mod tests;
+mod text_input;
+mod toast;
+mod toggle;
+#[cfg(feature = "watch_assets")]
+mod watch;
+mod world_actions;
+mod world_info;
+
+use std::{
+    collections::{HashMap, VecDeque},
+    f64::consts::TAU,
+    fs,
+    path::{Path, PathBuf},
+    sync::{Arc, Mutex},
+    vec,
+};
+
+use ab_glyph::{Font as _, ScaleFont};
+use bevy::{
+    asset::{FileAssetIo, LoadState},
+    ecs::{
+        schedule::StateError,
+        system::{Command, CommandQueue, SystemParam},
+    },
+    input::mouse::{MouseScrollUnit, MouseWheel},
+    prelude::*,
+    ui::FocusPolicy,
+    utils::tracing::field,
+};
+use bevy_asset_loader::AssetCollection;
+use futures_lite::future::block_on;
+use serde::Deserialize;
+
+use crate::{
+    game::GameManifest,
+    locale::Locale,
+    settings::{MenuKey, Settings, ThemePreset},
+    state::{BufferedState, GameState, OpeningGame},
+    world::{CurrentWorld, OpenWorld},
+};
+
+use self::{
+    access::{AccessPlugin, AccessibleLabel},
+    actions::{button_action, press_shortcuts, ActionsPlugin},
+    asset_rows::{
+        resolve_asset_lists, AssetButtonAction, AssetListSource, AssetRowsPlugin, AssetSort,
+        ASSET_PAGE_SIZE,
+    },
+    focus::{Disabled, FocusPlugin, Focusable, Focused},
+    loading::LoadingPlugin,
+    music::MusicPlugin,
+    pause::PausePlugin,
+    scale::{MenuNode, ScalePlugin},
+    slider::{MenuSlider, SliderAction, SliderPlugin},
+    tabs::{spawn_tabs, MenuTab, MenuTabs, TabsPlugin},
+    text_input::{MenuTextInput, TextInputField, TextInputPlugin},
+    toast::{MenuToast, ToastPlugin, ToastSeverity},
+    toggle::{MenuToggle, ToggleAction, TogglePlugin},
+    world_actions::{
+        manage_worlds_menu, poll_world_copy, show_world_selection, CopyBar, WorldActionsPlugin,
+        WorldCheckbox, WorldSelection,
+    },
+    world_info::WorldInfoPlugin,
+};
+
+pub use self::{
+    access::{AccessRole, MenuAnnouncement},
+    actions::{MenuActionHandler, MenuActionHandlers, MenuTelemetry, MenuTelemetryHandler},
+};
+
+#[cfg(any(feature = "debug_menu", debug_assertions))]
+use self::debug::DebugMenuPlugin;
+#[cfg(feature = "watch_assets")]
+use self::watch::WatchPlugin;
+
+#[derive(Default)]
+pub struct MenuPlugin {
+    config: MenuConfig,
+}
+
+impl MenuPlugin {
+    // `root` is built in code with `MenuBuilder::new`, or deserialized from RON like
+    // `MAIN_MENU_PATH` for lists, sliders and the rest. Its title is replaced by `title`.
+    pub fn new(root: MenuBuilder, title: impl Into<String>) -> Self {
+        Self {
+            config: MenuConfig {
+                root: Some(root),
+                title: title.into(),
+            },
+        }
+    }
+}
+
+impl Plugin for MenuPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_plugin(AccessPlugin)
+            .add_plugin(ActionsPlugin)
+            .add_plugin(AssetRowsPlugin)
+            .add_plugin(FocusPlugin)
+            .add_plugin(LoadingPlugin)
+            .add_plugin(MusicPlugin)
+            .add_plugin(PausePlugin)
+            .add_plugin(ScalePlugin)
+            .add_plugin(SliderPlugin)
+            .add_plugin(TabsPlugin)
+            .add_plugin(TextInputPlugin)
+            .add_plugin(ToastPlugin)
+            .add_plugin(TogglePlugin)
+            .add_plugin(WorldActionsPlugin)
+            .add_plugin(WorldInfoPlugin)
+            .init_resource::<Fonts>()
+            .init_resource::<MenuTheme>()
+            .init_resource::<MenuPaths>()
+            .init_resource::<MenuStackInfo>()
+            .init_resource::<MenuPath>()
+            .init_resource::<MenuImageCache>()
+            .init_resource::<KeyRebinding>()
+            .add_system_to_stage(CoreStage::PreUpdate, apply_theme_preset)
+            .insert_resource(self.config.clone())
+            .add_event::<Rebuild>()
+            .add_event::<ReplaceMenu>()
+            .add_event::<MenuAction>()
+            .add_event::<MenuSpawned>()
+            .add_event::<ReturnToMenu>()
+            .add_event::<OpenMenuPath>()
+            .add_event::<MenuWorldEvent>()
+            .add_startup_system(create_asset_dirs)
+            .add_system(fallback_fonts)
+            .add_system(select_locale_font.before(MenuSystem::Spawn))
+            .add_system(update_menu_stack_info.after(MenuSystem::Spawn))
+            .add_system(open_menu_path)
+            .add_system(push_menu_path.after(open_menu_path).after(poll_world_copy))
+            .add_system(send_world_events)
+            .add_system_set(
+                SystemSet::on_enter(GameState::MainMenu)
+                    .with_system(init_main_menu)
+                    .with_system(init_background),
+            )
+            .add_system_set(
+                SystemSet::on_enter(GameState::Menu)
+                    .with_system(init_menu.label(MenuSystem::Spawn)),
+            )
+            .add_system_set(
+                SystemSet::on_update(GameState::Menu)
+                    .with_system(
+                        capture_key_binding
+                            .after(focus::read_nav_input)
+                            .after(press_shortcuts),
+                    )
+                    .with_system(color_buttons)
+                    .with_system(update_cursor_icon)
+                    .with_system(blend_button_colors.after(color_buttons))
+                    .with_system(scale_buttons)
+                    .with_system(scroll_to_focus)
+                    .with_system(scroll_menu)
+                    .with_system(fallback_icons)
+                    .with_system(load_paragraphs)
+                    .with_system(animate_menus)
+                    .with_system(pulse_main_title)
+                    .with_system(restyle_main_menu)
+                    .with_system(resize_background)
+                    .with_system(relocalize_menus)
+                    .with_system(
+                        rebuild_menus
+                            .label(MenuSystem::Spawn)
+                            .after(button_action)
+                            .after(relocalize_menus),
+                    )
+                    .with_system(
+                        replace_menus
+                            .label(MenuSystem::Spawn)
+                            .after(resolve_asset_lists)
+                            .after(rebuild_menus),
+                    )
+                    .with_system(swap_staged_menus.after(rebuild_menus).after(replace_menus))
+                    .with_system(update_setting_text)
+                    .with_system(
+                        fit_button_labels
+                            .after(update_setting_text)
+                            .after(show_world_selection),
+                    ),
+            )
+            .add_system_set(
+                SystemSet::on_exit(GameState::Menu)
+                    .with_system(term_menu)
+                    .with_system(reset_cursor_icon),
+            )
+            .add_system_set(SystemSet::on_enter(GameState::LoadingWorld).with_system(term_menus))
+            .add_system_set(
+                SystemSet::on_enter(GameState::Game)
+                    .with_system(term_camera)
+                    .with_system(term_background)
+                    .with_system(term_footer),
+            )
+            .add_system_set(SystemSet::on_update(GameState::Game).with_system(return_to_menu));
+
+        // Always there in debug builds
+        #[cfg(any(feature = "debug_menu", debug_assertions))]
+        app.add_plugin(DebugMenuPlugin);
+        #[cfg(feature = "watch_assets")]
+        app.add_plugin(WatchPlugin);
+    }
+}
+
+const FONTS_PATH: &str = "fonts";
+const FONT_PATH: &str = "fonts/FiraSans-Bold.ttf";
+// Compiled in, so text stays legible if the font asset is missing
+const FALLBACK_FONT: &[u8] = include_bytes!("../../assets/fonts/FiraSans-Bold.ttf");
+
+// Not an `AssetCollection`, which would wait on a missing font forever
+pub struct Fonts {
+    // The locale's font, or `default`
+    font: Handle<Font>,
+    default: Handle<Font>,
+    fallback: Handle<Font>,
+    // Every font in `FONTS_PATH`, by file stem
+    named: HashMap<String, Handle<Font>>,
+}
+
+impl Fonts {
+    fn get(&self, name: &Option<String>) -> Handle<Font> {
+        name.as_ref()
+            .and_then(|name| self.named.get(name))
+            .unwrap_or(&self.font)
+            .clone()
+    }
+}
+
+impl FromWorld for Fonts {
+    fn from_world(world: &mut World) -> Self {
+        let asset_server = world.resource::<AssetServer>();
+        let default = asset_server.load(FONT_PATH);
+        let named = asset_server
+            .asset_io()
+            .read_directory(Path::new(FONTS_PATH))
+            .map(|paths| {
+                paths
+                    .filter(|path| {
+                        path.extension()
+                            .is_some_and(|extension| extension == "ttf" || extension == "otf")
+                    })
+                    .filter_map(|path| {
+                        let name = path.file_stem()?.to_string_lossy().into_owned();
+                        Some((name, asset_server.load(path.as_path())))
+                    })
+                    .collect()
+            })
+            .unwrap_or_default();
+        let fallback = world
+            .resource_mut::<Assets<Font>>()
+            .add(Font::try_from_bytes(FALLBACK_FONT.to_vec()).unwrap());
+        Self {
+            font: default.clone(),
+            default,
+            fallback,
+            named,
+        }
+    }
+}
+
+#[derive(AssetCollection)]
+pub struct MenuSounds {
+    #[asset(path = "sounds/hover.wav")]
+    hover: Handle<AudioSource>,
+    #[asset(path = "sounds/click.wav")]
+    click: Handle<AudioSource>,
+    #[asset(path = "sounds/menu_music.wav")]
+    music: Handle<AudioSource>,
+}
+
+// Keeps thumbnails loaded between builds of a list, so reopening a menu doesn't load them again
+#[derive(Clone, Default)]
+struct MenuImageCache(Arc<Mutex<HashMap<PathBuf, Handle<Image>>>>);
+
+impl MenuImageCache {
+    fn load(&self, asset_server: &AssetServer, path: PathBuf) -> Handle<Image> {
+        self.0
+            .lock()
+            .unwrap()
+            .entry(path)
+            .or_insert_with_key(|path| {
+                debug!("Loading image {}", path.display());
+                asset_server.load(path.as_path())
+            })
+            .clone()
+    }
+
+    // Drops the images under `dir`, so they can unload
+    fn evict(&self, dir: &Path) {
+        self.0
+            .lock()
+            .unwrap()
+            .retain(|path, _| !path.starts_with(dir));
+    }
+}
+
+#[derive(AssetCollection)]
+pub struct MenuImages {
+    #[asset(path = "textures/menu_background.png")]
+    background: Handle<Image>,
+    // For worlds without a thumbnail, and images that fail to load
+    #[asset(path = "textures/world_thumbnail.png")]
+    placeholder: Handle<Image>,
+}
+
+#[derive(Clone, Component, Deserialize)]
+enum Action {
+    Menu(MenuBuilder),
+    Back,
+    Play(PathBuf),
+    Edit(PathBuf),
+    CreateWorld(PathBuf),
+    RenameWorld(PathBuf),
+    DuplicateWorld(PathBuf),
+    // Extracts an archive in the world list into a world beside it
+    ExtractWorld(PathBuf),
+    // Pins or unpins the world at the top of world lists
+    ToggleFavorite(PathBuf),
+    // Stops the world duplication or extraction in progress
+    CancelCopy,
+    // Drops the slow listing of the list on this entity, leaving the rest of the menu
+    #[serde(skip)]
+    CancelListing(Entity),
+    Setting(SettingButton),
+    // Asks to save or discard unsaved changes before leaving the settings
+    CloseSettings,
+    SaveSettings,
+    DiscardSettings,
+    // Toggles the world in `WorldSelection`
+    SelectWorld(PathBuf),
+    // Asks to delete the selected worlds
+    DeleteSelectedWorlds,
+    DeleteWorlds(Vec<PathBuf>),
+    // Moves each world back from where `trash_world` put it
+    #[serde(skip)]
+    RestoreWorlds(Vec<(PathBuf, PathBuf)>),
+    // Clears the selection before leaving the world manager
+    CloseWorldManager,
+    // Closes the pause menu
+    Resume,
+    // Leaves the paused game for the main menu
+    QuitToMenu,
+    Quit,
+    // Runs the handler registered under this id in `MenuActionHandlers`
+    Custom(String),
+    // Closes a confirmation prompt and runs the action it was confirming
+    Confirmed(Box<Action>),
+    // Shows the tab at this index in the `MenuTabs`
+    #[serde(skip)]
+    SelectTab(Entity, usize),
+}
+
+impl Action {
+    fn changes_state(&self) -> bool {
+        !matches!(
+            self,
+            Action::Setting(_)
+                | Action::CancelCopy
+                | Action::CancelListing(_)
+                | Action::SelectWorld(_)
+                | Action::Custom(_)
+                | Action::SelectTab(..)
+                | Action::RestoreWorlds(_)
+                | Action::ToggleFavorite(_)
+        )
+    }
+
+    fn name(&self) -> &'static str {
+        match self {
+            Action::Menu(_) => "Menu",
+            Action::Back => "Back",
+            Action::Play(_) => "Play",
+            Action::Edit(_) => "Edit",
+            Action::CreateWorld(_) => "CreateWorld",
+            Action::RenameWorld(_) => "RenameWorld",
+            Action::DuplicateWorld(_) => "DuplicateWorld",
+            Action::ExtractWorld(_) => "ExtractWorld",
+            Action::ToggleFavorite(_) => "ToggleFavorite",
+            Action::CancelCopy => "CancelCopy",
+            Action::CancelListing(_) => "CancelListing",
+            Action::Setting(_) => "Setting",
+            Action::CloseSettings => "CloseSettings",
+            Action::SaveSettings => "SaveSettings",
+            Action::DiscardSettings => "DiscardSettings",
+            Action::SelectWorld(_) => "SelectWorld",
+            Action::DeleteSelectedWorlds => "DeleteSelectedWorlds",
+            Action::DeleteWorlds(_) => "DeleteWorlds",
+            Action::RestoreWorlds(_) => "RestoreWorlds",
+            Action::CloseWorldManager => "CloseWorldManager",
+            Action::Resume => "Resume",
+            Action::QuitToMenu => "QuitToMenu",
+            Action::Quit => "Quit",
+            Action::Custom(_) => "Custom",
+            Action::Confirmed(_) => "Confirmed",
+            Action::SelectTab(..) => "SelectTab",
+        }
+    }
+}
+
+const DATA_DIR_NAME: &str = "voxmod";
+
+// Where asset buttons look for worlds and games, relative to the asset folder. If there's a data
+// directory, the same paths under it are listed too, and new worlds are written there. Deleted
+// worlds go to `trash`, relative to the folder they were in unless it's absolute.
+#[derive(Clone)]
+pub struct MenuPaths {
+    pub games: PathBuf,
+    pub worlds: PathBuf,
+    pub data: Option<PathBuf>,
+    pub trash: PathBuf,
+}
+
+impl Default for MenuPaths {
+    fn default() -> Self {
+        Self {
+            games: "games".into(),
+            worlds: "worlds".into(),
+            data: dirs::data_dir().map(|data| data.join(DATA_DIR_NAME)),
+            trash: ".trash".into(),
+        }
+    }
+}
+
+impl MenuPaths {
+    fn writable(&self, dir: &Path) -> PathBuf {
+        match &self.data {
+            Some(data) => data.join(dir),
+            None => dir.to_owned(),
+        }
+    }
+
+    // The writable location comes first, so its entries shadow bundled ones with the same name
+    fn overlay(&self, dir: &Path) -> Vec<PathBuf> {
+        let mut dirs = vec![dir.to_owned()];
+        if let Some(data) = &self.data {
+            dirs.insert(0, data.join(dir));
+        }
+        dirs
+    }
+}
+
+// So a fresh install lists and creates worlds without a missing directory in the way
+fn create_asset_dirs(asset_server: Res<AssetServer>, paths: Res<MenuPaths>) {
+    for dir in [&paths.games, &paths.worlds] {
+        let dir = match asset_fs_path(&asset_server, &paths.writable(dir)) {
+            Some(dir) => dir,
+            None => continue,
+        };
+
+        if let Err(err) = fs::create_dir_all(&dir) {
+            warn!("Couldn't create {}: {}", dir.display(), err);
+        }
+    }
+}
+
+#[derive(Clone, Component, Copy, Debug, Deserialize)]
+enum SettingButton {
+    RenderDistance,
+    Language,
+    KeyBinding(MenuKey),
+    Theme,
+}
+
+// The key waiting for the next key press to be bound to it
+#[derive(Default)]
+struct KeyRebinding(Option<MenuKey>);
+
+const RENDER_DISTANCE_STEP: u32 = 2;
+const MAX_RENDER_DISTANCE: u32 = 16;
+
+impl SettingButton {
+    fn text(self, settings: &Settings, locale: &Locale, rebinding: &KeyRebinding) -> String {
+        match self {
+            SettingButton::RenderDistance => format!(
+                "{}: {}",
+                locale.get("settings.render_distance"),
+                settings.render_distance
+            ),
+            SettingButton::Language => format!(
+                "{}: {}",
+                locale.get("settings.language"),
+                locale.get("language.name")
+            ),
+            SettingButton::KeyBinding(key) => format!(
+                "{}: {}",
+                locale.get(key.label()),
+                if rebinding.0 == Some(key) {
+                    locale.get("settings.press_key")
+                } else {
+                    format!("{:?}", settings.key_bindings.get(key))
+                }
+            ),
+            SettingButton::Theme => format!(
+                "{}: {}",
+                locale.get("settings.theme"),
+                locale.get(settings.theme.label())
+            ),
+        }
+    }
+
+    fn change(self, settings: &mut Settings, locale: &Locale) {
+        match self {
+            SettingButton::Language => {
+                if let Some(language) = locale.next_language() {
+                    settings.language = language.clone();
+                }
+            }
+            SettingButton::RenderDistance => {
+                settings.render_distance = if settings.render_distance >= MAX_RENDER_DISTANCE {
+                    RENDER_DISTANCE_STEP
+                } else {
+                    settings.render_distance + RENDER_DISTANCE_STEP
+                }
+            }
+            // Bound by the next key press instead, in `capture_key_binding`
+            SettingButton::KeyBinding(_) => (),
+            SettingButton::Theme => settings.theme = settings.theme.next(),
+        }
+    }
+}
+
+const SECS_PER_DAY: u64 = 24 * 60 * 60;
+
+// Civil-from-days, see http://howardhinnant.github.io/date_algorithms.html
+fn format_date(secs: u64) -> String {
+    let days = (secs / SECS_PER_DAY) as i64 + 719468;
+    let era = days.div_euclid(146097);
+    let day_of_era = days.rem_euclid(146097);
+    let year_of_era =
+        (day_of_era - day_of_era / 1460 + day_of_era / 36524 - day_of_era / 146096) / 365;
+    let day_of_year = day_of_era - (365 * year_of_era + year_of_era / 4 - year_of_era / 100);
+    let shifted_month = (5 * day_of_year + 2) / 153;
+    let day = day_of_year - (153 * shifted_month + 2) / 5 + 1;
+    let month = if shifted_month < 10 {
+        shifted_month + 3
+    } else {
+        shifted_month - 9
+    };
+    let year = year_of_era + era * 400 + (month <= 2) as i64;
+
+    format!("{:04}-{:02}-{:02}", year, month, day)
+}
+
+fn format_play_time(secs: u64) -> String {
+    let minutes = secs / 60;
+    if minutes < 60 {
+        format!("{}m", minutes)
+    } else {
+        format!("{}h {}m", minutes / 60, minutes % 60)
+    }
+}
+
+fn confirm_menu(prompt: &str, confirm: &str, action: Action) -> MenuBuilder {
+    MenuBuilder {
+        title: MenuTitle {
+            text: prompt.to_string(),
+            size: MenuTitleSize::Heading,
+            font: None,
+        },
+        buttons: vec![
+            MenuButtonsBuilder::Static(MenuButtonRow::new(vec![MenuButton::new(
+                confirm.to_string(),
+                Action::Confirmed(Box::new(action)),
+            )])),
+            MenuButtonsBuilder::Static(MenuButtonRow::new(vec![MenuButton::new(
+                "menu.cancel",
+                Action::Back,
+            )])),
+        ],
+        // Cancel, like the delete prompt
+        default_focus: Some(1),
+        layout: default(),
+        cache: default(),
+    }
+}
+
+// Pushed over the settings, so leaving it leaves both
+fn unsaved_settings_menu() -> MenuBuilder {
+    MenuBuilder {
+        title: MenuTitle {
+            text: "menu.unsaved_settings".to_string(),
+            size: MenuTitleSize::Heading,
+            font: None,
+        },
+        buttons: vec![
+            MenuButtonsBuilder::Static(MenuButtonRow::new(vec![MenuButton::new(
+                "menu.save",
+                Action::SaveSettings,
+            )])),
+            MenuButtonsBuilder::Static(MenuButtonRow::new(vec![MenuButton::new(
+                "menu.discard",
+                Action::DiscardSettings,
+            )])),
+            MenuButtonsBuilder::Static(MenuButtonRow::new(vec![MenuButton::new(
+                "menu.cancel",
+                Action::Back,
+            )])),
+        ],
+        default_focus: None,
+        layout: default(),
+        cache: default(),
+    }
+}
+
+fn asset_name(path: &Path) -> String {
+    path.file_name()
+        .map(|name| name.to_string_lossy().into_owned())
+        .unwrap_or_default()
+}
+
+// Paths in the data directory are absolute, so they don't depend on the asset source
+fn asset_fs_path(asset_server: &AssetServer, path: &Path) -> Option<PathBuf> {
+    if path.is_absolute() {
+        return Some(path.to_owned());
+    }
+
+    asset_server
+        .asset_io()
+        .downcast_ref::<FileAssetIo>()
+        .map(|asset_io| asset_io.root_path().join(path))
+}
+
+// Sorted, so menus built from a directory don't depend on the order the platform lists it in
+fn read_asset_dir(asset_server: &AssetServer, dir: &Path) -> Vec<PathBuf> {
+    // `FileAssetIo` can't list directories outside the asset folder
+    let mut paths: Vec<_> = if dir.is_absolute() {
+        fs::read_dir(dir)
+            .map(|entries| {
+                entries
+                    .filter_map(|entry| Some(entry.ok()?.path()))
+                    .collect()
+            })
+            .unwrap_or_default()
+    } else {
+        asset_server
+            .asset_io()
+            .read_directory(dir)
+            .map(|paths| paths.collect())
+            .unwrap_or_default()
+    };
+    // Hidden entries, like worlds still being copied, aren't assets yet
+    paths.retain(|path| !asset_name(path).starts_with('.'));
+    paths.sort();
+    paths
+}
+
+fn asset_exists(asset_server: &AssetServer, path: &Path) -> bool {
+    asset_fs_path(asset_server, path).is_none_or(|path| path.is_dir())
+}
+
+#[derive(Clone, Deserialize)]
+enum MenuTitleSize {
+    MainTitle,
+    Heading,
+}
+
+#[derive(Clone, Deserialize)]
+struct MenuTitle {
+    text: String,
+    size: MenuTitleSize,
+    // A font in `FONTS_PATH`, by file stem
+    #[serde(default)]
+    font: Option<String>,
+}
+
+#[derive(Clone, Deserialize)]
+pub struct MenuButton {
+    #[serde(default)]
+    text: String,
+    #[serde(default)]
+    subtitle: Option<String>,
+    action: Action,
+    #[serde(default)]
+    disabled: bool,
+    #[serde(default)]
+    small: bool,
+    // Handles can't be deserialized without an asset server
+    #[serde(skip)]
+    icon: Option<Handle<Image>>,
+    #[serde(default)]
+    shortcut: Option<KeyCode>,
+    #[serde(default)]
+    overflow: LabelOverflow,
+    #[serde(default)]
+    font: Option<String>,
+    // A prompt to confirm the action through before it runs
+    #[serde(default)]
+    confirm: Option<String>,
+}
+
+impl MenuButton {
+    fn new(text: impl Into<String>, action: Action) -> Self {
+        Self {
+            text: text.into(),
+            subtitle: None,
+            action,
+            disabled: false,
+            small: false,
+            icon: None,
+            shortcut: None,
+            overflow: default(),
+            font: None,
+            confirm: None,
+        }
+    }
+
+    pub fn menu(text: impl Into<String>, menu: MenuBuilder) -> Self {
+        Self::new(text, Action::Menu(menu))
+    }
+
+    pub fn back(text: impl Into<String>) -> Self {
+        Self::new(text, Action::Back)
+    }
+
+    pub fn quit(text: impl Into<String>) -> Self {
+        Self::new(text, Action::Quit)
+    }
+
+    // Runs the handler registered under `id` in `MenuActionHandlers`, for actions outside the crate
+    pub fn custom(text: impl Into<String>, id: impl Into<String>) -> Self {
+        Self::new(text, Action::Custom(id.into()))
+    }
+}
+
+// What a label too long for its button does
+#[derive(Clone, Copy, Default, Deserialize)]
+enum LabelOverflow {
+    #[default]
+    Ellipsis,
+    // The button grows to fit
+    Wrap,
+}
+
+#[derive(Clone, Copy, Default, Deserialize)]
+enum RowAlign {
+    Start,
+    #[default]
+    Center,
+    SpaceBetween,
+}
+
+#[derive(Clone, Copy, Default, Deserialize)]
+enum RowWidth {
+    #[default]
+    Fixed,
+    Fill,
+}
+
+#[derive(Clone, Copy, Default, Deserialize)]
+struct RowLayout {
+    #[serde(default)]
+    align: RowAlign,
+    #[serde(default)]
+    width: RowWidth,
+    // Sized to its buttons instead of the menu's width, for menus laid out in a row
+    #[serde(skip)]
+    fit: bool,
+}
+
+#[derive(Clone, Copy, Default, Deserialize)]
+enum MenuLayout {
+    #[default]
+    Column,
+    Row,
+}
+
+#[derive(Clone, Deserialize)]
+pub struct MenuButtonRow {
+    buttons: Vec<MenuButton>,
+    #[serde(default)]
+    layout: RowLayout,
+}
+
+impl MenuButtonRow {
+    pub fn new(buttons: Vec<MenuButton>) -> Self {
+        Self {
+            buttons,
+            layout: default(),
+        }
+    }
+}
+
+#[derive(Clone, Deserialize)]
+enum MenuButtonsBuilder {
+    Static(MenuButtonRow),
+    PerAsset {
+        action: AssetButtonAction,
+        sort: AssetSort,
+        // Rows built at a time, defaults to `ASSET_PAGE_SIZE`
+        #[serde(default)]
+        page_size: Option<usize>,
+        // Lists directories without the marker file, but with assets in them, as sub-menus
+        #[serde(default)]
+        categories: bool,
+        // Lists a category's directory instead of the action's
+        #[serde(default)]
+        category: Option<PathBuf>,
+    },
+    // Per-asset buttons as tiles, wrapped into rows of `columns`
+    Grid {
+        action: AssetButtonAction,
+        sort: AssetSort,
+        columns: usize,
+        #[serde(default)]
+        page_size: Option<usize>,
+    },
+    Slider {
+        label: String,
+        min: f32,
+        max: f32,
+        value_action: SliderAction,
+    },
+    Toggle {
+        label: String,
+        toggle_action: ToggleAction,
+    },
+    Search {
+        placeholder: String,
+    },
+    // Wrapped body text, loaded from an asset file, with `{version}` replaced
+    Paragraph {
+        path: PathBuf,
+    },
+    // How far along the world duplication in progress is
+    CopyProgress,
+    // The name, version, author and description from a game's manifest
+    GameDetails {
+        game: PathBuf,
+    },
+    // Text inputs hold a validator, so they can only be built in code
+    #[serde(skip)]
+    TextInput(MenuTextInput),
+    Tabs(Vec<MenuTabBuilder>),
+}
+
+#[derive(Clone, Deserialize)]
+struct MenuTabBuilder {
+    label: String,
+    buttons: Vec<MenuButtonsBuilder>,
+}
+
+impl MenuButtonsBuilder {
+    fn is_dynamic(&self) -> bool {
+        match self {
+            MenuButtonsBuilder::PerAsset { .. } | MenuButtonsBuilder::Grid { .. } => true,
+            MenuButtonsBuilder::Tabs(tabs) => tabs
+                .iter()
+                .any(|tab| tab.buttons.iter().any(MenuButtonsBuilder::is_dynamic)),
+            _ => false,
+        }
+    }
+
+    fn build(&self, locale: &Locale, paths: &MenuPaths) -> MenuItem {
+        match self {
+            MenuButtonsBuilder::Static(row) => MenuItem::Buttons(row.clone()),
+            MenuButtonsBuilder::PerAsset {
+                action,
+                sort,
+                page_size,
+                categories,
+                category,
+            } => MenuItem::AssetList(AssetListSource {
+                action: *action,
+                dirs: action.list_dirs(category.as_deref(), paths),
+                sort: *sort,
+                categories: *categories,
+                subtitle: locale.get("world.last_played"),
+                page_size: page_size.unwrap_or(ASSET_PAGE_SIZE).max(1),
+                columns: None,
+            }),
+            MenuButtonsBuilder::Grid {
+                action,
+                sort,
+                columns,
+                page_size,
+            } => MenuItem::AssetList(AssetListSource {
+                action: *action,
+                dirs: paths.overlay(action.assets_path(paths)),
+                sort: *sort,
+                categories: false,
+                subtitle: locale.get("world.last_played"),
+                page_size: page_size.unwrap_or(ASSET_PAGE_SIZE).max(1),
+                columns: Some((*columns).max(1)),
+            }),
+            MenuButtonsBuilder::Slider {
+                label,
+                min,
+                max,
+                value_action,
+            } => MenuItem::Slider(MenuSlider {
+                label: label.clone(),
+                min: *min,
+                max: *max,
+                action: *value_action,
+            }),
+            MenuButtonsBuilder::Toggle {
+                label,
+                toggle_action,
+            } => MenuItem::Toggle(MenuToggle {
+                label: label.clone(),
+                action: *toggle_action,
+            }),
+            MenuButtonsBuilder::Search { placeholder } => MenuItem::TextInput(MenuTextInput::new(
+                TextInputField::Search,
+                placeholder.clone(),
+                None,
+            )),
+            MenuButtonsBuilder::Paragraph { path } => {
+                MenuItem::Paragraph(MenuParagraph::Asset(path.clone()))
+            }
+            MenuButtonsBuilder::GameDetails { game } => {
+                MenuItem::Paragraph(MenuParagraph::GameDetails(game.clone()))
+            }
+            MenuButtonsBuilder::CopyProgress => MenuItem::CopyProgress,
+            MenuButtonsBuilder::TextInput(input) => MenuItem::TextInput(input.clone()),
+            MenuButtonsBuilder::Tabs(tabs) => MenuItem::Tabs(
+                tabs.iter()
+                    .map(|tab| MenuTab {
+                        label: tab.label.clone(),
+                        items: tab
+                            .buttons
+                            .iter()
+                            .map(|buttons| buttons.build(locale, paths))
+                            .collect(),
+                    })
+                    .collect(),
+            ),
+        }
+    }
+}
+
+#[derive(Clone, Deserialize)]
+pub struct MenuBuilder {
+    title: MenuTitle,
+    buttons: Vec<MenuButtonsBuilder>,
+    // Index into the focus order of the button focused when the menu opens, instead of the first
+    #[serde(default)]
+    default_focus: Option<usize>,
+    #[serde(default)]
+    layout: MenuLayout,
+    #[serde(skip)]
+    cache: MenuCache,
+}
+
+// Shared between clones of a builder, so a static menu is only built once
+#[derive(Clone, Default)]
+struct MenuCache(Arc<Mutex<Option<Menu>>>);
+
+impl MenuBuilder {
+    pub fn new(title: impl Into<String>) -> Self {
+        Self {
+            title: MenuTitle {
+                text: title.into(),
+                size: MenuTitleSize::Heading,
+                font: None,
+            },
+            buttons: Vec::new(),
+            default_focus: None,
+            layout: default(),
+            cache: default(),
+        }
+    }
+
+    pub fn with_row(mut self, row: MenuButtonRow) -> Self {
+        self.buttons.push(MenuButtonsBuilder::Static(row));
+        self
+    }
+
+    fn is_dynamic(&self) -> bool {
+        self.buttons.iter().any(MenuButtonsBuilder::is_dynamic)
+    }
+
+    fn build(&self, locale: &Locale, paths: &MenuPaths) -> Menu {
+        if self.is_dynamic() {
+            return self.build_uncached(locale, paths);
+        }
+
+        self.cache
+            .0
+            .lock()
+            .unwrap()
+            .get_or_insert_with(|| self.build_uncached(locale, paths))
+            .clone()
+    }
+
+    fn invalidate(&self) {
+        *self.cache.0.lock().unwrap() = None;
+    }
+
+    fn build_uncached(&self, locale: &Locale, paths: &MenuPaths) -> Menu {
+        debug!("Building menu {}", self.title.text);
+
+        let mut items = self
+            .buttons
+            .iter()
+            .map(|buttons| buttons.build(locale, paths))
+            .collect::<Vec<_>>();
+
+        if let Some(input) = items.iter_mut().find_map(|item| match item {
+            MenuItem::TextInput(input) => Some(input),
+            _ => None,
+        }) {
+            input.focused = true;
+        }
+
+        Menu {
+            title: self.title.clone(),
+            items,
+            layout: self.layout,
+        }
+    }
+}
+
+#[derive(Component)]
+pub struct MenuGrid {
+    pub columns: usize,
+}
+
+// Holds the columns a long run of button rows is split into, by `MenuTheme::max_buttons_per_column`
+#[derive(Component)]
+pub struct MenuColumns;
+
+const MENU_COLUMN_GAP: f32 = 20.;
+
+#[derive(Clone)]
+enum MenuItem {
+    Buttons(MenuButtonRow),
+    AssetList(AssetListSource),
+    Slider(MenuSlider),
+    Toggle(MenuToggle),
+    TextInput(MenuTextInput),
+    Paragraph(MenuParagraph),
+    CopyProgress,
+    Tabs(Vec<MenuTab>),
+}
+
+impl MenuItem {
+    fn spawn(self, parent: &mut ChildBuilder, fonts: &Fonts, theme: &MenuTheme, locale: &Locale) {
+        match self {
+            MenuItem::Buttons(row) => {
+                row.spawn(parent, fonts, theme, locale);
+            }
+            MenuItem::AssetList(list) => list.spawn(parent, fonts, theme, locale),
+            MenuItem::Slider(slider) => slider.spawn(parent, fonts, theme),
+            MenuItem::Toggle(toggle) => toggle.spawn(parent, fonts, theme),
+            MenuItem::CopyProgress => spawn_progress_bar(parent, theme, CopyBar),
+            MenuItem::TextInput(input) => input.spawn(parent, fonts, theme),
+            MenuItem::Tabs(tabs) => spawn_tabs(tabs, parent, fonts, theme, locale),
+            MenuItem::Paragraph(paragraph) => {
+                parent
+                    .spawn_bundle(TextBundle {
+                        style: Style {
+                            align_self: AlignSelf::Center,
+                            flex_shrink: 0.,
+                            margin: theme.item_margin,
+                            size: Size::new(Val::Px(theme.paragraph_width), Val::Auto),
+                            ..default()
+                        },
+                        text: Text::with_section(
+                            String::new(),
+                            TextStyle {
+                                font: fonts.font.clone(),
+                                font_size: theme.paragraph_text_size,
+                                color: theme.title_color,
+                            },
+                            default(),
+                        ),
+                        ..default()
+                    })
+                    .insert(paragraph);
+            }
+        }
+    }
+}
+
+#[derive(Clone)]
+struct Menu {
+    title: MenuTitle,
+    items: Vec<MenuItem>,
+    layout: MenuLayout,
+}
+
+// The presets don't go without a background
+#[derive(Clone)]
+pub enum MenuBackground {
+    None,
+    Color(Color),
+    Image,
+}
+
+pub struct MenuTheme {
+    pub background: MenuBackground,
+    pub item_margin: Rect<Val>,
+    pub scrim_color: Color,
+    pub breadcrumb_size: f32,
+    pub title_size: f32,
+    pub heading_size: f32,
+    pub title_color: Color,
+    // The version in the main menu's corner
+    pub footer_size: f32,
+    pub footer_color: Color,
+    // Slowly pulses the main title's color
+    pub animate_title: bool,
+    // Whether keyboard and gamepad focus wraps past the first and last buttons, or stops there
+    pub wrap_navigation: bool,
+    // How long an asset list loads before it offers to back out, for folders on slow networks
+    pub slow_listing_secs: f32,
+    pub button_size: Size<Val>,
+    // Caps buttons in pixels, so percentage widths stay reasonable on wide screens
+    pub button_max_width: Option<f32>,
+    // Consecutive button rows past this many go in side-by-side columns
+    pub max_buttons_per_column: Option<usize>,
+    pub button_color: Color,
+    pub button_hover_color: Color,
+    pub button_press_color: Color,
+    pub button_selected_color: Color,
+    pub button_disabled_color: Color,
+    // Hovered and focused buttons grow to this over `button_scale_secs`
+    pub button_hover_scale: f32,
+    pub button_scale_secs: f32,
+    // Draws `button_border_*` and `button_shadow_*` around and under buttons
+    pub button_border: bool,
+    pub button_border_color: Color,
+    pub button_border_width: f32,
+    // Up is positive, like the rest of the UI
+    pub button_shadow_offset: Vec2,
+    pub button_shadow_color: Color,
+    pub button_text_size: f32,
+    pub button_subtitle_size: f32,
+    pub button_icon_size: f32,
+    pub button_icon_margin: f32,
+    pub shortcut_hint_size: f32,
+    // Text only wraps at a width in pixels
+    pub paragraph_width: f32,
+    pub paragraph_text_size: f32,
+    pub small_button_size: Size<Val>,
+    pub small_button_text_size: f32,
+    pub button_text_color: Color,
+    pub button_disabled_text_color: Color,
+}
+
+impl Default for MenuTheme {
+    fn default() -> Self {
+        Self {
+            background: MenuBackground::Image,
+            item_margin: Rect {
+                left: Val::Percent(0.),
+                right: Val::Percent(0.),
+                top: Val::Px(10.),
+                bottom: Val::Px(10.),
+            },
+            scrim_color: Color::rgba(0., 0., 0., 0.5),
+            breadcrumb_size: 30.,
+            title_size: 100.,
+            heading_size: 65.,
+            title_color: Color::WHITE,
+            footer_size: 20.,
+            footer_color: Color::rgba(1., 1., 1., 0.5),
+            animate_title: true,
+            wrap_navigation: true,
+            slow_listing_secs: 5.,
+            button_size: Size::new(Val::Percent(50.), Val::Px(50.)),
+            button_max_width: None,
+            max_buttons_per_column: None,
+            button_color: Color::WHITE,
+            button_hover_color: Color::rgb(0.75, 0.75, 0.75),
+            button_press_color: Color::GRAY,
+            button_selected_color: Color::rgb(0.6, 0.8, 1.),
+            button_disabled_color: Color::rgb(0.35, 0.35, 0.35),
+            button_hover_scale: 1.05,
+            button_scale_secs: 0.1,
+            button_border: false,
+            button_border_color: Color::BLACK,
+            button_border_width: 2.,
+            button_shadow_offset: Vec2::new(4., -4.),
+            button_shadow_color: Color::rgba(0., 0., 0., 0.4),
+            button_text_size: 50.,
+            button_subtitle_size: 25.,
+            button_icon_size: 40.,
+            button_icon_margin: 10.,
+            shortcut_hint_size: 30.,
+            paragraph_width: 800.,
+            paragraph_text_size: 30.,
+            small_button_size: Size::new(Val::Px(150.), Val::Px(50.)),
+            small_button_text_size: 30.,
+            button_text_color: Color::BLACK,
+            button_disabled_text_color: Color::rgb(0.6, 0.6, 0.6),
+        }
+    }
+}
+
+impl MenuTheme {
+    pub fn preset(preset: ThemePreset) -> Self {
+        match preset {
+            ThemePreset::Default => default(),
+            // Every button color keeps black text above WCAG AA's 4.5:1, and white text on black
+            // is 21:1
+            ThemePreset::HighContrast => Self {
+                background: MenuBackground::Color(Color::BLACK),
+                scrim_color: Color::rgba(0., 0., 0., 0.85),
+                title_color: Color::WHITE,
+                footer_color: Color::WHITE,
+                animate_title: false,
+                button_color: Color::WHITE,
+                button_hover_color: Color::rgb(0.55, 0.85, 1.),
+                button_press_color: Color::rgb(0.3, 0.7, 1.),
+                button_selected_color: Color::rgb(0.6, 1., 0.6),
+                button_disabled_color: Color::rgb(0.5, 0.5, 0.5),
+                button_border: true,
+                button_border_color: Color::YELLOW,
+                button_border_width: 4.,
+                button_shadow_color: Color::NONE,
+                button_text_color: Color::BLACK,
+                button_disabled_text_color: Color::BLACK,
+                ..default()
+            },
+            ThemePreset::Dark => Self {
+                background: MenuBackground::Color(Color::rgb(0.08, 0.08, 0.1)),
+                scrim_color: Color::rgba(0., 0., 0., 0.6),
+                title_color: Color::rgb(0.92, 0.92, 0.92),
+                footer_color: Color::rgba(0.92, 0.92, 0.92, 0.5),
+                button_color: Color::rgb(0.2, 0.2, 0.24),
+                button_hover_color: Color::rgb(0.3, 0.3, 0.36),
+                button_press_color: Color::rgb(0.14, 0.14, 0.17),
+                button_selected_color: Color::rgb(0.2, 0.3, 0.45),
+                button_disabled_color: Color::rgb(0.12, 0.12, 0.14),
+                button_text_color: Color::rgb(0.92, 0.92, 0.92),
+                button_disabled_text_color: Color::rgb(0.45, 0.45, 0.45),
+                ..default()
+            },
+        }
+    }
+}
+
+// Swaps in the chosen preset. A theme the app inserted itself is kept until a preset is chosen.
+fn apply_theme_preset(
+    settings: Res<Settings>,
+    mut theme: ResMut<MenuTheme>,
+    mut preset: Local<Option<ThemePreset>>,
+    mut rebuilds: EventWriter<Rebuild>,
+) {
+    if !settings.is_changed() || *preset == Some(settings.theme) {
+        return;
+    }
+
+    let first = preset.is_none();
+    *preset = Some(settings.theme);
+    if first && settings.theme == ThemePreset::Default {
+        return;
+    }
+
+    *theme = MenuTheme::preset(settings.theme);
+    if !first {
+        rebuilds.send(Rebuild::All);
+    }
+}
+
+const SCROLL_LINE_HEIGHT: f32 = 20.;
+
+impl Menu {
+    fn spawn(
+        self,
+        commands: &mut Commands,
+        fonts: &Fonts,
+        theme: &MenuTheme,
+        locale: &Locale,
+        breadcrumb: String,
+        modal: bool,
+    ) -> Entity {
+        let mut breadcrumb_e = None;
+        let dismiss = self
+            .items
+            .iter()
+            .rev()
+            .find_map(|item| match item {
+                MenuItem::Buttons(row) => {
+                    row.buttons.iter().find_map(|button| match button.action {
+                        Action::Back
+                        | Action::CloseSettings
+                        | Action::CloseWorldManager
+                        | Action::CancelCopy
+                        | Action::Resume => Some(button.action.clone()),
+                        _ => None,
+                    })
+                }
+                _ => None,
+            })
+            .unwrap_or(Action::Back);
+
+        commands
+            .spawn_bundle(NodeBundle {
+                style: Style {
+                    flex_direction: FlexDirection::ColumnReverse,
+                    align_items: AlignItems::Center,
+                    justify_content: JustifyContent::Center,
+                    position_type: PositionType::Absolute,
+                    position: Rect {
+                        left: Val::Percent(MENU_SLIDE_RIGHT),
+                        ..default()
+                    },
+                    size: Size::new(Val::Percent(100.), Val::Percent(100.)),
+                    ..default()
+                },
+                color: Color::NONE.into(),
+                ..default()
+            })
+            .insert(MenuNode)
+            .with_children(|parent| {
+                if modal {
+                    parent
+                        .spawn_bundle(ButtonBundle {
+                            style: Style {
+                                position_type: PositionType::Absolute,
+                                size: Size::new(Val::Percent(100.), Val::Percent(100.)),
+                                ..default()
+                            },
+                            color: theme.scrim_color.into(),
+                            ..default()
+                        })
+                        .insert_bundle((dismiss.clone(), MenuScrim));
+                }
+
+                breadcrumb_e = Some(
+                    parent
+                        .spawn_bundle(TextBundle {
+                            style: Style {
+                                flex_shrink: 0.,
+                                ..default()
+                            },
+                            text: Text::with_section(
+                                breadcrumb,
+                                TextStyle {
+                                    font: fonts.font.clone(),
+                                    font_size: theme.breadcrumb_size,
+                                    color: theme.title_color,
+                                },
+                                default(),
+                            ),
+                            ..default()
+                        })
+                        .id(),
+                );
+
+                let mut title = parent.spawn_bundle(TextBundle {
+                    style: Style {
+                        margin: theme.item_margin,
+                        flex_shrink: 0.,
+                        ..default()
+                    },
+                    text: Text::with_section(
+                        locale.get(&self.title.text),
+                        TextStyle {
+                            font: fonts.get(&self.title.font),
+                            font_size: match self.title.size {
+                                MenuTitleSize::MainTitle => theme.title_size,
+                                MenuTitleSize::Heading => theme.heading_size,
+                            },
+                            color: theme.title_color,
+                        },
+                        default(),
+                    ),
+                    ..default()
+                });
+                title.insert(AccessibleLabel {
+                    role: AccessRole::Heading,
+                    name: locale.get(&self.title.text),
+                });
+                if let MenuTitleSize::MainTitle = self.title.size {
+                    title.insert(MainTitle);
+                }
+
+                parent
+                    .spawn_bundle(NodeBundle {
+                        style: Style {
+                            flex_direction: FlexDirection::ColumnReverse,
+                            size: Size::new(Val::Percent(100.), Val::Auto),
+                            overflow: Overflow::Hidden,
+                            ..default()
+                        },
+                        color: Color::NONE.into(),
+                        focus_policy: FocusPolicy::Pass,
+                        ..default()
+                    })
+                    .with_children(|parent| {
+                        parent
+                            .spawn_bundle(NodeBundle {
+                                style: Style {
+                                    flex_direction: match self.layout {
+                                        MenuLayout::Column => FlexDirection::ColumnReverse,
+                                        MenuLayout::Row => row_direction(locale),
+                                    },
+                                    justify_content: JustifyContent::Center,
+                                    flex_shrink: 0.,
+                                    size: Size::new(Val::Percent(100.), Val::Auto),
+                                    ..default()
+                                },
+                                color: Color::NONE.into(),
+                                focus_policy: FocusPolicy::Pass,
+                                ..default()
+                            })
+                            .insert(MenuScroll::default())
+                            .with_children(|parent| {
+                                let mut items = self.items.into_iter().peekable();
+                                while let Some(item) = items.next() {
+                                    match (self.layout, item) {
+                                        (MenuLayout::Row, MenuItem::Buttons(mut row)) => {
+                                            row.layout.fit = true;
+                                            row.spawn(parent, fonts, theme, locale);
+                                        }
+                                        (MenuLayout::Column, MenuItem::Buttons(row)) => {
+                                            let mut rows = vec![row];
+                                            while let Some(MenuItem::Buttons(row)) =
+                                                items.next_if(|item| {
+                                                    matches!(item, MenuItem::Buttons(_))
+                                                })
+                                            {
+                                                rows.push(row);
+                                            }
+                                            spawn_button_rows(parent, rows, fonts, theme, locale);
+                                        }
+                                        (_, item) => item.spawn(parent, fonts, theme, locale),
+                                    }
+                                }
+                            });
+                    });
+            })
+            .insert_bundle((
+                MenuTrail {
+                    title: locale.get(&self.title.text),
+                    breadcrumb: breadcrumb_e.unwrap(),
+                },
+                MenuDismiss(dismiss),
+            ))
+            .id()
+    }
+}
+
+// A run of rows longer than `MenuTheme::max_buttons_per_column` is split into columns. Columns share
+// the menu's width, so their buttons fill them rather than taking `button_size`.
+fn spawn_button_rows(
+    parent: &mut ChildBuilder,
+    mut rows: Vec<MenuButtonRow>,
+    fonts: &Fonts,
+    theme: &MenuTheme,
+    locale: &Locale,
+) {
+    let per_column = match theme.max_buttons_per_column {
+        Some(per_column) if rows.len() > per_column.max(1) => per_column.max(1),
+        _ => {
+            for row in rows {
+                row.spawn(parent, fonts, theme, locale);
+            }
+            return;
+        }
+    };
+
+    parent
+        .spawn_bundle(NodeBundle {
+            style: Style {
+                flex_direction: row_direction(locale),
+                justify_content: JustifyContent::Center,
+                flex_shrink: 0.,
+                size: Size::new(Val::Percent(100.), Val::Auto),
+                ..default()
+            },
+            color: Color::NONE.into(),
+            focus_policy: FocusPolicy::Pass,
+            ..default()
+        })
+        .insert(MenuColumns)
+        .with_children(|parent| {
+            for column in rows.chunks_mut(per_column) {
+                parent
+                    .spawn_bundle(NodeBundle {
+                        style: Style {
+                            flex_direction: FlexDirection::ColumnReverse,
+                            flex_grow: 1.,
+                            flex_basis: Val::Px(0.),
+                            padding: Rect {
+                                left: Val::Px(MENU_COLUMN_GAP / 2.),
+                                right: Val::Px(MENU_COLUMN_GAP / 2.),
+                                ..default()
+                            },
+                            ..default()
+                        },
+                        color: Color::NONE.into(),
+                        focus_policy: FocusPolicy::Pass,
+                        ..default()
+                    })
+                    .with_children(|parent| {
+                        for row in column {
+                            row.layout.width = RowWidth::Fill;
+                            row.spawn(parent, fonts, theme, locale);
+                        }
+                    });
+            }
+        });
+}
+
+const PROGRESS_BAR_WIDTH: f32 = 50.;
+const PROGRESS_BAR_HEIGHT: f32 = 20.;
+
+// `bar` goes on the fill, whose width is the progress
+fn spawn_progress_bar(parent: &mut ChildBuilder, theme: &MenuTheme, bar: impl Component) {
+    parent
+        .spawn_bundle(NodeBundle {
+            style: Style {
+                align_self: AlignSelf::Center,
+                flex_shrink: 0.,
+                margin: theme.item_margin,
+                size: Size::new(
+                    Val::Percent(PROGRESS_BAR_WIDTH),
+                    Val::Px(PROGRESS_BAR_HEIGHT),
+                ),
+                ..default()
+            },
+            color: theme.button_hover_color.into(),
+            ..default()
+        })
+        .with_children(|parent| {
+            parent
+                .spawn_bundle(NodeBundle {
+                    style: Style {
+                        size: Size::new(Val::Percent(0.), Val::Percent(100.)),
+                        ..default()
+                    },
+                    color: theme.button_color.into(),
+                    ..default()
+                })
+                .insert(bar);
+        });
+}
+
+fn row_direction(locale: &Locale) -> FlexDirection {
+    if locale.rtl() {
+        FlexDirection::RowReverse
+    } else {
+        FlexDirection::Row
+    }
+}
+
+impl MenuButtonRow {
+    fn spawn(
+        &self,
+        parent: &mut ChildBuilder,
+        fonts: &Fonts,
+        theme: &MenuTheme,
+        locale: &Locale,
+    ) -> Entity {
+        parent
+            .spawn_bundle(NodeBundle {
+                style: Style {
+                    flex_direction: row_direction(locale),
+                    justify_content: match self.layout.align {
+                        RowAlign::Start => JustifyContent::FlexStart,
+                        RowAlign::Center => JustifyContent::Center,
+                        RowAlign::SpaceBetween => JustifyContent::SpaceBetween,
+                    },
+                    flex_shrink: 0.,
+                    size: Size::new(
+                        if self.layout.fit {
+                            Val::Auto
+                        } else {
+                            Val::Percent(100.)
+                        },
+                        Val::Auto,
+                    ),
+                    ..default()
+                },
+                color: Color::NONE.into(),
+                focus_policy: FocusPolicy::Pass,
+                ..default()
+            })
+            .with_children(|parent| {
+                for button in &self.buttons {
+                    button.spawn(parent, fonts, theme, locale, self.layout.width);
+                }
+            })
+            .id()
+    }
+}
+
+impl MenuButton {
+    fn spawn(
+        &self,
+        parent: &mut ChildBuilder,
+        fonts: &Fonts,
+        theme: &MenuTheme,
+        locale: &Locale,
+        width: RowWidth,
+    ) {
+        let layout = Style {
+            margin: theme.item_margin,
+            size: match (width, self.small, &self.subtitle) {
+                (RowWidth::Fill, _, _) => Size::new(Val::Auto, Val::Auto),
+                (RowWidth::Fixed, true, _) => theme.small_button_size,
+                (RowWidth::Fixed, false, None) if !self.wraps() => theme.button_size,
+                (RowWidth::Fixed, false, _) => Size::new(theme.button_size.width, Val::Auto),
+            },
+            min_size: Size::new(Val::Auto, theme.button_size.height),
+            max_size: Size::new(
+                match (self.small, theme.button_max_width) {
+                    (false, Some(max_width)) => Val::Px(max_width),
+                    _ => Val::Undefined,
+                },
+                Val::Undefined,
+            ),
+            flex_grow: match width {
+                RowWidth::Fixed => 0.,
+                RowWidth::Fill => 1.,
+            },
+            flex_basis: match width {
+                RowWidth::Fixed => Val::Auto,
+                RowWidth::Fill => Val::Px(0.),
+            },
+            ..default()
+        };
+
+        if !theme.button_border {
+            self.spawn_button(parent, fonts, theme, locale, layout);
+            return;
+        }
+
+        // The button fills a frame of the border color, over its shadow
+        parent
+            .spawn_bundle(NodeBundle {
+                style: layout,
+                color: Color::NONE.into(),
+                focus_policy: FocusPolicy::Pass,
+                ..default()
+            })
+            .with_children(|parent| {
+                parent.spawn_bundle(NodeBundle {
+                    style: Style {
+                        position_type: PositionType::Absolute,
+                        position: Rect {
+                            left: Val::Px(theme.button_shadow_offset.x),
+                            top: Val::Px(theme.button_shadow_offset.y),
+                            ..default()
+                        },
+                        size: Size::new(Val::Percent(100.), Val::Percent(100.)),
+                        ..default()
+                    },
+                    color: theme.button_shadow_color.into(),
+                    focus_policy: FocusPolicy::Pass,
+                    ..default()
+                });
+
+                parent
+                    .spawn_bundle(NodeBundle {
+                        style: Style {
+                            flex_grow: 1.,
+                            padding: Rect::all(Val::Px(theme.button_border_width)),
+                            ..default()
+                        },
+                        color: theme.button_border_color.into(),
+                        focus_policy: FocusPolicy::Pass,
+                        ..default()
+                    })
+                    .with_children(|parent| {
+                        self.spawn_button(
+                            parent,
+                            fonts,
+                            theme,
+                            locale,
+                            Style {
+                                flex_grow: 1.,
+                                ..default()
+                            },
+                        );
+                    });
+            });
+    }
+
+    fn wraps(&self) -> bool {
+        matches!(self.overflow, LabelOverflow::Wrap)
+    }
+
+    fn spawn_button(
+        &self,
+        parent: &mut ChildBuilder,
+        fonts: &Fonts,
+        theme: &MenuTheme,
+        locale: &Locale,
+        layout: Style,
+    ) {
+        let text_color = if self.disabled {
+            theme.button_disabled_text_color
+        } else {
+            theme.button_text_color
+        };
+
+        let color = if self.disabled {
+            theme.button_disabled_color
+        } else {
+            theme.button_color
+        };
+        let mut button = parent.spawn_bundle(ButtonBundle {
+            style: Style {
+                flex_direction: FlexDirection::ColumnReverse,
+                align_items: AlignItems::Center,
+                justify_content: JustifyContent::Center,
+                ..layout
+            },
+            color: color.into(),
+            ..default()
+        });
+        let action = match &self.confirm {
+            Some(prompt) => Action::Menu(confirm_menu(prompt, "menu.confirm", self.action.clone())),
+            None => self.action.clone(),
+        };
+        button.insert_bundle((
+            action,
+            Focusable,
+            TargetColor(color),
+            AccessibleLabel {
+                role: AccessRole::Button,
+                name: locale.get(&self.text),
+            },
+        ));
+        if self.disabled {
+            button.insert(Disabled);
+        }
+
+        // Separates the icon and shortcut hint from the label, which comes first in right-to-left
+        let beside_label = if locale.rtl() {
+            Rect {
+                left: Val::Px(theme.button_icon_margin),
+                ..default()
+            }
+        } else {
+            Rect {
+                right: Val::Px(theme.button_icon_margin),
+                ..default()
+            }
+        };
+
+        let spawn_label = |parent: &mut ChildBuilder| {
+            let label = locale.get(&self.text);
+            let mut text = parent.spawn_bundle(TextBundle {
+                text: Text::with_section(
+                    label.clone(),
+                    TextStyle {
+                        font: fonts.get(&self.font),
+                        font_size: if self.small {
+                            theme.small_button_text_size
+                        } else {
+                            theme.button_text_size
+                        },
+                        color: text_color,
+                    },
+                    default(),
+                ),
+                ..default()
+            });
+            text.insert(ButtonLabel {
+                overflow: self.overflow,
+                full: label.clone(),
+                fitted: label,
+                width: 0.,
+            });
+
+            match &self.action {
+                Action::Setting(setting) => {
+                    text.insert(*setting);
+                }
+                Action::SelectWorld(world) => {
+                    text.insert(WorldCheckbox(world.clone()));
+                }
+                _ => (),
+            }
+        };
+
+        if let Some(shortcut) = self.shortcut {
+            button.insert(Shortcut(shortcut));
+        }
+
+        button.with_children(|parent| {
+            if self.icon.is_none() && self.shortcut.is_none() {
+                spawn_label(parent);
+            } else {
+                parent
+                    .spawn_bundle(NodeBundle {
+                        style: Style {
+                            flex_direction: row_direction(locale),
+                            align_items: AlignItems::Center,
+                            ..default()
+                        },
+                        color: Color::NONE.into(),
+                        focus_policy: FocusPolicy::Pass,
+                        ..default()
+                    })
+                    .with_children(|parent| {
+                        if let Some(icon) = &self.icon {
+                            parent
+                                .spawn_bundle(ImageBundle {
+                                    style: Style {
+                                        size: Size::new(
+                                            Val::Px(theme.button_icon_size),
+                                            Val::Px(theme.button_icon_size),
+                                        ),
+                                        margin: beside_label,
+                                        ..default()
+                                    },
+                                    image: icon.clone().into(),
+                                    focus_policy: FocusPolicy::Pass,
+                                    ..default()
+                                })
+                                .insert(MenuIcon);
+                        }
+
+                        if let Some(shortcut) = self.shortcut {
+                            parent.spawn_bundle(TextBundle {
+                                style: Style {
+                                    margin: beside_label,
+                                    ..default()
+                                },
+                                text: Text::with_section(
+                                    format!("[{:?}]", shortcut),
+                                    TextStyle {
+                                        font: fonts.font.clone(),
+                                        font_size: theme.shortcut_hint_size,
+                                        color: text_color,
+                                    },
+                                    default(),
+                                ),
+                                ..default()
+                            });
+                        }
+
+                        spawn_label(parent);
+                    });
+            }
+
+            if let Some(subtitle) = &self.subtitle {
+                parent.spawn_bundle(TextBundle {
+                    text: Text::with_section(
+                        subtitle.clone(),
+                        TextStyle {
+                            font: fonts.get(&self.font),
+                            font_size: theme.button_subtitle_size,
+                            color: text_color,
+                        },
+                        default(),
+                    ),
+                    ..default()
+                });
+            }
+        });
+    }
+}
+
+#[derive(Component)]
+struct ButtonLabel {
+    overflow: LabelOverflow,
+    full: String,
+    // What the label was fit to, to tell when something else changes the text
+    fitted: String,
+    width: f32,
+}
+
+const BUTTON_LABEL_PADDING: f32 = 10.;
+const ELLIPSIS: &str = "…";
+
+fn text_width(font: &Font, font_size: f32, text: &str) -> f32 {
+    let font = font.font.as_scaled(font_size);
+    let mut last = None;
+    text.chars()
+        .map(|char| {
+            let glyph = font.glyph_id(char);
+            let kern = last.map_or(0., |last| font.kern(last, glyph));
+            last = Some(glyph);
+            kern + font.h_advance(glyph)
+        })
+        .sum()
+}
+
+fn ellipsize(label: &str, font: &Font, font_size: f32, width: f32) -> String {
+    if text_width(font, font_size, label) <= width {
+        return label.to_string();
+    }
+
+    let ellipsis = text_width(font, font_size, ELLIPSIS);
+    let mut fitted = label.to_string();
+    while !fitted.is_empty() && text_width(font, font_size, &fitted) + ellipsis > width {
+        fitted.pop();
+    }
+    format!("{}{}", fitted.trim_end(), ELLIPSIS)
+}
+
+// Fits labels to the button sizes from the last layout
+fn fit_button_labels(
+    fonts: Res<Assets<Font>>,
+    mut labels: Query<(&mut ButtonLabel, &mut Text, &mut Style, &Node, &Parent)>,
+    buttons: Query<&Node, With<Button>>,
+    nodes: Query<&Node>,
+    parents: Query<&Parent>,
+) {
+    for (mut label, mut text, mut style, node, parent) in labels.iter_mut() {
+        // Labels next to an icon or shortcut hint share the button with them
+        let (button, beside) = match buttons.get(**parent) {
+            Ok(button) => (button, 0.),
+            Err(_) => match parents
+                .get(**parent)
+                .and_then(|row_parent| buttons.get(**row_parent))
+            {
+                Ok(button) => (button, nodes.get(**parent).unwrap().size.x - node.size.x),
+                Err(_) => continue,
+            },
+        };
+        if button.size.x == 0. {
+            continue;
+        }
+
+        let width = (button.size.x - beside - BUTTON_LABEL_PADDING * 2.).max(0.);
+        let section = &text.sections[0];
+        if section.value != label.fitted {
+            label.full = section.value.clone();
+        } else if width == label.width {
+            continue;
+        }
+
+        let fitted = match label.overflow {
+            LabelOverflow::Ellipsis => match fonts.get(&section.style.font) {
+                Some(font) => ellipsize(&label.full, font, section.style.font_size, width),
+                None => continue,
+            },
+            LabelOverflow::Wrap => {
+                style.max_size.width = Val::Px(width);
+                label.full.clone()
+            }
+        };
+
+        if text.sections[0].value != fitted {
+            text.sections[0].value = fitted.clone();
+        }
+        label.fitted = fitted;
+        label.width = width;
+    }
+}
+
+#[derive(Component)]
+struct MenuScrim;
+
+// What the back key does in a menu, the same as clicking its scrim
+#[derive(Component)]
+struct MenuDismiss(Action);
+
+#[derive(Component)]
+struct Shortcut(KeyCode);
+
+#[derive(Clone, Debug, Eq, Hash, PartialEq, SystemLabel)]
+pub enum MenuSystem {
+    // Systems that spawn menus, including rebuilt ones
+    Spawn,
+}
+
+// Sent by `MenuSystem::Spawn` systems. The menu is spawned through commands, so systems after them
+// can add to it with `Commands`, but can't query it until the stage ends.
+pub struct MenuSpawned {
+    pub entity: Entity,
+}
+
+// Adds a row of buttons to the end of a spawned menu, like a mod's entry in a menu it didn't build.
+// Focus moves through it like the rest. Rebuilt menus don't keep it, but send `MenuSpawned` again.
+pub struct AppendMenuButton {
+    pub menu: Entity,
+    pub row: MenuButtonRow,
+}
+
+impl Command for AppendMenuButton {
+    fn write(mut self, world: &mut World) {
+        let mut stack = vec![self.menu];
+        let scroll_e = loop {
+            let entity = match stack.pop() {
+                Some(entity) => entity,
+                None => {
+                    warn!(
+                        "Couldn't append buttons to {:?}, which isn't a menu",
+                        self.menu
+                    );
+                    return;
+                }
+            };
+            if world.get::<MenuScroll>(entity).is_some() {
+                break entity;
+            }
+            stack.extend(
+                world
+                    .get::<Children>(entity)
+                    .into_iter()
+                    .flat_map(|children| children.iter()),
+            );
+        };
+
+        if let Some(MenuSource(MenuBuilder {
+            layout: MenuLayout::Row,
+            ..
+        })) = world.get::<MenuSource>(self.menu)
+        {
+            self.row.layout.fit = true;
+        }
+
+        let mut queue = CommandQueue::default();
+        let mut commands = Commands::new(&mut queue, world);
+        commands.entity(scroll_e).with_children(|parent| {
+            self.row.spawn(
+                parent,
+                world.resource::<Fonts>(),
+                world.resource::<MenuTheme>(),
+                world.resource::<Locale>(),
+            );
+        });
+        queue.apply(world);
+    }
+}
+
+// Sent when an action leaves the menus, changes worlds on disk or flips a toggle, for the host app
+// to react to
+#[derive(Clone, Debug)]
+pub enum MenuAction {
+    Play(PathBuf),
+    Edit(PathBuf),
+    CreateWorld { game: PathBuf, world: PathBuf },
+    RenameWorld { world: PathBuf, renamed: PathBuf },
+    DuplicateWorld { world: PathBuf, copy: PathBuf },
+    ImportWorld { archive: PathBuf, world: PathBuf },
+    DeleteWorlds(Vec<PathBuf>),
+    RestoreWorlds(Vec<PathBuf>),
+    Toggle { toggle: ToggleAction, on: bool },
+    Quit,
+}
+
+// Worlds changed on disk, for keeping indexes of them in sync
+#[derive(Clone, Debug)]
+pub enum MenuWorldEvent {
+    Created(PathBuf),
+    Deleted(PathBuf),
+    Renamed { world: PathBuf, renamed: PathBuf },
+    // Extracted from an archive
+    Imported(PathBuf),
+}
+
+// Every change to worlds on disk is sent as a `MenuAction` already, whichever path it took
+fn send_world_events(
+    mut menu_actions: EventReader<MenuAction>,
+    mut world_events: EventWriter<MenuWorldEvent>,
+) {
+    for menu_action in menu_actions.iter() {
+        match menu_action {
+            MenuAction::CreateWorld { world, .. } => {
+                world_events.send(MenuWorldEvent::Created(world.clone()));
+            }
+            MenuAction::DuplicateWorld { copy, .. } => {
+                world_events.send(MenuWorldEvent::Created(copy.clone()));
+            }
+            MenuAction::ImportWorld { world, .. } => {
+                world_events.send(MenuWorldEvent::Imported(world.clone()));
+            }
+            MenuAction::RenameWorld { world, renamed } => {
+                world_events.send(MenuWorldEvent::Renamed {
+                    world: world.clone(),
+                    renamed: renamed.clone(),
+                });
+            }
+            MenuAction::DeleteWorlds(worlds) => world_events.send_batch(
+                worlds
+                    .iter()
+                    .map(|world| MenuWorldEvent::Deleted(world.clone())),
+            ),
+            MenuAction::RestoreWorlds(worlds) => world_events.send_batch(
+                worlds
+                    .iter()
+                    .map(|world| MenuWorldEvent::Created(world.clone())),
+            ),
+            MenuAction::Play(_)
+            | MenuAction::Edit(_)
+            | MenuAction::Toggle { .. }
+            | MenuAction::Quit => (),
+        }
+    }
+}
+
+// Leaves the game for a freshly built main menu
+pub struct ReturnToMenu;
+
+// Kept pending across frames until the state change can be queued
+fn return_to_menu(
+    mut commands: Commands,
+    mut returns: EventReader<ReturnToMenu>,
+    mut state: ResMut<State<GameState>>,
+    mut pending: Local<bool>,
+) {
+    *pending |= returns.iter().last().is_some();
+    if !*pending {
+        return;
+    }
+
+    if let Err(err) = state.set(GameState::MainMenu) {
+        warn!("Couldn't return to the menu: {}", err);
+        return;
+    }
+
+    *pending = false;
+    commands.remove_resource::<CurrentWorld>();
+    commands.remove_resource::<OpeningGame>();
+}
+
+// Opens `menu` over the current state, so popping it goes back. Menus opened from a menu go
+// through `GameState::Buffer`, since a state can't be pushed over itself.
+pub fn open_menu(
+    commands: &mut Commands,
+    state: &mut State<GameState>,
+    menu: MenuBuilder,
+) -> Result<(), StateError> {
+    if *state.current() == GameState::Menu {
+        state.push(GameState::Buffer)?;
+        commands.insert_resource(BufferedState(GameState::Menu));
+    } else {
+        state.push(GameState::Menu)?;
+    }
+
+    debug!("Opening menu {}", menu.title.text);
+    commands.insert_resource(NextMenu(menu));
+    Ok(())
+}
+
+// Pushes each menu over the last, as if opened from it, so backing out walks down the path. Sent
+// from the game, it opens over the pause menu. Waits for the menus to be open.
+pub struct OpenMenuPath(pub Vec<MenuBuilder>);
+
+// Left to push of the latest `OpenMenuPath`, one at a time since each is a state change
+#[derive(Default)]
+struct MenuPath(VecDeque<MenuBuilder>);
+
+fn open_menu_path(mut opens: EventReader<OpenMenuPath>, mut path: ResMut<MenuPath>) {
+    if let Some(open) = opens.iter().last() {
+        path.0 = open.0.iter().cloned().collect();
+    }
+}
+
+fn push_menu_path(
+    mut commands: Commands,
+    mut path: ResMut<MenuPath>,
+    mut state: ResMut<State<GameState>>,
+) {
+    if path.0.is_empty() {
+        return;
+    }
+
+    // Another state change got there first, so try again next frame
+    match state.current() {
+        GameState::MainMenu | GameState::Menu | GameState::Paused => {
+            if open_menu(&mut commands, &mut state, path.0[0].clone()).is_ok() {
+                path.0.pop_front();
+            }
+        }
+        // Over the pause menu, which freezes the game and brings a camera for the menus
+        GameState::Game => {
+            let _ = state.push(GameState::Paused);
+        }
+        // Left to get somewhere a menu can go over first
+        GameState::Loading | GameState::Buffer | GameState::LoadingWorld | GameState::Edit => (),
+    }
+}
+
+#[derive(Component, Default)]
+struct MenuScroll(f32);
+
+impl MenuScroll {
+    fn scroll_by(
+        &mut self,
+        dy: f32,
+        style: &mut Style,
+        rows: &Children,
+        container: &Node,
+        nodes: &Query<&Node>,
+    ) {
+        let rows_height = rows
+            .iter()
+            .map(|row| nodes.get(*row).unwrap().size.y)
+            .sum::<f32>();
+        let max_scroll = (rows_height - container.size.y).max(0.);
+        self.0 = (self.0 + dy).clamp(-max_scroll, 0.);
+        style.position.top = Val::Px(self.0);
+    }
+}
+
+struct MenuEntry {
+    entity: Entity,
+    focus: Option<usize>,
+    layout: MenuLayout,
+}
+
+#[derive(Deref, DerefMut)]
+struct MenuEs(Vec<MenuEntry>);
+
+impl MenuEs {
+    fn top(&self) -> Option<Entity> {
+        self.last().map(|entry| entry.entity)
+    }
+
+    // The top menu's title key, which reads the same in every language, for tracing spans
+    fn top_title<'a>(&self, sources: &'a Query<&MenuSource>) -> &'a str {
+        self.top()
+            .and_then(|menu_e| sources.get(menu_e).ok())
+            .map_or("", |source| &source.title.text)
+    }
+}
+
+#[derive(Component)]
+struct MenuTrail {
+    title: String,
+    breadcrumb: Entity,
+}
+
+// The live menu stack, for host systems and overlays that can't see `MenuEs`
+#[derive(Default, PartialEq)]
+pub struct MenuStackInfo {
+    pub depth: usize,
+    pub title: Option<String>,
+    // Titles from the bottom menu up to the current one
+    pub trail: Vec<String>,
+}
+
+// Menus push, pop and rebuild through `MenuEs`, and relocalizing retitles their trails
+fn update_menu_stack_info(
+    menu_es: Option<Res<MenuEs>>,
+    trails: Query<&MenuTrail>,
+    changed_trails: Query<(), Changed<MenuTrail>>,
+    mut info: ResMut<MenuStackInfo>,
+) {
+    let menus_changed = menu_es
+        .as_ref()
+        .map_or(info.depth > 0, |menu_es| menu_es.is_changed());
+    if !menus_changed && changed_trails.is_empty() {
+        return;
+    }
+
+    let trail = menu_es
+        .iter()
+        .flat_map(|menu_es| menu_es.iter())
+        .filter_map(|entry| Some(trails.get(entry.entity).ok()?.title.clone()))
+        .collect::<Vec<_>>();
+    let new_info = MenuStackInfo {
+        depth: menu_es.map_or(0, |menu_es| menu_es.len()),
+        title: trail.last().cloned(),
+        trail,
+    };
+    if *info != new_info {
+        *info = new_info;
+    }
+}
+
+const BREADCRUMB_SEPARATOR: &str = " > ";
+const RTL_BREADCRUMB_SEPARATOR: &str = " < ";
+
+// Right-to-left trails start at the right
+fn join_breadcrumb<'a>(
+    titles: impl DoubleEndedIterator<Item = &'a str>,
+    locale: &Locale,
+) -> String {
+    if locale.rtl() {
+        titles
+            .rev()
+            .collect::<Vec<_>>()
+            .join(RTL_BREADCRUMB_SEPARATOR)
+    } else {
+        titles.collect::<Vec<_>>().join(BREADCRUMB_SEPARATOR)
+    }
+}
+
+fn breadcrumb(menu_es: &[MenuEntry], trails: &Query<&MenuTrail>, locale: &Locale) -> String {
+    join_breadcrumb(
+        menu_es
+            .iter()
+            .filter_map(|entry| trails.get(entry.entity).ok())
+            .map(|trail| trail.title.as_str()),
+        locale,
+    )
+}
+
+fn menu_root(mut entity: Entity, parents: &Query<&Parent>) -> Entity {
+    while let Ok(parent) = parents.get(entity) {
+        entity = **parent;
+    }
+    entity
+}
+
+fn in_menu(mut entity: Entity, menu_e: Entity, parents: &Query<&Parent>) -> bool {
+    loop {
+        if entity == menu_e {
+            return true;
+        }
+
+        match parents.get(entity) {
+            Ok(parent) => entity = **parent,
+            Err(_) => return false,
+        }
+    }
+}
+
+#[derive(Deref)]
+struct NextMenu(MenuBuilder);
+
+#[derive(Component, Deref)]
+struct MenuSource(MenuBuilder);
+
+pub enum Rebuild {
+    Dynamic,
+    All,
+}
+
+const MENU_TRANSITION_SECS: f32 = 0.25;
+const MENU_SLIDE_LEFT: f32 = -100.;
+const MENU_SLIDE_RIGHT: f32 = 100.;
+
+enum MenuTransitionEnd {
+    Show,
+    Hide,
+    Despawn,
+}
+
+#[derive(Component)]
+struct MenuTransition {
+    timer: Timer,
+    from: f32,
+    to: f32,
+    end: MenuTransitionEnd,
+}
+
+// Menus are spawned off-screen and swapped in once everything they load is ready
+#[derive(Component)]
+struct StagedMenu {
+    swap: MenuSwap,
+    timeout: Timer,
+}
+
+// Shown anyway after this long, so a stalled load can't hold up the menu
+const MENU_STAGING_TIMEOUT_SECS: f32 = 1.;
+
+enum MenuSwap {
+    // Slides in over the menu below it
+    Push,
+    // Takes the place of a rebuilt menu
+    Replace { old: Entity },
+}
+
+impl StagedMenu {
+    fn new(swap: MenuSwap) -> Self {
+        Self {
+            swap,
+            timeout: Timer::from_seconds(MENU_STAGING_TIMEOUT_SECS, false),
+        }
+    }
+}
+
+impl MenuTransition {
+    fn slide(style: &Style, to: f32, end: MenuTransitionEnd) -> Self {
+        Self {
+            timer: Timer::from_seconds(MENU_TRANSITION_SECS, false),
+            from: match style.position.left {
+                Val::Percent(left) => left,
+                _ => 0.,
+            },
+            to,
+            end,
+        }
+    }
+}
+
+const MAIN_MENU_PATH: &str = "menus/main.ron";
+
+#[derive(Clone)]
+struct MenuConfig {
+    // Loaded from `MAIN_MENU_PATH` if not set
+    root: Option<MenuBuilder>,
+    title: String,
+}
+
+impl Default for MenuConfig {
+    fn default() -> Self {
+        Self {
+            root: None,
+            title: "voxmod".to_string(),
+        }
+    }
+}
+
+#[derive(Component)]
+struct MenuCamera;
+
+#[derive(Component)]
+struct MenuFooter;
+
+const FOOTER_MARGIN: f32 = 10.;
+
+// Which build this is, for bug reports. The commit is only known if the build sets `GIT_HASH`.
+fn build_info() -> String {
+    match option_env!("GIT_HASH") {
+        Some(hash) => format!("v{} ({})", env!("CARGO_PKG_VERSION"), hash),
+        None => format!("v{}", env!("CARGO_PKG_VERSION")),
+    }
+}
+
+#[allow(clippy::too_many_arguments)]
+fn init_main_menu(
+    mut commands: Commands,
+    cameras: Query<(), With<CameraUi>>,
+    footers: Query<Entity, With<MenuFooter>>,
+    config: Res<MenuConfig>,
+    asset_server: Res<AssetServer>,
+    fonts: Res<Fonts>,
+    theme: Res<MenuTheme>,
+    locale: Res<Locale>,
+    mut toasts: EventWriter<MenuToast>,
+    mut state: ResMut<State<GameState>>,
+) {
+    // The host app may bring its own UI camera, and a second one would draw the UI twice
+    if cameras.is_empty() {
+        commands
+            .spawn_bundle(UiCameraBundle::default())
+            .insert(MenuCamera);
+    }
+
+    for footer_e in footers.iter() {
+        commands.entity(footer_e).despawn_recursive();
+    }
+    // Outside the menus, so it stays in the corner whatever they're showing
+    commands
+        .spawn_bundle(TextBundle {
+            style: Style {
+                position_type: PositionType::Absolute,
+                position: Rect {
+                    left: Val::Px(FOOTER_MARGIN),
+                    bottom: Val::Px(FOOTER_MARGIN),
+                    ..default()
+                },
+                ..default()
+            },
+            text: Text::with_section(
+                build_info(),
+                TextStyle {
+                    font: fonts.font.clone(),
+                    font_size: theme.footer_size,
+                    color: theme.footer_color,
+                },
+                default(),
+            ),
+            focus_policy: FocusPolicy::Pass,
+            ..default()
+        })
+        .insert_bundle((MenuFooter, MenuNode));
+
+    let mut main_menu = config.root.clone().unwrap_or_else(|| {
+        let path = Path::new(MAIN_MENU_PATH);
+        block_on(asset_server.asset_io().load_path(path))
+            .map_err(|err| err.to_string())
+            .and_then(|bytes| ron::de::from_bytes(&bytes).map_err(|err| err.to_string()))
+            .unwrap_or_else(|err| {
+                toasts.send(MenuToast::new(
+                    ToastSeverity::Error,
+                    locale
+                        .get("menu.default_menu")
+                        .replace("{path}", &path.display().to_string())
+                        .replace("{error}", &err),
+                ));
+                default_main_menu()
+            })
+    });
+    main_menu.title.text = config.title.clone();
+
+    open_menu(&mut commands, &mut state, main_menu).unwrap();
+}
+
+fn settings_menu() -> MenuBuilder {
+    MenuBuilder {
+        title: MenuTitle {
+            text: "menu.settings".to_string(),
+            size: MenuTitleSize::Heading,
+            font: None,
+        },
+        buttons: vec![
+            MenuButtonsBuilder::Slider {
+                label: "settings.master_volume".to_string(),
+                min: 0.,
+                max: 1.,
+                value_action: SliderAction::MasterVolume,
+            },
+            MenuButtonsBuilder::Slider {
+                label: "settings.music_volume".to_string(),
+                min: 0.,
+                max: 1.,
+                value_action: SliderAction::MusicVolume,
+            },
+            MenuButtonsBuilder::Toggle {
+                label: "settings.fullscreen".to_string(),
+                toggle_action: ToggleAction::Fullscreen,
+            },
+            MenuButtonsBuilder::Toggle {
+                label: "settings.vsync".to_string(),
+                toggle_action: ToggleAction::Vsync,
+            },
+            MenuButtonsBuilder::Toggle {
+                label: "settings.confirm_on_quit".to_string(),
+                toggle_action: ToggleAction::ConfirmQuit,
+            },
+            MenuButtonsBuilder::Static(MenuButtonRow::new(vec![MenuButton::new(
+                String::new(),
+                Action::Setting(SettingButton::Language),
+            )])),
+            MenuButtonsBuilder::Static(MenuButtonRow::new(vec![MenuButton::new(
+                String::new(),
+                Action::Setting(SettingButton::RenderDistance),
+            )])),
+            MenuButtonsBuilder::Static(MenuButtonRow::new(vec![MenuButton::new(
+                String::new(),
+                Action::Setting(SettingButton::Theme),
+            )])),
+            MenuButtonsBuilder::Static(MenuButtonRow::new(vec![MenuButton::new(
+                "menu.controls",
+                Action::Menu(controls_menu()),
+            )])),
+            MenuButtonsBuilder::Static(MenuButtonRow::new(vec![MenuButton::new(
+                "menu.back",
+                Action::CloseSettings,
+            )])),
+        ],
+        default_focus: None,
+        layout: default(),
+        cache: default(),
+    }
+}
+
+// Each binding's button waits for a key press to rebind it
+fn controls_menu() -> MenuBuilder {
+    let button = |text: &str, action| {
+        MenuButtonsBuilder::Static(MenuButtonRow::new(vec![MenuButton::new(text, action)]))
+    };
+
+    MenuBuilder {
+        title: MenuTitle {
+            text: "menu.controls".to_string(),
+            size: MenuTitleSize::Heading,
+            font: None,
+        },
+        buttons: MenuKey::ALL
+            .into_iter()
+            .map(|key| button("", Action::Setting(SettingButton::KeyBinding(key))))
+            .chain([button("menu.back", Action::Back)])
+            .collect(),
+        default_focus: None,
+        layout: default(),
+        cache: default(),
+    }
+}
+
+const CREDITS_PATH: &str = "text/credits.txt";
+
+fn default_main_menu() -> MenuBuilder {
+    MenuBuilder {
+        title: MenuTitle {
+            // Replaced by the app title
+            text: String::new(),
+            size: MenuTitleSize::MainTitle,
+            font: None,
+        },
+        buttons: vec![
+            MenuButtonsBuilder::PerAsset {
+                action: AssetButtonAction::Continue,
+                sort: AssetSort::LastPlayedDescending,
+                page_size: None,
+                categories: false,
+                category: None,
+            },
+            MenuButtonsBuilder::Static(MenuButtonRow::new(vec![MenuButton::new(
+                "menu.play",
+                Action::Menu(MenuBuilder {
+                    title: MenuTitle {
+                        text: "menu.choose_world".to_string(),
+                        size: MenuTitleSize::Heading,
+                        font: None,
+                    },
+                    buttons: vec![
+                        MenuButtonsBuilder::Search {
+                            placeholder: "menu.search".to_string(),
+                        },
+                        MenuButtonsBuilder::Grid {
+                            action: AssetButtonAction::Play,
+                            sort: AssetSort::ModifiedDescending,
+                            columns: 2,
+                            page_size: None,
+                        },
+                        MenuButtonsBuilder::Static(MenuButtonRow::new(vec![MenuButton::new(
+                            "menu.create_world",
+                            Action::Menu(MenuBuilder {
+                                title: MenuTitle {
+                                    text: "menu.choose_game".to_string(),
+                                    size: MenuTitleSize::Heading,
+                                    font: None,
+                                },
+                                buttons: vec![
+                                    MenuButtonsBuilder::PerAsset {
+                                        action: AssetButtonAction::CreateWorld,
+                                        sort: AssetSort::NameAscending,
+                                        page_size: None,
+                                        categories: true,
+                                        category: None,
+                                    },
+                                    MenuButtonsBuilder::Static(MenuButtonRow::new(vec![
+                                        MenuButton::new("menu.back", Action::Back),
+                                    ])),
+                                ],
+                                default_focus: None,
+                                layout: default(),
+                                cache: default(),
+                            }),
+                        )])),
+                        MenuButtonsBuilder::Static(MenuButtonRow::new(vec![MenuButton::new(
+                            "menu.manage_worlds",
+                            Action::Menu(manage_worlds_menu()),
+                        )])),
+                        MenuButtonsBuilder::Static(MenuButtonRow::new(vec![MenuButton::new(
+                            "menu.back",
+                            Action::Back,
+                        )])),
+                    ],
+                    default_focus: None,
+                    layout: default(),
+                    cache: default(),
+                }),
+            )])),
+            MenuButtonsBuilder::Static(MenuButtonRow::new(vec![MenuButton::new(
+                "menu.edit",
+                Action::Menu(MenuBuilder {
+                    title: MenuTitle {
+                        text: "menu.choose_edit_world".to_string(),
+                        size: MenuTitleSize::Heading,
+                        font: None,
+                    },
+                    buttons: vec![
+                        MenuButtonsBuilder::Search {
+                            placeholder: "menu.search".to_string(),
+                        },
+                        MenuButtonsBuilder::PerAsset {
+                            action: AssetButtonAction::Edit,
+                            sort: AssetSort::ModifiedDescending,
+                            page_size: None,
+                            categories: false,
+                            category: None,
+                        },
+                        MenuButtonsBuilder::Static(MenuButtonRow::new(vec![MenuButton::new(
+                            "menu.back",
+                            Action::Back,
+                        )])),
+                    ],
+                    default_focus: None,
+                    layout: default(),
+                    cache: default(),
+                }),
+            )])),
+            MenuButtonsBuilder::Static(MenuButtonRow::new(vec![MenuButton::new(
+                "menu.settings",
+                Action::Menu(settings_menu()),
+            )])),
+            MenuButtonsBuilder::Static(MenuButtonRow::new(vec![MenuButton::new(
+                "menu.about",
+                Action::Menu(MenuBuilder {
+                    title: MenuTitle {
+                        text: "menu.about".to_string(),
+                        size: MenuTitleSize::Heading,
+                        font: None,
+                    },
+                    buttons: vec![
+                        MenuButtonsBuilder::Paragraph {
+                            path: CREDITS_PATH.into(),
+                        },
+                        MenuButtonsBuilder::Static(MenuButtonRow::new(vec![MenuButton::new(
+                            "menu.back",
+                            Action::Back,
+                        )])),
+                    ],
+                    default_focus: None,
+                    layout: default(),
+                    cache: default(),
+                }),
+            )])),
+            MenuButtonsBuilder::Static(MenuButtonRow::new(vec![MenuButton {
+                shortcut: Some(KeyCode::Q),
+                ..MenuButton::new("menu.quit", Action::Quit)
+            }])),
+        ],
+        default_focus: None,
+        layout: default(),
+        cache: default(),
+    }
+}
+
+#[derive(Component)]
+struct MenuBackdrop;
+
+fn init_background(
+    mut commands: Commands,
+    backdrops: Query<Entity, With<MenuBackdrop>>,
+    images: Res<MenuImages>,
+    theme: Res<MenuTheme>,
+) {
+    for backdrop_e in backdrops.iter() {
+        commands.entity(backdrop_e).despawn_recursive();
+    }
+
+    let sprite = match &theme.background {
+        MenuBackground::None => return,
+        MenuBackground::Color(color) => SpriteBundle {
+            sprite: Sprite {
+                color: *color,
+                ..default()
+            },
+            ..default()
+        },
+        MenuBackground::Image => SpriteBundle {
+            texture: images.background.clone(),
+            ..default()
+        },
+    };
+
+    commands
+        .spawn_bundle(OrthographicCameraBundle::new_2d())
+        .insert(MenuBackdrop);
+    commands.spawn_bundle(sprite).insert(MenuBackdrop);
+}
+
+// The background and footer outlive menu rebuilds, so they're restyled in place
+fn restyle_main_menu(
+    commands: Commands,
+    backdrops: Query<Entity, With<MenuBackdrop>>,
+    mut footers: Query<&mut Text, With<MenuFooter>>,
+    images: Res<MenuImages>,
+    theme: Res<MenuTheme>,
+) {
+    if !theme.is_changed() || theme.is_added() {
+        return;
+    }
+
+    for mut text in footers.iter_mut() {
+        text.sections[0].style.font_size = theme.footer_size;
+        text.sections[0].style.color = theme.footer_color;
+    }
+
+    // There's no background over a paused game
+    if !backdrops.is_empty() {
+        init_background(commands, backdrops, images, theme);
+    }
+}
+
+fn resize_background(windows: Res<Windows>, mut sprites: Query<&mut Sprite, With<MenuBackdrop>>) {
+    let size = match windows.get_primary() {
+        Some(window) => Vec2::new(window.width(), window.height()),
+        None => return,
+    };
+
+    for mut sprite in sprites.iter_mut() {
+        if sprite.custom_size != Some(size) {
+            sprite.custom_size = Some(size);
+        }
+    }
+}
+
+fn term_camera(mut commands: Commands, cameras: Query<Entity, With<MenuCamera>>) {
+    for camera_e in cameras.iter() {
+        commands.entity(camera_e).despawn_recursive();
+    }
+}
+
+fn term_background(mut commands: Commands, backdrops: Query<Entity, With<MenuBackdrop>>) {
+    for backdrop_e in backdrops.iter() {
+        commands.entity(backdrop_e).despawn_recursive();
+    }
+}
+
+fn term_footer(mut commands: Commands, footers: Query<Entity, With<MenuFooter>>) {
+    for footer_e in footers.iter() {
+        commands.entity(footer_e).despawn_recursive();
+    }
+}
+
+#[allow(clippy::too_many_arguments)]
+fn init_menu(
+    mut commands: Commands,
+    mut menu_es: Option<ResMut<MenuEs>>,
+    trails: Query<&MenuTrail>,
+    fonts: Res<Fonts>,
+    theme: Res<MenuTheme>,
+    locale: Res<Locale>,
+    paths: Res<MenuPaths>,
+    config: Res<MenuConfig>,
+    next_menu: Option<Res<NextMenu>>,
+    mut spawned: EventWriter<MenuSpawned>,
+    mut state: ResMut<State<GameState>>,
+) {
+    // Depth in these spans is how many menus were open when it started
+    let span = info_span!(
+        "menu_enter",
+        menu = field::Empty,
+        depth = menu_es.as_ref().map_or(0, |menu_es| menu_es.len())
+    );
+    let _entered = span.enter();
+
+    let next_menu = match next_menu {
+        Some(next_menu) => next_menu.0.clone(),
+        None if menu_es.is_some() => {
+            error!(
+                "Entered {:?} without a NextMenu, returning to the menu below it",
+                GameState::Menu
+            );
+            // Whatever was queued first leaves the state instead
+            if let Err(err) = state.pop() {
+                error!("Couldn't return to the menu below: {}", err);
+                return;
+            }
+            commands.insert_resource(SkippedMenu);
+            return;
+        }
+        // Nothing below to go back to, so the main menu stands in
+        None => {
+            error!(
+                "Entered {:?} without a NextMenu, showing the main menu",
+                GameState::Menu
+            );
+            let mut main_menu = config.root.clone().unwrap_or_else(default_main_menu);
+            main_menu.title.text = config.title.clone();
+            main_menu
+        }
+    };
+    span.record("menu", &next_menu.title.text.as_str());
+
+    let trail = menu_es
+        .as_ref()
+        .map(|menu_es| breadcrumb(menu_es, &trails, &locale))
+        .unwrap_or_default();
+    let menu_e = next_menu.build(&locale, &paths).spawn(
+        &mut commands,
+        &fonts,
+        &theme,
+        &locale,
+        trail,
+        menu_es.is_some(),
+    );
+    commands.entity(menu_e).insert_bundle((
+        MenuSource(next_menu.clone()),
+        StagedMenu::new(MenuSwap::Push),
+    ));
+    spawned.send(MenuSpawned { entity: menu_e });
+    let entry = MenuEntry {
+        entity: menu_e,
+        focus: next_menu.default_focus,
+        layout: next_menu.layout,
+    };
+    if let Some(menu_es) = &mut menu_es {
+        menu_es.push(entry);
+    } else {
+        commands.insert_resource(MenuEs(vec![entry]));
+    }
+
+    commands.remove_resource::<NextMenu>();
+}
+
+fn open_world(commands: &mut Commands, asset_server: &AssetServer, world: &Path) {
+    info!("Opening world {}", world.display());
+    match asset_fs_path(asset_server, world) {
+        Some(path) => commands.insert_resource(OpenWorld::open(path)),
+        None => warn!("Can't track play time for {}", world.display()),
+    }
+    commands.insert_resource(CurrentWorld(world.to_path_buf()));
+    commands.insert_resource(OpeningGame);
+}
+
+#[derive(Component)]
+struct MenuIcon;
+
+#[derive(Clone, Component)]
+enum MenuParagraph {
+    Asset(PathBuf),
+    GameDetails(PathBuf),
+}
+
+fn game_details(game: &Path, asset_server: &AssetServer, locale: &Locale) -> String {
+    // Games don't need a manifest with anything in it, so they fall back to the folder name
+    let manifest = GameManifest::load(asset_server, game).unwrap_or_else(|err| {
+        warn!("{}, showing {} without details", err, game.display());
+        default()
+    });
+
+    let mut details = vec![manifest.name.unwrap_or_else(|| asset_name(game))];
+    if let Some(version) = manifest.version {
+        details.push(locale.get("game.version").replace("{version}", &version));
+    }
+    if let Some(author) = manifest.author {
+        details.push(locale.get("game.author").replace("{author}", &author));
+    }
+    if let Some(description) = manifest.description {
+        details.push(String::new());
+        details.push(description);
+    }
+    details.join("\n")
+}
+
+fn load_paragraphs(
+    mut commands: Commands,
+    asset_server: Res<AssetServer>,
+    locale: Res<Locale>,
+    mut paragraphs: Query<(Entity, &MenuParagraph, &mut Text)>,
+    mut toasts: EventWriter<MenuToast>,
+) {
+    for (paragraph_e, paragraph, mut text) in paragraphs.iter_mut() {
+        match paragraph {
+            MenuParagraph::Asset(path) => match block_on(asset_server.asset_io().load_path(path))
+                .map_err(|err| err.to_string())
+                .and_then(|bytes| String::from_utf8(bytes).map_err(|err| err.to_string()))
+            {
+                Ok(paragraph) => {
+                    text.sections[0].value =
+                        paragraph.replace("{version}", env!("CARGO_PKG_VERSION"));
+                }
+                Err(err) => toasts.send(MenuToast::new(
+                    ToastSeverity::Error,
+                    locale
+                        .get("menu.load_failed")
+                        .replace("{path}", &path.display().to_string())
+                        .replace("{error}", &err),
+                )),
+            },
+            MenuParagraph::GameDetails(game) => {
+                text.sections[0].value = game_details(game, &asset_server, &locale);
+            }
+        }
+        commands.entity(paragraph_e).remove::<MenuParagraph>();
+    }
+}
+
+fn fallback_icons(
+    asset_server: Res<AssetServer>,
+    images: Res<MenuImages>,
+    mut icons: Query<&mut UiImage, With<MenuIcon>>,
+) {
+    for mut icon in icons.iter_mut() {
+        if icon.0 != images.placeholder && asset_server.get_load_state(&icon.0) == LoadState::Failed
+        {
+            icon.0 = images.placeholder.clone();
+        }
+    }
+}
+
+// Text spawned before the font failed to load is switched over too
+fn fallback_fonts(
+    asset_server: Res<AssetServer>,
+    mut fonts: ResMut<Fonts>,
+    mut texts: Query<&mut Text>,
+) {
+    if fonts.font != fonts.fallback && asset_server.get_load_state(&fonts.font) == LoadState::Failed
+    {
+        error!("Couldn't load the menu font, using the embedded font");
+        if fonts.default == fonts.font {
+            fonts.default = fonts.fallback.clone();
+        }
+        fonts.font = fonts.fallback.clone();
+    }
+
+    for mut text in texts.iter_mut() {
+        let failed = text
+            .sections
+            .iter()
+            .any(|section| asset_server.get_load_state(&section.style.font) == LoadState::Failed);
+        if !failed {
+            continue;
+        }
+
+        for section in &mut text.sections {
+            if asset_server.get_load_state(&section.style.font) == LoadState::Failed {
+                section.style.font = fonts.fallback.clone();
+            }
+        }
+    }
+}
+
+fn select_locale_font(locale: Res<Locale>, mut fonts: ResMut<Fonts>, mut texts: Query<&mut Text>) {
+    if !locale.is_changed() {
+        return;
+    }
+
+    let font = match locale.font() {
+        Some(name) => fonts.named.get(name).cloned().unwrap_or_else(|| {
+            warn!("No font {} in {}, using the default font", name, FONTS_PATH);
+            fonts.default.clone()
+        }),
+        None => fonts.default.clone(),
+    };
+    if font == fonts.font {
+        return;
+    }
+
+    for mut text in texts.iter_mut() {
+        for section in &mut text.sections {
+            if section.style.font == fonts.font {
+                section.style.font = font.clone();
+            }
+        }
+    }
+    fonts.font = font;
+}
+
+#[derive(Component)]
+struct TargetColor(Color);
+
+const BUTTON_BLEND_SECS: f32 = 0.1;
+
+type ButtonColors<'w, 's, 'a> = Query<
+    'w,
+    's,
+    (
+        &'a Interaction,
+        &'a mut TargetColor,
+        &'a Action,
+        Option<&'a Focused>,
+        Option<&'a Disabled>,
+    ),
+>;
+
+type HoverableButtons<'w, 's, 'a> =
+    Query<'w, 's, &'a Interaction, (With<Button>, Without<Disabled>, Without<MenuScrim>)>;
+
+// Counts every hovered button, so moving from one button straight onto another keeps the hand
+fn update_cursor_icon(mut windows: ResMut<Windows>, buttons: HoverableButtons) {
+    let hovered = buttons
+        .iter()
+        .filter(|interaction| !matches!(interaction, Interaction::None))
+        .count();
+    let icon = if hovered > 0 {
+        CursorIcon::Hand
+    } else {
+        CursorIcon::Default
+    };
+
+    if let Some(window) = windows.get_primary_mut() {
+        if window.cursor_icon() != icon {
+            window.set_cursor_icon(icon);
+        }
+    }
+}
+
+fn reset_cursor_icon(mut windows: ResMut<Windows>) {
+    if let Some(window) = windows.get_primary_mut() {
+        window.set_cursor_icon(CursorIcon::Default);
+    }
+}
+
+fn color_buttons(
+    mut buttons: ButtonColors,
+    tabs: Query<&MenuTabs>,
+    theme: Res<MenuTheme>,
+    selection: Res<WorldSelection>,
+) {
+    for (interaction, mut target, action, focused, disabled) in buttons.iter_mut() {
+        let selected = match action {
+            Action::SelectWorld(world) => selection.0.contains(world),
+            Action::SelectTab(tabs_e, index) => {
+                tabs.get(*tabs_e).is_ok_and(|tabs| tabs.active == *index)
+            }
+            _ => false,
+        };
+        let new_target = match (disabled, interaction, focused) {
+            (Some(_), _, _) => theme.button_disabled_color,
+            (None, Interaction::Clicked, _) => theme.button_press_color,
+            (None, Interaction::Hovered, _) | (None, _, Some(_)) => theme.button_hover_color,
+            (None, Interaction::None, None) if selected => theme.button_selected_color,
+            (None, Interaction::None, None) => theme.button_color,
+        };
+
+        if target.0 != new_target {
+            target.0 = new_target;
+        }
+    }
+}
+
+type ButtonScales<'w, 's, 'a> = Query<
+    'w,
+    's,
+    (
+        &'a Interaction,
+        Option<&'a Focused>,
+        Option<&'a Disabled>,
+        &'a mut Transform,
+    ),
+    (With<Action>, Without<MenuScrim>),
+>;
+
+fn scale_buttons(time: Res<Time>, theme: Res<MenuTheme>, mut buttons: ButtonScales) {
+    let step = if theme.button_scale_secs > 0. {
+        (theme.button_hover_scale - 1.).abs() * time.delta_seconds() / theme.button_scale_secs
+    } else {
+        f32::INFINITY
+    };
+
+    for (interaction, focused, disabled, mut transform) in buttons.iter_mut() {
+        let target = match (disabled, interaction, focused) {
+            (Some(_), _, _) | (None, Interaction::None, None) => 1.,
+            _ => theme.button_hover_scale,
+        };
+        let scale = transform.scale.x;
+        if scale == target {
+            continue;
+        }
+
+        let scale = scale + (target - scale).clamp(-step, step);
+        transform.scale = Vec3::new(scale, scale, 1.);
+    }
+}
+
+fn blend_button_colors(time: Res<Time>, mut buttons: Query<(&TargetColor, &mut UiColor)>) {
+    let step = time.delta_seconds() / BUTTON_BLEND_SECS;
+    for (target, mut color) in buttons.iter_mut() {
+        if color.0 == target.0 {
+            continue;
+        }
+
+        let [r, g, b, a] = color.0.as_rgba_f32();
+        let [target_r, target_g, target_b, target_a] = target.0.as_rgba_f32();
+        let approach = |from: f32, to: f32| from + (to - from).clamp(-step, step);
+        color.0 = Color::rgba(
+            approach(r, target_r),
+            approach(g, target_g),
+            approach(b, target_b),
+            approach(a, target_a),
+        );
+    }
+}
+
+fn update_setting_text(
+    settings: Res<Settings>,
+    locale: Res<Locale>,
+    rebinding: Res<KeyRebinding>,
+    mut texts: Query<(&mut Text, &SettingButton, ChangeTrackers<SettingButton>)>,
+) {
+    for (mut text, setting, setting_tracker) in texts.iter_mut() {
+        if settings.is_changed()
+            || locale.is_changed()
+            || rebinding.is_changed()
+            || setting_tracker.is_added()
+        {
+            text.sections[0].value = setting.text(&settings, &locale, &rebinding);
+        }
+    }
+}
+
+fn capture_key_binding(
+    keys: Res<Input<KeyCode>>,
+    mut rebinding: ResMut<KeyRebinding>,
+    mut settings: ResMut<Settings>,
+    locale: Res<Locale>,
+    mut toasts: EventWriter<MenuToast>,
+) {
+    let key = match rebinding.0 {
+        Some(key) => key,
+        None => return,
+    };
+    let key_code = match keys.get_just_pressed().next() {
+        Some(key_code) => *key_code,
+        None => return,
+    };
+
+    settings.key_bindings.set(key, key_code);
+    rebinding.0 = None;
+
+    let conflicts = settings.key_bindings.conflicts(key);
+    if !conflicts.is_empty() {
+        let actions = conflicts
+            .into_iter()
+            .chain([key])
+            .map(|key| locale.get(key.label()))
+            .collect::<Vec<_>>()
+            .join(", ");
+        toasts.send(MenuToast::new(
+            ToastSeverity::Warning,
+            locale
+                .get("settings.key_conflict")
+                .replace("{key}", &format!("{:?}", key_code))
+                .replace("{actions}", &actions),
+        ));
+    }
+}
+
+fn scroll_menu(
+    mut wheels: EventReader<MouseWheel>,
+    menu_es: Option<Res<MenuEs>>,
+    mut scrolls: Query<(&mut MenuScroll, &mut Style, &Children, &Parent)>,
+    containers: Query<(&Node, &Parent)>,
+    nodes: Query<&Node>,
+) {
+    let dy = wheels
+        .iter()
+        .map(|wheel| match wheel.unit {
+            MouseScrollUnit::Line => wheel.y * SCROLL_LINE_HEIGHT,
+            MouseScrollUnit::Pixel => wheel.y,
+        })
+        .sum::<f32>();
+    let menu_e = match menu_es.as_ref().and_then(|menu_es| menu_es.top()) {
+        Some(menu_e) => menu_e,
+        None => return,
+    };
+    if dy == 0. {
+        return;
+    }
+
+    for (mut scroll, mut style, rows, parent) in scrolls.iter_mut() {
+        let (container, container_parent) = containers.get(**parent).unwrap();
+        if **container_parent != menu_e {
+            continue;
+        }
+
+        scroll.scroll_by(dy, &mut style, rows, container, &nodes);
+    }
+}
+
+fn scroll_to_focus(
+    focused: Query<(Entity, &Node, &GlobalTransform), Added<Focused>>,
+    menu_es: Option<Res<MenuEs>>,
+    parents: Query<&Parent>,
+    mut scrolls: Query<(&mut MenuScroll, &mut Style, &Children, &Parent)>,
+    containers: Query<(&Node, &GlobalTransform)>,
+    nodes: Query<&Node>,
+) {
+    let menu_e = match menu_es.as_ref().and_then(|menu_es| menu_es.top()) {
+        Some(menu_e) => menu_e,
+        None => return,
+    };
+
+    'focused: for (focused_e, node, transform) in focused.iter() {
+        if !in_menu(focused_e, menu_e, &parents) {
+            continue;
+        }
+
+        let mut panel_e = focused_e;
+        while scrolls.get(panel_e).is_err() {
+            match parents.get(panel_e) {
+                Ok(parent) => panel_e = **parent,
+                Err(_) => continue 'focused,
+            }
+        }
+        let (mut scroll, mut style, rows, parent) = scrolls.get_mut(panel_e).unwrap();
+
+        let (container, container_transform) = containers.get(**parent).unwrap();
+        let top_overflow = transform.translation.y + node.size.y / 2.
+            - (container_transform.translation.y + container.size.y / 2.);
+        let bottom_overflow = container_transform.translation.y
+            - container.size.y / 2.
+            - (transform.translation.y - node.size.y / 2.);
+        let dy = if top_overflow > 0. {
+            top_overflow
+        } else if bottom_overflow > 0. {
+            -bottom_overflow
+        } else {
+            continue;
+        };
+
+        scroll.scroll_by(dy, &mut style, rows, container, &nodes);
+    }
+}
+
+// Spawns menus in place of open ones, staged like any other so they only show once they're ready
+#[derive(SystemParam)]
+struct MenuRespawner<'w, 's> {
+    commands: Commands<'w, 's>,
+    staged: Query<'w, 's, (Entity, &'static StagedMenu)>,
+    fonts: Res<'w, Fonts>,
+    theme: Res<'w, MenuTheme>,
+    locale: Res<'w, Locale>,
+    paths: Res<'w, MenuPaths>,
+    spawned: EventWriter<'w, 's, MenuSpawned>,
+}
+
+impl MenuRespawner<'_, '_> {
+    fn respawn(&mut self, menu_es: &mut MenuEs, i: usize, source: MenuBuilder, trail: String) {
+        let old_e = menu_es[i].entity;
+        let menu_e = source.build(&self.locale, &self.paths).spawn(
+            &mut self.commands,
+            &self.fonts,
+            &self.theme,
+            &self.locale,
+            trail,
+            i > 0,
+        );
+        self.commands.entity(menu_e).insert(MenuSource(source));
+        self.spawned.send(MenuSpawned { entity: menu_e });
+
+        // A menu that was never shown can be dropped right away
+        if let Ok((
+            _,
+            StagedMenu {
+                swap: MenuSwap::Push,
+                ..
+            },
+        )) = self.staged.get(old_e)
+        {
+            self.commands
+                .entity(menu_e)
+                .insert(StagedMenu::new(MenuSwap::Push));
+            self.commands.entity(old_e).despawn_recursive();
+            menu_es[i].entity = menu_e;
+            return;
+        }
+
+        for (staged_e, staged) in self.staged.iter() {
+            if let MenuSwap::Replace { old, .. } = staged.swap {
+                if old == old_e {
+                    self.commands.entity(staged_e).despawn_recursive();
+                }
+            }
+        }
+
+        self.commands
+            .entity(menu_e)
+            .insert(StagedMenu::new(MenuSwap::Replace { old: old_e }));
+    }
+}
+
+// Swaps an open menu for another, so backing out of the new one skips the old
+struct ReplaceMenu {
+    menu: Entity,
+    with: MenuBuilder,
+}
+
+fn replace_menus(
+    mut replaces: EventReader<ReplaceMenu>,
+    menu_es: Option<ResMut<MenuEs>>,
+    trails: Query<&MenuTrail>,
+    mut respawner: MenuRespawner,
+) {
+    let mut menu_es = match menu_es {
+        Some(menu_es) => menu_es,
+        None => return,
+    };
+
+    for replace in replaces.iter() {
+        // Popped in the meantime
+        let i = match menu_es
+            .iter()
+            .position(|entry| entry.entity == replace.menu)
+        {
+            Some(i) => i,
+            None => continue,
+        };
+
+        info!("Replacing menu with {}", replace.with.title.text);
+        let trail = breadcrumb(&menu_es[..i], &trails, &respawner.locale);
+        menu_es[i].focus = replace.with.default_focus;
+        menu_es[i].layout = replace.with.layout;
+        respawner.respawn(&mut menu_es, i, replace.with.clone(), trail);
+    }
+}
+
+fn rebuild_menus(
+    mut rebuilds: EventReader<Rebuild>,
+    menu_es: Option<ResMut<MenuEs>>,
+    sources: Query<&MenuSource>,
+    trails: Query<&MenuTrail>,
+    mut respawner: MenuRespawner,
+) {
+    let all = match rebuilds
+        .iter()
+        .map(|rebuild| matches!(rebuild, Rebuild::All))
+        .reduce(|all, rebuild_all| all || rebuild_all)
+    {
+        Some(all) => all,
+        None => return,
+    };
+
+    let mut menu_es = match menu_es {
+        Some(menu_es) => menu_es,
+        None => return,
+    };
+
+    // Replacements aren't spawned yet, so the breadcrumbs come from the menus being replaced. Menus
+    // whose spawn commands haven't been applied yet are skipped, and built fresh anyway.
+    let titles = menu_es
+        .iter()
+        .map(|entry| {
+            trails
+                .get(entry.entity)
+                .ok()
+                .map(|trail| trail.title.clone())
+        })
+        .collect::<Vec<_>>();
+
+    for i in 0..menu_es.len() {
+        let source = match sources.get(menu_es[i].entity) {
+            Ok(source) => source,
+            Err(_) => continue,
+        };
+        if !all && !source.is_dynamic() {
+            continue;
+        }
+
+        let _span = info_span!(
+            "menu_rebuild",
+            menu = source.title.text.as_str(),
+            depth = menu_es.len(),
+            all
+        )
+        .entered();
+        source.invalidate();
+        let trail = join_breadcrumb(
+            titles[..i].iter().flatten().map(String::as_str),
+            &respawner.locale,
+        );
+        respawner.respawn(&mut menu_es, i, source.0.clone(), trail);
+    }
+}
+
+fn menu_ready(
+    menu_e: Entity,
+    children: &Query<&Children>,
+    loading: &Query<(), With<AssetListSource>>,
+    icons: &Query<&UiImage, With<MenuIcon>>,
+    asset_server: &AssetServer,
+) -> bool {
+    let mut stack = vec![menu_e];
+    while let Some(entity) = stack.pop() {
+        if loading.get(entity).is_ok() {
+            return false;
+        }
+
+        if let Ok(icon) = icons.get(entity) {
+            if let LoadState::NotLoaded | LoadState::Loading = asset_server.get_load_state(&icon.0)
+            {
+                return false;
+            }
+        }
+
+        if let Ok(entity_children) = children.get(entity) {
+            stack.extend(entity_children.iter());
+        }
+    }
+    true
+}
+
+#[allow(clippy::too_many_arguments)]
+fn swap_staged_menus(
+    mut commands: Commands,
+    time: Res<Time>,
+    menu_es: Option<ResMut<MenuEs>>,
+    mut staged: Query<(Entity, &mut StagedMenu)>,
+    nodes: Query<&Style, With<Node>>,
+    children: Query<&Children>,
+    loading: Query<(), With<AssetListSource>>,
+    icons: Query<&UiImage, With<MenuIcon>>,
+    asset_server: Res<AssetServer>,
+) {
+    let mut menu_es = match menu_es {
+        Some(menu_es) => menu_es,
+        None => return,
+    };
+
+    for (menu_e, mut staged) in staged.iter_mut() {
+        staged.timeout.tick(time.delta());
+        if !staged.timeout.finished()
+            && !menu_ready(menu_e, &children, &loading, &icons, &asset_server)
+        {
+            continue;
+        }
+
+        commands.entity(menu_e).remove::<StagedMenu>();
+        match &staged.swap {
+            MenuSwap::Push => {
+                // Popped before it was ready, and already on its way out
+                let i = match menu_es.iter().position(|entry| entry.entity == menu_e) {
+                    Some(i) => i,
+                    None => continue,
+                };
+
+                commands.entity(menu_e).insert(MenuTransition {
+                    timer: Timer::from_seconds(MENU_TRANSITION_SECS, false),
+                    from: MENU_SLIDE_RIGHT,
+                    to: 0.,
+                    end: MenuTransitionEnd::Show,
+                });
+                if let Some(prev) = i.checked_sub(1).map(|prev| menu_es[prev].entity) {
+                    let transition = MenuTransition::slide(
+                        nodes.get(prev).unwrap(),
+                        MENU_SLIDE_LEFT,
+                        MenuTransitionEnd::Hide,
+                    );
+                    commands.entity(prev).insert(transition);
+                }
+            }
+            MenuSwap::Replace { old } => {
+                let i = match menu_es.iter().position(|entry| entry.entity == *old) {
+                    Some(i) => i,
+                    // The rebuilt menu was popped in the meantime
+                    None => {
+                        commands.entity(menu_e).despawn_recursive();
+                        continue;
+                    }
+                };
+
+                let mut style = nodes.get(menu_e).unwrap().clone();
+                if i + 1 == menu_es.len() {
+                    style.display = Display::Flex;
+                    style.position.left = Val::Percent(0.);
+                } else {
+                    style.display = Display::None;
+                    style.position.left = Val::Percent(MENU_SLIDE_LEFT);
+                }
+                commands.entity(menu_e).insert(style);
+                commands.entity(*old).despawn_recursive();
+                menu_es[i].entity = menu_e;
+            }
+        }
+    }
+}
+
+fn relocalize_menus(
+    locale: Res<Locale>,
+    mut language: Local<String>,
+    mut rebuilds: EventWriter<Rebuild>,
+) {
+    if locale.language() == *language {
+        return;
+    }
+
+    if !language.is_empty() {
+        rebuilds.send(Rebuild::All);
+    }
+    *language = locale.language().to_string();
+}
+
+#[derive(Component)]
+struct MainTitle;
+
+const TITLE_PULSE_SECS: f64 = 4.;
+// How much darker the title gets at the bottom of a pulse
+const TITLE_PULSE_DEPTH: f32 = 0.25;
+// Brightness is rounded to this many steps, so the title's text is only re-laid out when the step
+// changes, not every frame
+const TITLE_PULSE_STEPS: f32 = 128.;
+
+fn pulse_main_title(
+    time: Res<Time>,
+    theme: Res<MenuTheme>,
+    mut titles: Query<(&mut Text, ChangeTrackers<MainTitle>), With<MainTitle>>,
+) {
+    if !theme.animate_title {
+        for (mut text, title_tracker) in titles.iter_mut() {
+            if theme.is_changed() || title_tracker.is_added() {
+                text.sections[0].style.color = theme.title_color;
+            }
+        }
+        return;
+    }
+
+    let phase = (time.seconds_since_startup() / TITLE_PULSE_SECS * TAU).cos() as f32;
+    let brightness = 1. - TITLE_PULSE_DEPTH * (1. - phase) / 2.;
+    let brightness = (brightness * TITLE_PULSE_STEPS).round() / TITLE_PULSE_STEPS;
+    let [red, green, blue, alpha] = theme.title_color.as_rgba_f32();
+    let color = Color::rgba(
+        red * brightness,
+        green * brightness,
+        blue * brightness,
+        alpha,
+    );
+    for (mut text, _) in titles.iter_mut() {
+        if text.sections[0].style.color != color {
+            text.sections[0].style.color = color;
+        }
+    }
+}
+
+fn animate_menus(
+    mut commands: Commands,
+    time: Res<Time>,
+    mut menus: Query<(Entity, &mut MenuTransition, &mut Style)>,
+) {
+    for (menu_e, mut transition, mut style) in menus.iter_mut() {
+        transition.timer.tick(time.delta());
+        let t = transition.timer.percent();
+        let eased = t * t * (3. - 2. * t);
+        style.position.left =
+            Val::Percent(transition.from + (transition.to - transition.from) * eased);
+
+        if transition.timer.finished() {
+            match transition.end {
+                MenuTransitionEnd::Show => (),
+                MenuTransitionEnd::Hide => style.display = Display::None,
+                MenuTransitionEnd::Despawn => {
+                    commands.entity(menu_e).despawn_recursive();
+                    continue;
+                }
+            }
+            commands.entity(menu_e).remove::<MenuTransition>();
+        }
+    }
+}
+
+// `GameState::LoadingWorld` replaces the menu a world was opened from, so the menus below it stay
+// in the state stack, but the game ends by setting `GameState::MainMenu`, which enters it fresh
+fn term_menus(
+    mut commands: Commands,
+    menu_es: Option<Res<MenuEs>>,
+    transitions: Query<Entity, With<MenuTransition>>,
+) {
+    let menu_es = match menu_es {
+        Some(menu_es) => menu_es,
+        None => return,
+    };
+
+    for MenuEntry { entity: menu_e, .. } in menu_es.iter() {
+        commands.entity(*menu_e).despawn_recursive();
+    }
+    for menu_e in transitions.iter() {
+        commands.entity(menu_e).despawn_recursive();
+    }
+    commands.remove_resource::<MenuEs>();
+}
+
+// Left by `init_menu` when it had nothing to show, so there's no menu for `term_menu` to pop
+struct SkippedMenu;
+
+#[allow(clippy::too_many_arguments)]
+fn term_menu(
+    mut commands: Commands,
+    skipped: Option<Res<SkippedMenu>>,
+    mut nodes: Query<&mut Style, With<Node>>,
+    transitions: Query<(Entity, &MenuTransition)>,
+    trails: Query<&MenuTrail>,
+    mut texts: Query<&mut Text>,
+    locale: Res<Locale>,
+    menu_es: Option<ResMut<MenuEs>>,
+    sources: Query<&MenuSource>,
+) {
+    let _span = info_span!(
+        "menu_exit",
+        menu = menu_es
+            .as_ref()
+            .map_or("", |menu_es| menu_es.top_title(&sources)),
+        depth = menu_es.as_ref().map_or(0, |menu_es| menu_es.len())
+    )
+    .entered();
+
+    if skipped.is_some() {
+        commands.remove_resource::<SkippedMenu>();
+        return;
+    }
+
+    // A rapid transition can exit the menu state again after the last menu is gone
+    let mut menu_es = match menu_es {
+        Some(menu_es) => menu_es,
+        None => return,
+    };
+    let popped_e = match menu_es.pop() {
+        Some(MenuEntry { entity, .. }) => entity,
+        None => {
+            commands.remove_resource::<MenuEs>();
+            return;
+        }
+    };
+    if let Some((MenuEntry { entity: menu_e, .. }, below)) = menu_es.split_last() {
+        // Either menu's spawn commands may not have applied yet, in which case it skips the slide
+        match nodes.get(popped_e) {
+            Ok(style) => {
+                let transition =
+                    MenuTransition::slide(style, MENU_SLIDE_RIGHT, MenuTransitionEnd::Despawn);
+                commands.entity(popped_e).insert(transition);
+            }
+            Err(_) => commands.entity(popped_e).despawn_recursive(),
+        }
+
+        if let Ok(mut style) = nodes.get_mut(*menu_e) {
+            style.display = Display::Flex;
+            let transition = MenuTransition::slide(&style, 0., MenuTransitionEnd::Show);
+            commands.entity(*menu_e).insert(transition);
+        }
+        if let Ok(trail) = trails.get(*menu_e) {
+            if let Ok(mut text) = texts.get_mut(trail.breadcrumb) {
+                text.sections[0].value = breadcrumb(below, &trails, &locale);
+            }
+        }
+    } else {
+        commands.entity(popped_e).despawn_recursive();
+        for (menu_e, transition) in transitions.iter() {
+            if let MenuTransitionEnd::Despawn = transition.end {
+                commands.entity(menu_e).despawn_recursive();
+            }
+        }
+        commands.remove_resource::<MenuEs>();
+    }
+}
@@ -0,0 +1,69 @@
+use bevy::{audio::AudioSink, prelude::*};
+
+use crate::{settings::Settings, state::GameState};
+
+use super::MenuSounds;
+
+pub struct MusicPlugin;
+
+impl Plugin for MusicPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_system(update_music_volume)
+            .add_system_set(SystemSet::on_enter(GameState::MainMenu).with_system(play_menu_music))
+            // Every way into the game goes through loading a world
+            .add_system_set(
+                SystemSet::on_enter(GameState::LoadingWorld).with_system(stop_menu_music),
+            );
+    }
+}
+
+struct MenuMusic(Handle<AudioSink>);
+
+fn music_volume(settings: &Settings) -> f32 {
+    settings.master_volume * settings.music_volume
+}
+
+fn play_menu_music(
+    mut commands: Commands,
+    music: Option<Res<MenuMusic>>,
+    audio: Res<Audio>,
+    sounds: Res<MenuSounds>,
+    sinks: Res<Assets<AudioSink>>,
+    settings: Res<Settings>,
+) {
+    if music.is_some() {
+        return;
+    }
+
+    let sink = audio.play_with_settings(
+        sounds.music.clone(),
+        PlaybackSettings::LOOP.with_volume(music_volume(&settings)),
+    );
+    // `Audio` only hands out a weak handle, which would let the sink drop
+    commands.insert_resource(MenuMusic(sinks.get_handle(sink)));
+}
+
+fn stop_menu_music(
+    mut commands: Commands,
+    music: Option<Res<MenuMusic>>,
+    sinks: Res<Assets<AudioSink>>,
+) {
+    if let Some(sink) = music.and_then(|music| sinks.get(&music.0)) {
+        sink.stop();
+    }
+    commands.remove_resource::<MenuMusic>();
+}
+
+fn update_music_volume(
+    settings: Res<Settings>,
+    music: Option<Res<MenuMusic>>,
+    sinks: Res<Assets<AudioSink>>,
+) {
+    if !settings.is_changed() {
+        return;
+    }
+
+    if let Some(sink) = music.and_then(|music| sinks.get(&music.0)) {
+        sink.set_volume(music_volume(&settings));
+    }
+}
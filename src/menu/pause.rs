@@ -0,0 +1,89 @@
+use bevy::prelude::*;
+
+use crate::state::GameState;
+
+use super::{
+    open_menu, settings_menu, Action, MenuBuilder, MenuButton, MenuButtonRow, MenuButtonsBuilder,
+    MenuCamera, MenuTheme, MenuTitle, MenuTitleSize,
+};
+
+pub struct PausePlugin;
+
+impl Plugin for PausePlugin {
+    fn build(&self, app: &mut App) {
+        app.add_system_set(SystemSet::on_enter(GameState::Paused).with_system(init_pause_menu))
+            .add_system_set(SystemSet::on_resume(GameState::Paused).with_system(resume_game))
+            .add_system_set(SystemSet::on_exit(GameState::Paused).with_system(term_pause_menu));
+    }
+}
+
+#[derive(Component)]
+struct PauseScrim;
+
+fn init_pause_menu(
+    mut commands: Commands,
+    cameras: Query<(), With<CameraUi>>,
+    theme: Res<MenuTheme>,
+    mut state: ResMut<State<GameState>>,
+) {
+    if cameras.is_empty() {
+        commands
+            .spawn_bundle(UiCameraBundle::default())
+            .insert(MenuCamera);
+    }
+    commands
+        .spawn_bundle(NodeBundle {
+            style: Style {
+                position_type: PositionType::Absolute,
+                size: Size::new(Val::Percent(100.), Val::Percent(100.)),
+                ..default()
+            },
+            color: theme.scrim_color.into(),
+            ..default()
+        })
+        .insert(PauseScrim);
+
+    // Whatever was queued first leaves `GameState::Paused`, taking the scrim with it
+    if let Err(err) = open_menu(&mut commands, &mut state, pause_menu()) {
+        warn!("Couldn't open the pause menu: {}", err);
+    }
+}
+
+fn pause_menu() -> MenuBuilder {
+    let button = |text: &str, action| {
+        MenuButtonsBuilder::Static(MenuButtonRow::new(vec![MenuButton::new(text, action)]))
+    };
+
+    MenuBuilder {
+        title: MenuTitle {
+            text: "menu.paused".to_string(),
+            size: MenuTitleSize::Heading,
+            font: None,
+        },
+        buttons: vec![
+            button("menu.resume", Action::Resume),
+            button("menu.settings", Action::Menu(settings_menu())),
+            button("menu.quit_to_menu", Action::QuitToMenu),
+        ],
+        default_focus: None,
+        layout: default(),
+        cache: default(),
+    }
+}
+
+// The pause menu was closed, either to keep playing or on the way out to the main menu
+fn resume_game(mut state: ResMut<State<GameState>>) {
+    if let Err(err) = state.pop() {
+        warn!("Couldn't leave the pause menu: {}", err);
+    }
+}
+
+fn term_pause_menu(
+    mut commands: Commands,
+    cameras: Query<Entity, With<MenuCamera>>,
+    scrims: Query<Entity, With<PauseScrim>>,
+) {
+    for entity in cameras.iter().chain(scrims.iter()) {
+        commands.entity(entity).despawn_recursive();
+    }
+}
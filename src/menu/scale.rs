@@ -0,0 +1,164 @@
+use bevy::prelude::*;
+
+use super::menu_root;
+use crate::state::GameState;
+
+pub struct ScalePlugin;
+
+impl Plugin for ScalePlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<MenuScale>().add_system_set(
+            SystemSet::on_update(GameState::Menu)
+                .with_system(update_scale)
+                .with_system(scale_nodes.after(update_scale)),
+        );
+    }
+}
+
+pub struct MenuScale(pub f32);
+
+impl Default for MenuScale {
+    fn default() -> Self {
+        Self(1.)
+    }
+}
+
+const REFERENCE_WIDTH: f32 = 1280.;
+const REFERENCE_HEIGHT: f32 = 720.;
+const MIN_SCALE: f32 = 0.5;
+const MAX_SCALE: f32 = 3.;
+
+// Bevy already maps `Val::Px` and font sizes to physical pixels by the window's scale factor, so
+// the menu scales by logical size, which changes when the window moves to a monitor with a
+// different scale factor. The size is compared instead of read from `WindowResized`, which would
+// be missed outside the menus, like when toggling fullscreen resizes the window a few frames late.
+fn update_scale(
+    mut size: Local<Option<Vec2>>,
+    windows: Res<Windows>,
+    mut scale: ResMut<MenuScale>,
+) {
+    let window = match windows.get_primary() {
+        Some(window) => window,
+        None => return,
+    };
+
+    let new_size = Vec2::new(window.width(), window.height());
+    if *size == Some(new_size) {
+        return;
+    }
+    *size = Some(new_size);
+
+    let new_scale = (new_size.x / REFERENCE_WIDTH)
+        .min(new_size.y / REFERENCE_HEIGHT)
+        .clamp(MIN_SCALE, MAX_SCALE);
+    if scale.0 != new_scale {
+        scale.0 = new_scale;
+    }
+}
+
+// Marks the root of UI that follows `MenuScale`. Everything spawned beneath it is scaled, and UI
+// from the host app or the game is left alone.
+#[derive(Component)]
+pub struct MenuNode;
+
+#[derive(Component)]
+struct BaseSize {
+    // What the values below were last scaled by
+    scale: f32,
+    size: Size<Val>,
+    min_size: Size<Val>,
+    margin: Rect<Val>,
+    padding: Rect<Val>,
+    bottom: Val,
+    font_sizes: Vec<f32>,
+}
+
+impl BaseSize {
+    // Values that no longer match the last scaling were set by another system, like a progress
+    // bar's width, and are left to it
+    fn apply(&mut self, scale: f32, style: &mut Style, text: Option<Mut<Text>>) {
+        let old = self.scale;
+        rescale(&mut style.size.width, self.size.width, old, scale);
+        rescale(&mut style.size.height, self.size.height, old, scale);
+        rescale(&mut style.min_size.width, self.min_size.width, old, scale);
+        rescale(&mut style.min_size.height, self.min_size.height, old, scale);
+        rescale_rect(&mut style.margin, self.margin, old, scale);
+        rescale_rect(&mut style.padding, self.padding, old, scale);
+        rescale(&mut style.position.bottom, self.bottom, old, scale);
+
+        if let Some(mut text) = text {
+            for (section, font_size) in text.sections.iter_mut().zip(&self.font_sizes) {
+                if section.style.font_size == font_size * old {
+                    section.style.font_size = font_size * scale;
+                }
+            }
+        }
+        self.scale = scale;
+    }
+}
+
+fn scale_val(val: Val, scale: f32) -> Val {
+    match val {
+        Val::Px(px) => Val::Px(px * scale),
+        val => val,
+    }
+}
+
+fn rescale(val: &mut Val, base: Val, old: f32, scale: f32) {
+    if *val == scale_val(base, old) {
+        *val = scale_val(base, scale);
+    }
+}
+
+fn rescale_rect(rect: &mut Rect<Val>, base: Rect<Val>, old: f32, scale: f32) {
+    rescale(&mut rect.left, base.left, old, scale);
+    rescale(&mut rect.right, base.right, old, scale);
+    rescale(&mut rect.top, base.top, old, scale);
+    rescale(&mut rect.bottom, base.bottom, old, scale);
+}
+
+type NewNodes<'w, 's, 'a> =
+    Query<'w, 's, (Entity, &'a mut Style, Option<&'a mut Text>), (Added<Node>, Without<BaseSize>)>;
+
+fn scale_nodes(
+    mut commands: Commands,
+    scale: Res<MenuScale>,
+    mut new_nodes: NewNodes,
+    mut nodes: Query<(&mut BaseSize, &mut Style, Option<&mut Text>)>,
+    menu_nodes: Query<(), With<MenuNode>>,
+    parents: Query<&Parent>,
+) {
+    for (node_e, mut style, text) in new_nodes.iter_mut() {
+        if !menu_nodes.contains(menu_root(node_e, &parents)) {
+            continue;
+        }
+
+        let mut base = BaseSize {
+            scale: 1.,
+            size: style.size,
+            min_size: style.min_size,
+            margin: style.margin,
+            padding: style.padding,
+            bottom: style.position.bottom,
+            font_sizes: text
+                .as_ref()
+                .map(|text| {
+                    text.sections
+                        .iter()
+                        .map(|section| section.style.font_size)
+                        .collect()
+                })
+                .unwrap_or_default(),
+        };
+        if scale.0 != 1. {
+            base.apply(scale.0, &mut style, text);
+        }
+        commands.entity(node_e).insert(base);
+    }
+
+    if scale.is_changed() {
+        for (mut base, mut style, text) in nodes.iter_mut() {
+            base.apply(scale.0, &mut style, text);
+        }
+    }
+}
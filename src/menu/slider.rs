@@ -0,0 +1,237 @@
+use bevy::prelude::*;
+use serde::Deserialize;
+
+use crate::{locale::Locale, settings::Settings, state::GameState};
+
+use super::{
+    focus::{self, Focusable, Focused, NavInput},
+    Fonts, MenuTheme,
+};
+
+pub struct SliderPlugin;
+
+impl Plugin for SliderPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_system_set(
+            SystemSet::on_update(GameState::Menu)
+                .with_system(drag_slider)
+                .with_system(nudge_slider.after(focus::read_nav_input))
+                .with_system(update_slider.after(drag_slider).after(nudge_slider)),
+        );
+    }
+}
+
+#[derive(Clone, Copy, Deserialize)]
+pub enum SliderAction {
+    MasterVolume,
+    MusicVolume,
+}
+
+impl SliderAction {
+    fn get(self, settings: &Settings) -> f32 {
+        match self {
+            SliderAction::MasterVolume => settings.master_volume,
+            SliderAction::MusicVolume => settings.music_volume,
+        }
+    }
+
+    fn set(self, settings: &mut Settings, value: f32) {
+        if self.get(settings) == value {
+            return;
+        }
+
+        match self {
+            SliderAction::MasterVolume => settings.master_volume = value,
+            SliderAction::MusicVolume => settings.music_volume = value,
+        }
+    }
+}
+
+#[derive(Clone, Component)]
+pub struct MenuSlider {
+    pub label: String,
+    pub min: f32,
+    pub max: f32,
+    pub action: SliderAction,
+}
+
+const SLIDER_STEPS: f32 = 10.;
+const SLIDER_TRACK_WIDTH: f32 = 25.;
+const SLIDER_TRACK_HEIGHT: f32 = 10.;
+const SLIDER_HANDLE_WIDTH: f32 = 20.;
+const SLIDER_HANDLE_HEIGHT: f32 = 30.;
+
+impl MenuSlider {
+    fn fraction(&self, settings: &Settings) -> f32 {
+        ((self.action.get(settings) - self.min) / (self.max - self.min)).clamp(0., 1.)
+    }
+
+    fn set_fraction(&self, settings: &mut Settings, fraction: f32) {
+        self.action.set(
+            settings,
+            (self.min + fraction * (self.max - self.min)).clamp(self.min, self.max),
+        );
+    }
+
+    fn text(&self, settings: &Settings, locale: &Locale) -> String {
+        format!(
+            "{}: {:.0}%",
+            locale.get(&self.label),
+            self.fraction(settings) * 100.
+        )
+    }
+
+    pub fn spawn(&self, parent: &mut ChildBuilder, fonts: &Fonts, theme: &MenuTheme) {
+        let mut label = None;
+        let mut handle = None;
+
+        parent
+            .spawn_bundle(NodeBundle {
+                style: Style {
+                    align_items: AlignItems::Center,
+                    justify_content: JustifyContent::Center,
+                    flex_shrink: 0.,
+                    size: Size::new(Val::Percent(100.), Val::Auto),
+                    ..default()
+                },
+                color: Color::NONE.into(),
+                ..default()
+            })
+            .with_children(|parent| {
+                label = Some(
+                    parent
+                        .spawn_bundle(TextBundle {
+                            style: Style {
+                                margin: theme.item_margin,
+                                ..default()
+                            },
+                            text: Text::with_section(
+                                String::new(),
+                                TextStyle {
+                                    font: fonts.font.clone(),
+                                    font_size: theme.button_text_size,
+                                    color: theme.title_color,
+                                },
+                                default(),
+                            ),
+                            ..default()
+                        })
+                        .id(),
+                );
+
+                let mut track = parent.spawn_bundle(NodeBundle {
+                    style: Style {
+                        margin: Rect {
+                            left: Val::Px(SLIDER_HANDLE_WIDTH),
+                            ..theme.item_margin
+                        },
+                        size: Size::new(
+                            Val::Percent(SLIDER_TRACK_WIDTH),
+                            Val::Px(SLIDER_TRACK_HEIGHT),
+                        ),
+                        ..default()
+                    },
+                    color: theme.button_hover_color.into(),
+                    ..default()
+                });
+                track.with_children(|parent| {
+                    handle = Some(
+                        parent
+                            .spawn_bundle(NodeBundle {
+                                style: Style {
+                                    position_type: PositionType::Absolute,
+                                    position: Rect {
+                                        bottom: Val::Px(
+                                            (SLIDER_TRACK_HEIGHT - SLIDER_HANDLE_HEIGHT) / 2.,
+                                        ),
+                                        ..default()
+                                    },
+                                    margin: Rect {
+                                        left: Val::Px(-SLIDER_HANDLE_WIDTH / 2.),
+                                        ..default()
+                                    },
+                                    size: Size::new(
+                                        Val::Px(SLIDER_HANDLE_WIDTH),
+                                        Val::Px(SLIDER_HANDLE_HEIGHT),
+                                    ),
+                                    ..default()
+                                },
+                                color: theme.button_color.into(),
+                                ..default()
+                            })
+                            .id(),
+                    );
+                });
+                track.insert_bundle((
+                    self.clone(),
+                    Interaction::default(),
+                    Focusable,
+                    SliderParts {
+                        label: label.unwrap(),
+                        handle: handle.unwrap(),
+                    },
+                ));
+            });
+    }
+}
+
+#[derive(Component)]
+struct SliderParts {
+    label: Entity,
+    handle: Entity,
+}
+
+fn drag_slider(
+    windows: Res<Windows>,
+    sliders: Query<(&MenuSlider, &Interaction, &Node, &GlobalTransform)>,
+    mut settings: ResMut<Settings>,
+) {
+    let cursor = match windows
+        .get_primary()
+        .and_then(|window| window.cursor_position())
+    {
+        Some(cursor) => cursor,
+        None => return,
+    };
+
+    for (slider, interaction, node, transform) in sliders.iter() {
+        if let Interaction::Clicked = interaction {
+            let left = transform.translation.x - node.size.x / 2.;
+            slider.set_fraction(&mut settings, (cursor.x - left) / node.size.x);
+        }
+    }
+}
+
+fn nudge_slider(
+    nav: Res<NavInput>,
+    sliders: Query<&MenuSlider, With<Focused>>,
+    mut settings: ResMut<Settings>,
+) {
+    let direction = nav.right as i32 - nav.left as i32;
+    if direction == 0 {
+        return;
+    }
+
+    for slider in sliders.iter() {
+        let fraction = slider.fraction(&settings) + direction as f32 / SLIDER_STEPS;
+        slider.set_fraction(&mut settings, fraction);
+    }
+}
+
+fn update_slider(
+    settings: Res<Settings>,
+    locale: Res<Locale>,
+    sliders: Query<(&MenuSlider, &SliderParts, ChangeTrackers<MenuSlider>)>,
+    mut texts: Query<&mut Text>,
+    mut styles: Query<&mut Style>,
+) {
+    for (slider, parts, slider_tracker) in sliders.iter() {
+        if !settings.is_changed() && !locale.is_changed() && !slider_tracker.is_added() {
+            continue;
+        }
+
+        texts.get_mut(parts.label).unwrap().sections[0].value = slider.text(&settings, &locale);
+        styles.get_mut(parts.handle).unwrap().position.left =
+            Val::Percent(slider.fraction(&settings) * 100.);
+    }
+}
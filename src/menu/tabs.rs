@@ -0,0 +1,187 @@
+use bevy::{prelude::*, ui::FocusPolicy};
+
+use crate::{locale::Locale, state::GameState};
+
+use super::{
+    actions::button_action,
+    focus::{self, Focused, NavInput},
+    in_menu, Action, Fonts, MenuButton, MenuButtonRow, MenuEs, MenuItem, MenuLayout, MenuTheme,
+};
+
+// Swaps between groups of items in place, instead of pushing a menu for each
+pub struct TabsPlugin;
+
+impl Plugin for TabsPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_event::<SelectTab>().add_system_set(
+            SystemSet::on_update(GameState::Menu).with_system(
+                switch_tabs
+                    .after(focus::read_nav_input)
+                    .after(button_action),
+            ),
+        );
+    }
+}
+
+#[derive(Clone)]
+pub struct MenuTab {
+    pub label: String,
+    pub items: Vec<MenuItem>,
+}
+
+// On the tab buttons and contents' container
+#[derive(Component)]
+pub struct MenuTabs {
+    pub active: usize,
+    contents: Vec<Entity>,
+}
+
+// Sent by the tab buttons' `Action::SelectTab`
+pub struct SelectTab {
+    pub tabs: Entity,
+    pub index: usize,
+}
+
+pub fn spawn_tabs(
+    tabs: Vec<MenuTab>,
+    parent: &mut ChildBuilder,
+    fonts: &Fonts,
+    theme: &MenuTheme,
+    locale: &Locale,
+) {
+    let mut container = parent.spawn_bundle(NodeBundle {
+        style: Style {
+            flex_direction: FlexDirection::ColumnReverse,
+            align_items: AlignItems::Center,
+            flex_shrink: 0.,
+            size: Size::new(Val::Percent(100.), Val::Auto),
+            ..default()
+        },
+        color: Color::NONE.into(),
+        focus_policy: FocusPolicy::Pass,
+        ..default()
+    });
+    let tabs_e = container.id();
+
+    let mut contents = Vec::new();
+    container.with_children(|parent| {
+        MenuButtonRow::new(
+            tabs.iter()
+                .enumerate()
+                .map(|(index, tab)| MenuButton {
+                    small: true,
+                    ..MenuButton::new(tab.label.clone(), Action::SelectTab(tabs_e, index))
+                })
+                .collect(),
+        )
+        .spawn(parent, fonts, theme, locale);
+
+        for (index, tab) in tabs.into_iter().enumerate() {
+            let content_e = parent
+                .spawn_bundle(NodeBundle {
+                    style: Style {
+                        // Hidden contents are left out of the focus order
+                        display: if index == 0 {
+                            Display::Flex
+                        } else {
+                            Display::None
+                        },
+                        flex_direction: FlexDirection::ColumnReverse,
+                        flex_shrink: 0.,
+                        size: Size::new(Val::Percent(100.), Val::Auto),
+                        ..default()
+                    },
+                    color: Color::NONE.into(),
+                    focus_policy: FocusPolicy::Pass,
+                    ..default()
+                })
+                .with_children(|parent| {
+                    for item in tab.items {
+                        item.spawn(parent, fonts, theme, locale);
+                    }
+                })
+                .id();
+            contents.push(content_e);
+        }
+    });
+
+    container.insert(MenuTabs {
+        active: 0,
+        contents,
+    });
+}
+
+// Left and right on a tab button switch to the tab beside it, in menus laid out in a column
+#[allow(clippy::too_many_arguments)]
+fn switch_tabs(
+    mut commands: Commands,
+    mut selects: EventReader<SelectTab>,
+    nav: Res<NavInput>,
+    menu_es: Option<Res<MenuEs>>,
+    focused: Query<(Entity, &Action), With<Focused>>,
+    tab_buttons: Query<(Entity, &Action)>,
+    parents: Query<&Parent>,
+    mut tabs: Query<&mut MenuTabs>,
+    mut styles: Query<&mut Style>,
+    theme: Res<MenuTheme>,
+) {
+    let mut selected = selects
+        .iter()
+        .map(|select| (select.tabs, select.index))
+        .collect::<Vec<_>>();
+
+    let step = nav.right as isize - nav.left as isize;
+    let mut stepped = None;
+    if let Some(menu) = menu_es.as_ref().and_then(|menu_es| menu_es.last()) {
+        if step != 0 && matches!(menu.layout, MenuLayout::Column) {
+            stepped = focused.iter().find_map(|(button_e, action)| match action {
+                Action::SelectTab(tabs_e, index) if in_menu(button_e, menu.entity, &parents) => {
+                    let len = tabs.get(*tabs_e).ok()?.contents.len() as isize;
+                    let next = *index as isize + step;
+                    let next = if theme.wrap_navigation {
+                        next.rem_euclid(len)
+                    } else {
+                        next.clamp(0, len - 1)
+                    } as usize;
+                    (next != *index).then(|| {
+                        commands.entity(button_e).remove::<Focused>();
+                        (*tabs_e, next)
+                    })
+                }
+                _ => None,
+            });
+        }
+    }
+    selected.extend(stepped);
+
+    for (tabs_e, index) in selected {
+        let mut menu_tabs = match tabs.get_mut(tabs_e) {
+            Ok(menu_tabs) => menu_tabs,
+            Err(_) => continue,
+        };
+        if index >= menu_tabs.contents.len() {
+            continue;
+        }
+
+        for (content, content_e) in menu_tabs.contents.iter().enumerate() {
+            if let Ok(mut style) = styles.get_mut(*content_e) {
+                style.display = if content == index {
+                    Display::Flex
+                } else {
+                    Display::None
+                };
+            }
+        }
+        menu_tabs.active = index;
+
+        // Focus follows the tab stepped to
+        if stepped == Some((tabs_e, index)) {
+            if let Some((button_e, _)) = tab_buttons.iter().find(|(_, action)| {
+                matches!(action, Action::SelectTab(button_tabs_e, button_index)
+                    if *button_tabs_e == tabs_e && *button_index == index)
+            }) {
+                commands.entity(button_e).insert(Focused);
+            }
+        }
+    }
+}
@@ -0,0 +1,1830 @@
+use std::{
+    env,
+    fmt::Write,
+    fs::{self, OpenOptions},
+    io::{self, ErrorKind, Write as _},
+    path::{Path, PathBuf},
+    process,
+    sync::{
+        atomic::{AtomicUsize, Ordering},
+        Arc,
+    },
+    thread,
+    time::{Duration, SystemTime},
+};
+
+use bevy::{
+    app::AppExit,
+    asset::{AssetIo, AssetIoError, AssetPlugin, BoxedFuture, FileAssetIo},
+    audio::AudioSink,
+    ecs::{event::Events, system::Command},
+    input::{keyboard::KeyboardInput, mouse::MouseWheel},
+    prelude::*,
+    tasks::TaskPool,
+    transform::TransformPlugin,
+    window::{WindowResized, WindowScaleFactorChanged},
+};
+
+use crate::{
+    archive::check_archive,
+    locale::Locale,
+    settings::{SavedSettings, Settings},
+    state::{BufferedState, GameState, StatePlugin, BUFFER_TIMEOUT_FRAMES},
+    world::{
+        copy_dir, CopyProgress, OpenWorld, WorldMeta, WorldPlugin, WORLD_META_FILE,
+        WORLD_THUMBNAIL_FILE,
+    },
+};
+
+use super::{
+    access::{AccessRole, AccessibleLabel, MenuAnnouncement},
+    asset_rows::{build_asset_rows, AssetButtonAction, AssetRows, AssetSort},
+    default_main_menu,
+    focus::Focused,
+    read_asset_dir,
+    scale::MenuScale,
+    settings_menu,
+    world_actions::{
+        duplicate_into, extract_into, purge_expired, restore_world, sanitize_world_name,
+        trash_world, WorldNameError, WorldSelection, CASE_INSENSITIVE_FS,
+    },
+    Action, AppendMenuButton, Fonts, Menu, MenuActionHandlers, MenuBuilder, MenuButton,
+    MenuButtonRow, MenuButtonsBuilder, MenuEs, MenuImageCache, MenuImages, MenuItem, MenuLayout,
+    MenuParagraph, MenuPaths, MenuPlugin, MenuSounds, MenuSource, OpenMenuPath, ReturnToMenu,
+    MAIN_MENU_PATH,
+};
+
+// One line per item, with the menus that buttons open indented under their row, so a change to
+// how menus are built shows up as a readable diff
+fn render_menu(menu: &Menu, locale: &Locale, paths: &MenuPaths) -> String {
+    let mut out = String::new();
+    write_menu(menu, locale, paths, 0, &mut out);
+    out
+}
+
+fn write_menu(menu: &Menu, locale: &Locale, paths: &MenuPaths, depth: usize, out: &mut String) {
+    writeln!(
+        out,
+        "{}menu {:?}{}",
+        "  ".repeat(depth),
+        menu.title.text,
+        match menu.layout {
+            MenuLayout::Column => "",
+            MenuLayout::Row => " in a row",
+        }
+    )
+    .unwrap();
+    for item in &menu.items {
+        write_item(item, locale, paths, depth + 1, out);
+    }
+}
+
+fn write_item(item: &MenuItem, locale: &Locale, paths: &MenuPaths, depth: usize, out: &mut String) {
+    let indent = "  ".repeat(depth);
+    match item {
+        MenuItem::Buttons(row) => {
+            writeln!(out, "{}{}", indent, render_buttons(&row.buttons)).unwrap();
+            for button in &row.buttons {
+                if let Action::Menu(builder) = &button.action {
+                    write_menu(&builder.build(locale, paths), locale, paths, depth + 1, out);
+                }
+            }
+        }
+        MenuItem::AssetList(list) => writeln!(
+            out,
+            "{}list {:?} from {} by {:?}{}{}",
+            indent,
+            list.action,
+            list.dirs
+                .iter()
+                .map(|dir| dir.display().to_string())
+                .collect::<Vec<_>>()
+                .join(", "),
+            list.sort,
+            if list.categories {
+                " with categories"
+            } else {
+                ""
+            },
+            list.columns
+                .map_or_else(String::new, |columns| format!(" in {} columns", columns)),
+        )
+        .unwrap(),
+        MenuItem::Slider(slider) => writeln!(
+            out,
+            "{}slider {:?} {}..{}",
+            indent, slider.label, slider.min, slider.max
+        )
+        .unwrap(),
+        MenuItem::Toggle(toggle) => writeln!(
+            out,
+            "{}toggle {:?} {:?}",
+            indent, toggle.label, toggle.action
+        )
+        .unwrap(),
+        MenuItem::TextInput(input) => {
+            writeln!(out, "{}text input {:?}", indent, input.placeholder).unwrap()
+        }
+        MenuItem::Paragraph(MenuParagraph::Asset(path) | MenuParagraph::GameDetails(path)) => {
+            writeln!(out, "{}paragraph {}", indent, path.display()).unwrap()
+        }
+        MenuItem::CopyProgress => writeln!(out, "{}copy progress", indent).unwrap(),
+        MenuItem::Tabs(tabs) => {
+            for tab in tabs {
+                writeln!(out, "{}tab {:?}", indent, tab.label).unwrap();
+                for item in &tab.items {
+                    write_item(item, locale, paths, depth + 1, out);
+                }
+            }
+        }
+    }
+}
+
+fn render_buttons(buttons: &[MenuButton]) -> String {
+    buttons
+        .iter()
+        .map(|button| {
+            format!(
+                "{:?} -> {}{}",
+                button.text,
+                render_action(&button.action),
+                if button.disabled { " (disabled)" } else { "" }
+            )
+        })
+        .collect::<Vec<_>>()
+        .join(", ")
+}
+
+fn render_action(action: &Action) -> String {
+    match action {
+        Action::Menu(builder) => format!("Menu({:?})", builder.title.text),
+        Action::Play(path)
+        | Action::Edit(path)
+        | Action::CreateWorld(path)
+        | Action::RenameWorld(path)
+        | Action::DuplicateWorld(path)
+        | Action::ExtractWorld(path)
+        | Action::ToggleFavorite(path)
+        | Action::SelectWorld(path) => format!("{}({})", action.name(), path.display()),
+        Action::Setting(setting) => format!("Setting({:?})", setting),
+        Action::Custom(name) => format!("Custom({:?})", name),
+        Action::Confirmed(action) => format!("Confirmed({})", render_action(action)),
+        _ => action.name().to_string(),
+    }
+}
+
+fn render_rows(rows: &AssetRows) -> String {
+    rows.iter()
+        .map(|(name, row)| format!("{}: {}\n", name, render_buttons(&row.buttons)))
+        .collect()
+}
+
+// Without the platform's data directory, so the tree doesn't depend on where it runs
+fn paths() -> MenuPaths {
+    MenuPaths {
+        data: None,
+        ..default()
+    }
+}
+
+fn main_menu() -> MenuBuilder {
+    let path = Path::new(env!("CARGO_MANIFEST_DIR"))
+        .join("assets")
+        .join(MAIN_MENU_PATH);
+    let mut main_menu: MenuBuilder = ron::de::from_bytes(&fs::read(path).unwrap()).unwrap();
+    main_menu.title.text = "voxmod".to_string();
+    main_menu
+}
+
+// Lists its paths as directories, so the menus can run without touching the disk
+struct MemoryAssetIo(Vec<PathBuf>);
+
+impl AssetIo for MemoryAssetIo {
+    fn load_path<'a>(&'a self, path: &'a Path) -> BoxedFuture<'a, Result<Vec<u8>, AssetIoError>> {
+        Box::pin(async move { Err(AssetIoError::NotFound(path.to_owned())) })
+    }
+
+    fn read_directory(
+        &self,
+        path: &Path,
+    ) -> Result<Box<dyn Iterator<Item = PathBuf>>, AssetIoError> {
+        let paths = self
+            .0
+            .iter()
+            .filter(|listed| listed.parent() == Some(path))
+            .cloned()
+            .collect::<Vec<_>>();
+        Ok(Box::new(paths.into_iter()))
+    }
+
+    fn is_directory(&self, _: &Path) -> bool {
+        true
+    }
+
+    fn watch_path_for_changes(&self, _: &Path) -> Result<(), AssetIoError> {
+        Ok(())
+    }
+
+    fn watch_for_changes(&self) -> Result<(), AssetIoError> {
+        Ok(())
+    }
+}
+
+// Counts the directory reads made through it
+struct CountingAssetIo<T>(T, Arc<AtomicUsize>);
+
+impl<T: AssetIo> AssetIo for CountingAssetIo<T> {
+    fn load_path<'a>(&'a self, path: &'a Path) -> BoxedFuture<'a, Result<Vec<u8>, AssetIoError>> {
+        self.0.load_path(path)
+    }
+
+    fn read_directory(
+        &self,
+        path: &Path,
+    ) -> Result<Box<dyn Iterator<Item = PathBuf>>, AssetIoError> {
+        self.1.fetch_add(1, Ordering::Relaxed);
+        self.0.read_directory(path)
+    }
+
+    fn is_directory(&self, path: &Path) -> bool {
+        self.0.is_directory(path)
+    }
+
+    fn watch_path_for_changes(&self, path: &Path) -> Result<(), AssetIoError> {
+        self.0.watch_path_for_changes(path)
+    }
+
+    fn watch_for_changes(&self) -> Result<(), AssetIoError> {
+        self.0.watch_for_changes()
+    }
+}
+
+// The menus without a window, renderer or audio, in `GameState::Loading` until `open_main_menu`
+fn menu_app() -> App {
+    menu_app_with(MenuPlugin::default())
+}
+
+fn menu_app_with(menu_plugin: MenuPlugin) -> App {
+    menu_app_over(
+        menu_plugin,
+        MemoryAssetIo(vec![
+            "worlds/alpha".into(),
+            "worlds/beta".into(),
+            "games/vanilla".into(),
+        ]),
+    )
+}
+
+// For the world actions that only work on the filesystem
+fn menu_app_over(menu_plugin: MenuPlugin, asset_io: impl AssetIo) -> App {
+    let mut app = App::new();
+    app.add_plugins(MinimalPlugins)
+        .add_plugin(TransformPlugin)
+        .add_plugin(AssetPlugin)
+        .insert_resource(AssetServer::new(asset_io, TaskPool::new()))
+        .add_asset::<Image>()
+        .add_asset::<Font>()
+        .add_asset::<AudioSink>()
+        .init_resource::<Input<KeyCode>>()
+        .init_resource::<Input<MouseButton>>()
+        .init_resource::<Input<GamepadButton>>()
+        .init_resource::<Touches>()
+        .init_resource::<Windows>()
+        .init_resource::<Audio>()
+        .init_resource::<Locale>()
+        .init_resource::<Settings>()
+        .insert_resource(SavedSettings(default()))
+        .insert_resource(paths())
+        .add_event::<ReceivedCharacter>()
+        .add_event::<KeyboardInput>()
+        .add_event::<MouseWheel>()
+        .add_event::<WindowResized>()
+        .add_event::<WindowScaleFactorChanged>()
+        .add_event::<AppExit>()
+        .insert_resource(Fonts {
+            font: default(),
+            default: default(),
+            fallback: default(),
+            named: default(),
+        })
+        .insert_resource(MenuSounds {
+            hover: default(),
+            click: default(),
+            music: default(),
+        })
+        .insert_resource(MenuImages {
+            background: default(),
+            placeholder: default(),
+        })
+        .add_plugin(StatePlugin)
+        .add_plugin(WorldPlugin)
+        .add_plugin(menu_plugin);
+    app
+}
+
+fn open_main_menu(app: &mut App) {
+    app.world
+        .resource_mut::<State<GameState>>()
+        .overwrite_set(GameState::MainMenu)
+        .unwrap();
+    run(app, 3);
+}
+
+// Asset lists are built on other threads, so each frame gives them a moment
+fn run(app: &mut App, frames: usize) {
+    for _ in 0..frames {
+        app.update();
+        thread::sleep(Duration::from_millis(5));
+    }
+}
+
+fn state(app: &App) -> GameState {
+    app.world.resource::<State<GameState>>().current().clone()
+}
+
+fn menu_depth(app: &App) -> usize {
+    app.world
+        .get_resource::<MenuEs>()
+        .map_or(0, |menu_es| menu_es.len())
+}
+
+fn top_menu_title(app: &App) -> String {
+    let menu_e = app.world.resource::<MenuEs>().top().unwrap();
+    app.world
+        .get::<MenuSource>(menu_e)
+        .unwrap()
+        .title
+        .text
+        .clone()
+}
+
+// The labels of the buttons in the top menu, with their buttons
+fn top_menu_buttons(app: &mut App) -> Vec<(Entity, String)> {
+    let menu_e = app.world.resource::<MenuEs>().top().unwrap();
+    let mut buttons = app
+        .world
+        .query_filtered::<(Entity, &AccessibleLabel), With<Button>>();
+    buttons
+        .iter(&app.world)
+        .filter(|&(button_e, _)| {
+            let mut entity = button_e;
+            loop {
+                if entity == menu_e {
+                    return true;
+                }
+                match app.world.get::<Parent>(entity) {
+                    Some(parent) => entity = **parent,
+                    None => return false,
+                }
+            }
+        })
+        .map(|(button_e, button_label)| (button_e, button_label.name.clone()))
+        .collect()
+}
+
+// The button with this label in the top menu
+fn find_button(app: &mut App, label: &str) -> Entity {
+    top_menu_buttons(app)
+        .into_iter()
+        .find(|(_, button_label)| button_label == label)
+        .map(|(button_e, _)| button_e)
+        .unwrap_or_else(|| panic!("No {:?} button in the top menu", label))
+}
+
+// Holds the pointer down on a button, as `ui_focus_system` would
+fn press_button(app: &mut App, label: &str) {
+    let button_e = find_button(app, label);
+    *app.world.get_mut::<Interaction>(button_e).unwrap() = Interaction::Clicked;
+    run(app, 1);
+}
+
+fn release_pointer(app: &mut App) {
+    let mut mouse_buttons = app.world.resource_mut::<Input<MouseButton>>();
+    mouse_buttons.press(MouseButton::Left);
+    mouse_buttons.release(MouseButton::Left);
+    run(app, 1);
+    app.world.resource_mut::<Input<MouseButton>>().clear();
+}
+
+// Clicks a button and waits out the transition it starts
+fn click(app: &mut App, label: &str) {
+    press_button(app, label);
+    release_pointer(app);
+    run(app, 4);
+}
+
+// Plays a world from the main menu, then leaves the game for the main menu again
+fn play_and_return(app: &mut App) {
+    click(app, "menu.play");
+    click(app, "alpha");
+    assert_eq!(state(app), GameState::Game);
+
+    app.world
+        .resource_mut::<Events<ReturnToMenu>>()
+        .send(ReturnToMenu);
+    run(app, 4);
+    assert_eq!(state(app), GameState::Menu);
+}
+
+fn ui_cameras(app: &mut App) -> usize {
+    app.world
+        .query_filtered::<(), With<CameraUi>>()
+        .iter(&app.world)
+        .count()
+}
+
+// A fresh asset folder for each test, since they run in parallel
+fn stub_assets(name: &str, files: &[&str]) -> PathBuf {
+    let dir = env::temp_dir().join(format!("voxmod-{}-{}", name, process::id()));
+    let _ = fs::remove_dir_all(&dir);
+    for file in files {
+        let path = dir.join(file);
+        fs::create_dir_all(path.parent().unwrap()).unwrap();
+        fs::write(path, "").unwrap();
+    }
+    dir
+}
+
+// Each file stored, or deflated if it's `true`, laid out the way common zip tools write them
+fn zip_archive(files: &[(&str, &[u8], bool)]) -> Vec<u8> {
+    let mut out = Vec::new();
+    let mut directory = Vec::new();
+    for (name, data, deflate) in files {
+        let (method, stored) = if *deflate {
+            (8u16, miniz_oxide::deflate::compress_to_vec(data, 6))
+        } else {
+            (0, data.to_vec())
+        };
+        let offset = out.len() as u32;
+        let mut fields = Vec::new();
+        fields.extend(0u16.to_le_bytes()); // Flags
+        fields.extend(method.to_le_bytes());
+        fields.extend([0; 4]); // Modified time and date
+        fields.extend(crc32fast::hash(data).to_le_bytes());
+        fields.extend((stored.len() as u32).to_le_bytes());
+        fields.extend((data.len() as u32).to_le_bytes());
+        fields.extend((name.len() as u16).to_le_bytes());
+        fields.extend(0u16.to_le_bytes()); // Extra field length
+
+        out.extend(0x04034b50u32.to_le_bytes());
+        out.extend(20u16.to_le_bytes());
+        out.extend(&fields);
+        out.extend(name.as_bytes());
+        out.extend(&stored);
+
+        directory.extend(0x02014b50u32.to_le_bytes());
+        directory.extend([20, 0, 20, 0]); // Versions made by and needed
+        directory.extend(&fields);
+        directory.extend([0; 10]); // Comment length, disk, attributes
+        directory.extend(offset.to_le_bytes());
+        directory.extend(name.as_bytes());
+    }
+
+    let directory_offset = out.len() as u32;
+    out.extend(&directory);
+    out.extend(0x06054b50u32.to_le_bytes());
+    out.extend([0; 4]); // Disks
+    out.extend((files.len() as u16).to_le_bytes());
+    out.extend((files.len() as u16).to_le_bytes());
+    out.extend((directory.len() as u32).to_le_bytes());
+    out.extend(directory_offset.to_le_bytes());
+    out.extend(0u16.to_le_bytes()); // Comment length
+    out
+}
+
+fn tar_header(name: &str, kind: u8, size: usize) -> [u8; 512] {
+    let mut header = [0; 512];
+    header[..name.len()].copy_from_slice(name.as_bytes());
+    header[100..107].copy_from_slice(b"0000644");
+    header[124..135].copy_from_slice(format!("{:011o}", size).as_bytes());
+    header[156] = kind;
+    header[257..263].copy_from_slice(b"ustar\0");
+    header[148..156].fill(b' ');
+    let checksum = header.iter().map(|&b| b as u32).sum::<u32>();
+    header[148..156].copy_from_slice(format!("{:06o}\0 ", checksum).as_bytes());
+    header
+}
+
+// Names too long for the header go in a GNU `L` entry before it, as GNU tar writes them
+fn tar_gz_archive(files: &[(&str, &[u8])]) -> Vec<u8> {
+    let mut tar = Vec::new();
+    let mut push = |name: &str, kind, data: &[u8]| {
+        tar.extend(tar_header(name, kind, data.len()));
+        tar.extend(data);
+        tar.resize(tar.len().next_multiple_of(512), 0);
+    };
+    for (name, data) in files {
+        if name.len() > 100 {
+            push("././@LongLink", b'L', format!("{}\0", name).as_bytes());
+            push(&name[..100], b'0', data);
+        } else {
+            push(name, b'0', data);
+        }
+    }
+    tar.extend([0; 1024]);
+
+    let mut out = vec![0x1f, 0x8b, 8, 0, 0, 0, 0, 0, 0, 255];
+    out.extend(miniz_oxide::deflate::compress_to_vec(&tar, 6));
+    out.extend(crc32fast::hash(&tar).to_le_bytes());
+    out.extend((tar.len() as u32).to_le_bytes());
+    out
+}
+
+// Writes the archive into a fresh asset folder and extracts it to `world` beside it, the way the
+// world list's Extract button does
+fn extract_archive(
+    name: &str,
+    file: &str,
+    archive: &[u8],
+    progress: &CopyProgress,
+) -> (PathBuf, io::Result<()>) {
+    let dir = stub_assets(name, &[]);
+    fs::create_dir_all(&dir).unwrap();
+    fs::write(dir.join(file), archive).unwrap();
+    let result = extract_into(
+        &dir.join(file),
+        &dir.join(".world"),
+        &dir.join("world"),
+        progress,
+    );
+    (dir, result)
+}
+
+#[test]
+fn main_menu_tree() {
+    let menu = main_menu().build(&Locale::default(), &paths());
+    assert_eq!(
+        render_menu(&menu, &Locale::default(), &paths()),
+        MAIN_MENU_TREE
+    );
+}
+
+// The fallback for a missing `MAIN_MENU_PATH` is kept in step with it
+#[test]
+fn default_main_menu_tree() {
+    let mut default_menu = default_main_menu();
+    default_menu.title.text = "voxmod".to_string();
+    assert_eq!(
+        render_menu(
+            &default_menu.build(&Locale::default(), &paths()),
+            &Locale::default(),
+            &paths()
+        ),
+        MAIN_MENU_TREE
+    );
+}
+
+#[test]
+fn world_rows() {
+    let dir = stub_assets(
+        "world-rows",
+        &[
+            &format!("worlds/alpha/{}", WORLD_META_FILE),
+            "worlds/Backup.tar.gz",
+        ],
+    );
+    let asset_server = AssetServer::new(FileAssetIo::new(&dir, false), TaskPool::new());
+
+    let rows = build_asset_rows(
+        AssetButtonAction::Play,
+        AssetSort::NameAscending,
+        false,
+        vec!["worlds/alpha".into(), "worlds/Backup.tar.gz".into()],
+        &asset_server,
+        "",
+        &default(),
+        &default(),
+        &TaskPool::new(),
+    );
+    fs::remove_dir_all(dir).unwrap();
+
+    assert_eq!(
+        render_rows(&rows),
+        "alpha: \"alpha\" -> Play(worlds/alpha), \"menu.pin\" -> ToggleFavorite(worlds/alpha), \
+         \"menu.rename\" -> Menu(\"menu.rename_world\"), \
+         \"menu.duplicate\" -> DuplicateWorld(worlds/alpha)\n\
+         backup.tar.gz: \"Backup.tar.gz\" -> ExtractWorld(worlds/Backup.tar.gz)\n"
+    );
+}
+
+// Rebuilding a list hands its buttons the handles it already has, rather than loading them again
+#[test]
+fn image_cache_reused_across_builds() {
+    let dir = stub_assets(
+        "image-cache",
+        &[
+            &format!("worlds/alpha/{}", WORLD_META_FILE),
+            &format!("worlds/alpha/{}", WORLD_THUMBNAIL_FILE),
+        ],
+    );
+    let asset_server = AssetServer::new(FileAssetIo::new(&dir, false), TaskPool::new());
+    let cache = MenuImageCache::default();
+    let build = || {
+        let rows = build_asset_rows(
+            AssetButtonAction::Play,
+            AssetSort::NameAscending,
+            false,
+            vec!["worlds/alpha".into()],
+            &asset_server,
+            "",
+            &default(),
+            &cache,
+            &TaskPool::new(),
+        );
+        let icons = rows
+            .iter()
+            .flat_map(|(_, row)| &row.buttons)
+            .filter_map(|button| button.icon.as_ref().map(|icon| icon.id))
+            .collect::<Vec<_>>();
+        (icons, cache.0.lock().unwrap().len())
+    };
+
+    let first = build();
+    let second = build();
+    fs::remove_dir_all(dir).unwrap();
+
+    // The thumbnail and the pin icon
+    assert_eq!(first.1, 2);
+    assert_eq!(first, second);
+}
+
+// Neither the order files were created in nor the order the platform lists them in shows up in
+// the rows
+#[test]
+fn deterministic_world_rows() {
+    let build = |dir: &Path| {
+        let asset_server = AssetServer::new(FileAssetIo::new(dir, false), TaskPool::new());
+        let mut worlds = read_asset_dir(&asset_server, Path::new("worlds"));
+        AssetSort::NameAscending.sort(&mut worlds, false, &asset_server);
+        render_rows(&build_asset_rows(
+            AssetButtonAction::Play,
+            AssetSort::NameAscending,
+            false,
+            worlds,
+            &asset_server,
+            "",
+            &default(),
+            &default(),
+            &TaskPool::new(),
+        ))
+    };
+    let worlds = ["gamma", "Alpha", "beta", "alpha", "delta"];
+    let meta = |world: &&str| format!("worlds/{}/{}", world, WORLD_META_FILE);
+    let files = worlds.iter().map(meta).collect::<Vec<_>>();
+    let shuffled_files = [3, 0, 4, 2, 1].map(|index| meta(&worlds[index]));
+
+    let dir = stub_assets(
+        "deterministic-rows",
+        &files.iter().map(String::as_str).collect::<Vec<_>>(),
+    );
+    let first = build(&dir);
+    let second = build(&dir);
+    fs::remove_dir_all(dir).unwrap();
+    let shuffled_dir = stub_assets(
+        "deterministic-rows-shuffled",
+        &shuffled_files
+            .iter()
+            .map(String::as_str)
+            .collect::<Vec<_>>(),
+    );
+    let shuffled = build(&shuffled_dir);
+    fs::remove_dir_all(shuffled_dir).unwrap();
+
+    assert_eq!(first.lines().count(), worlds.len());
+    assert_eq!(first, second);
+    assert_eq!(first, shuffled);
+}
+
+// Favorites go above the rest, which keep the order they're sorted in
+#[test]
+fn favorites_sort_first() {
+    let dir = stub_assets("favorites", &[]);
+    for (name, last_played, favorite) in
+        [("alpha", 1, false), ("beta", 3, false), ("gamma", 2, true)]
+    {
+        let world = dir.join("worlds").join(name);
+        fs::create_dir_all(&world).unwrap();
+        WorldMeta {
+            game: "games/vanilla".into(),
+            seed: 0,
+            last_played: Some(last_played),
+            play_time: 0,
+            favorite,
+        }
+        .save(&world)
+        .unwrap();
+    }
+    let asset_server = AssetServer::new(FileAssetIo::new(&dir, false), TaskPool::new());
+
+    let mut worlds = read_asset_dir(&asset_server, Path::new("worlds"));
+    AssetSort::LastPlayedDescending.sort(&mut worlds, true, &asset_server);
+    fs::remove_dir_all(dir).unwrap();
+
+    assert_eq!(
+        worlds,
+        ["worlds/gamma", "worlds/beta", "worlds/alpha"].map(PathBuf::from)
+    );
+}
+
+#[test]
+fn game_category_rows() {
+    let dir = stub_assets("game-rows", &["games/vanilla/game.ron", "games/mods/.keep"]);
+    let asset_server = AssetServer::new(FileAssetIo::new(&dir, false), TaskPool::new());
+
+    let rows = build_asset_rows(
+        AssetButtonAction::CreateWorld,
+        AssetSort::NameAscending,
+        true,
+        vec!["games/mods".into(), "games/vanilla".into()],
+        &asset_server,
+        "",
+        &default(),
+        &default(),
+        &TaskPool::new(),
+    );
+    fs::remove_dir_all(dir).unwrap();
+
+    assert_eq!(
+        render_rows(&rows),
+        "mods: \"mods\" -> Menu(\"mods\")\n\
+         vanilla: \"vanilla\" -> Menu(\"menu.new_world\")\n"
+    );
+}
+
+#[test]
+fn reserved_world_names() {
+    for name in ["CON", "nul", "COM1.txt"] {
+        assert!(
+            matches!(
+                sanitize_world_name(name, None, None),
+                Err(WorldNameError::Reserved(_))
+            ),
+            "{}",
+            name
+        );
+    }
+}
+
+#[test]
+fn world_names_with_bad_ends() {
+    for name in ["name.", "name "] {
+        assert!(
+            matches!(
+                sanitize_world_name(name, None, None),
+                Err(WorldNameError::BadEnd)
+            ),
+            "{:?}",
+            name
+        );
+    }
+}
+
+#[test]
+fn empty_world_names() {
+    for name in ["", "   "] {
+        assert!(
+            matches!(
+                sanitize_world_name(name, None, None),
+                Err(WorldNameError::Empty)
+            ),
+            "{:?}",
+            name
+        );
+    }
+}
+
+#[test]
+fn world_names_with_separators() {
+    for (name, illegal) in [("a/b", '/'), ("a\\b", '\\'), ("a:b", ':')] {
+        assert!(
+            matches!(
+                sanitize_world_name(name, None, None),
+                Err(WorldNameError::IllegalChar(char)) if char == illegal
+            ),
+            "{:?}",
+            name
+        );
+    }
+}
+
+#[test]
+fn unicode_world_name() {
+    assert!(sanitize_world_name("Wörld 世界", None, None).is_ok());
+}
+
+#[test]
+fn taken_world_names() {
+    let dir = stub_assets("taken-world-names", &["worlds/alpha/.keep"]);
+    let worlds = dir.join("worlds");
+
+    let exact = sanitize_world_name("alpha", Some(&worlds), None);
+    let other_case = sanitize_world_name("ALPHA", Some(&worlds), None);
+    let renaming = sanitize_world_name("Alpha", Some(&worlds), Some("alpha"));
+    fs::remove_dir_all(dir).unwrap();
+
+    assert!(matches!(exact, Err(WorldNameError::Taken)));
+    // Only a collision where the filesystem would fold the case
+    assert_eq!(
+        matches!(other_case, Err(WorldNameError::Taken)),
+        CASE_INSENSITIVE_FS
+    );
+    assert!(renaming.is_ok());
+}
+
+#[test]
+fn trash_and_restore_world() {
+    let dir = stub_assets(
+        "trash-world",
+        &[&format!("worlds/alpha/{}", WORLD_META_FILE)],
+    );
+    let asset_server = AssetServer::new(FileAssetIo::new(&dir, false), TaskPool::new());
+    let world = Path::new("worlds/alpha");
+
+    let before = SystemTime::now()
+        .duration_since(SystemTime::UNIX_EPOCH)
+        .unwrap()
+        .as_secs();
+    let trashed = trash_world(&asset_server, &paths(), world).unwrap();
+    let trashed_name = trashed.file_name().unwrap().to_string_lossy().into_owned();
+    let trashed_meta = trashed.join(WORLD_META_FILE).is_file();
+    let left_behind = dir.join(world).exists();
+
+    restore_world(&asset_server, world, &trashed).unwrap();
+    let restored = dir.join(world).join(WORLD_META_FILE).is_file();
+    let still_trashed = trashed.exists();
+    fs::remove_dir_all(dir).unwrap();
+
+    assert_eq!(trashed.parent().unwrap().file_name().unwrap(), ".trash");
+    let (stamp, name) = trashed_name.split_once('-').unwrap();
+    assert!(stamp.parse::<u64>().unwrap() >= before, "{}", trashed_name);
+    assert_eq!(name, "alpha");
+    assert!(trashed_meta);
+    assert!(!left_behind);
+    assert!(restored);
+    assert!(!still_trashed);
+}
+
+// A world made with the same name since is kept, and the trashed one stays in the trash
+#[test]
+fn restore_world_over_taken_name() {
+    let dir = stub_assets(
+        "restore-taken",
+        &[&format!("worlds/alpha/{}", WORLD_META_FILE)],
+    );
+    let asset_server = AssetServer::new(FileAssetIo::new(&dir, false), TaskPool::new());
+    let world = Path::new("worlds/alpha");
+
+    let trashed = trash_world(&asset_server, &paths(), world).unwrap();
+    fs::create_dir_all(dir.join(world)).unwrap();
+    let restored = restore_world(&asset_server, world, &trashed);
+    let still_trashed = trashed.join(WORLD_META_FILE).is_file();
+    let replaced = dir.join(world).join(WORLD_META_FILE).exists();
+    fs::remove_dir_all(dir).unwrap();
+
+    assert!(restored.is_err());
+    assert!(still_trashed);
+    assert!(!replaced);
+}
+
+const DAY_SECS: u64 = 24 * 60 * 60;
+
+#[test]
+fn purge_expired_trash() {
+    let now = 100 * DAY_SECS;
+    let expired = format!("{}-old", now - 31 * DAY_SECS);
+    let recent = format!("{}-new", now - 29 * DAY_SECS);
+    let dir = stub_assets(
+        "purge-trash",
+        &[
+            &format!("worlds/.trash/{}/{}", expired, WORLD_META_FILE),
+            &format!("worlds/.trash/{}/{}", recent, WORLD_META_FILE),
+            // Put there by hand, not by `trash_world`
+            &format!("worlds/.trash/keep/{}", WORLD_META_FILE),
+        ],
+    );
+    let trashes = [dir.join("worlds/.trash")];
+    let trashed = || {
+        let mut names = fs::read_dir(&trashes[0])
+            .unwrap()
+            .map(|entry| entry.unwrap().file_name())
+            .collect::<Vec<_>>();
+        names.sort();
+        names
+    };
+
+    purge_expired(&trashes, 0, now);
+    let kept_without_retention = trashed();
+    purge_expired(&trashes, 30, now);
+    let kept = trashed();
+    fs::remove_dir_all(dir).unwrap();
+
+    assert_eq!(kept_without_retention, [&*expired, &recent, "keep"]);
+    assert_eq!(kept, [&*recent, "keep"]);
+}
+
+#[test]
+fn open_world_keeps_broken_metadata() {
+    let dir = stub_assets("broken-world-meta", &[]);
+    let world = dir.join("worlds/alpha");
+    fs::create_dir_all(&world).unwrap();
+    // Cut off partway through a write
+    let broken = "(game: \"games/vanilla\", seed:";
+    fs::write(world.join(WORLD_META_FILE), broken).unwrap();
+
+    OpenWorld::open(world.clone());
+    let meta = fs::read_to_string(world.join(WORLD_META_FILE)).unwrap();
+    fs::remove_dir_all(dir).unwrap();
+
+    assert_eq!(meta, broken);
+}
+
+#[test]
+fn broken_world_returns_to_menu() {
+    let dir = stub_assets("broken-world-load", &[]);
+    let world = dir.join("worlds/alpha");
+    fs::create_dir_all(&world).unwrap();
+    fs::write(world.join(WORLD_META_FILE), "(game:").unwrap();
+
+    let mut app = menu_app();
+    open_main_menu(&mut app);
+    app.world.insert_resource(OpenWorld::open(world));
+    app.world
+        .resource_mut::<State<GameState>>()
+        .overwrite_replace(GameState::LoadingWorld)
+        .unwrap();
+    run(&mut app, 4);
+    fs::remove_dir_all(dir).unwrap();
+
+    assert_eq!(state(&app), GameState::Menu);
+    assert_eq!(menu_depth(&app), 1);
+    assert!(app.world.get_resource::<OpenWorld>().is_none());
+}
+
+// The copy is held partway through a file by reading it from a pipe, which is only written to
+// as the test goes
+#[cfg(unix)]
+#[test]
+fn cancelled_duplicate() {
+    let dir = stub_assets(
+        "cancelled-duplicate",
+        &[&format!("worlds/alpha/{}", WORLD_META_FILE)],
+    );
+    let region = dir.join("worlds/alpha/region");
+    assert!(process::Command::new("mkfifo")
+        .arg(&region)
+        .status()
+        .unwrap()
+        .success());
+    let asset_server = AssetServer::new(FileAssetIo::new(&dir, false), TaskPool::new());
+    let partial = dir.join("worlds/.alpha copy");
+    let copy = dir.join("worlds/alpha copy");
+
+    let progress = Arc::new(CopyProgress::default());
+    let copying = {
+        let (world, partial, copy, progress) = (
+            dir.join("worlds/alpha"),
+            partial.clone(),
+            copy.clone(),
+            progress.clone(),
+        );
+        thread::spawn(move || duplicate_into(&world, &partial, &copy, &progress))
+    };
+    let mut pipe = OpenOptions::new().write(true).open(&region).unwrap();
+    pipe.write_all(b"stone").unwrap();
+    while progress.copied_bytes.load(Ordering::Relaxed) < 5 {
+        thread::sleep(Duration::from_millis(1));
+    }
+    let listed_while_copying = read_asset_dir(&asset_server, Path::new("worlds"));
+    let partial_while_copying = partial.exists();
+
+    progress.cancel.store(true, Ordering::Relaxed);
+    pipe.write_all(b"stone").unwrap();
+    let result = copying.join().unwrap();
+    drop(pipe);
+    let listed = read_asset_dir(&asset_server, Path::new("worlds"));
+    let partial_left = partial.exists();
+    let copy_left = copy.exists();
+    fs::remove_dir_all(dir).unwrap();
+
+    assert!(partial_while_copying);
+    assert_eq!(listed_while_copying, [Path::new("worlds/alpha")]);
+    assert_eq!(result.unwrap_err().kind(), ErrorKind::Interrupted);
+    assert!(!partial_left);
+    assert!(!copy_left);
+    assert_eq!(listed, [Path::new("worlds/alpha")]);
+}
+
+// Running as root can read any file, so the unreadable one is a link to nothing
+#[cfg(unix)]
+#[test]
+fn copy_dir_skips_unreadable_files() {
+    let dir = stub_assets(
+        "copy-skips-unreadable",
+        &[&format!("alpha/{}", WORLD_META_FILE)],
+    );
+    let world = dir.join("alpha");
+    let region = b"stone ".repeat(20000);
+    fs::create_dir_all(world.join("region")).unwrap();
+    fs::create_dir_all(world.join("empty")).unwrap();
+    fs::write(world.join("region/0.bin"), &region).unwrap();
+    let unreadable = world.join("region/1.bin");
+    std::os::unix::fs::symlink(dir.join("missing"), &unreadable).unwrap();
+    let unreadable_size = fs::symlink_metadata(&unreadable).unwrap().len();
+
+    let progress = CopyProgress::default();
+    let result = copy_dir(&world, &dir.join("copy"), &progress);
+    let copied_region = fs::read(dir.join("copy/region/0.bin"));
+    let copied_meta = dir.join("copy").join(WORLD_META_FILE).exists();
+    let copied_empty = dir.join("copy/empty").is_dir();
+    let copied_unreadable = fs::symlink_metadata(dir.join("copy/region/1.bin")).is_ok();
+    fs::remove_dir_all(dir).unwrap();
+
+    result.unwrap();
+    assert_eq!(copied_region.unwrap(), region);
+    assert!(copied_meta);
+    assert!(copied_empty);
+    assert!(!copied_unreadable);
+    assert_eq!(progress.files.load(Ordering::Relaxed), 2);
+    assert_eq!(*progress.skipped.lock().unwrap(), [unreadable]);
+    assert_eq!(
+        progress.copied_bytes.load(Ordering::Relaxed),
+        progress.total_bytes.load(Ordering::Relaxed) - unreadable_size
+    );
+}
+
+// Both of the compression methods worlds are zipped with
+#[test]
+fn extract_zip() {
+    let region = b"stone ".repeat(1000);
+    let archive = zip_archive(&[
+        (
+            WORLD_META_FILE,
+            b"(game: \"games/vanilla\", seed: 1)",
+            false,
+        ),
+        ("region/0.bin", &region, true),
+    ]);
+    let progress = CopyProgress::default();
+    let (dir, result) = extract_archive("extract-zip", "alpha.zip", &archive, &progress);
+    let checked = check_archive(&dir.join("alpha.zip"));
+    let meta = fs::read_to_string(dir.join("world").join(WORLD_META_FILE));
+    let extracted_region = fs::read(dir.join("world/region/0.bin"));
+    let partial = dir.join(".world").exists();
+    fs::remove_dir_all(dir).unwrap();
+
+    result.unwrap();
+    checked.unwrap();
+    assert_eq!(meta.unwrap(), "(game: \"games/vanilla\", seed: 1)");
+    assert_eq!(extracted_region.unwrap(), region);
+    assert!(!partial);
+    assert_eq!(progress.files.load(Ordering::Relaxed), 2);
+    assert!(progress.skipped.lock().unwrap().is_empty());
+}
+
+// The folder the world was zipped from becomes the world
+#[test]
+fn extract_zip_with_wrapper_folder() {
+    let archive = zip_archive(&[
+        ("alpha/", b"", false),
+        (&format!("alpha/{}", WORLD_META_FILE), b"()", true),
+        ("alpha/region/0.bin", b"stone", false),
+    ]);
+    let (dir, result) = extract_archive(
+        "extract-zip-wrapper",
+        "alpha.zip",
+        &archive,
+        &CopyProgress::default(),
+    );
+    let checked = check_archive(&dir.join("alpha.zip"));
+    let meta = dir.join("world").join(WORLD_META_FILE).is_file();
+    let region = dir.join("world/region/0.bin").is_file();
+    let partial = dir.join(".world").exists();
+    fs::remove_dir_all(dir).unwrap();
+
+    result.unwrap();
+    checked.unwrap();
+    assert!(meta);
+    assert!(region);
+    assert!(!partial);
+}
+
+#[test]
+fn extract_tar_gz_with_long_name() {
+    let long_name = format!("alpha/region/{}.bin", "r".repeat(120));
+    let archive = tar_gz_archive(&[
+        (&format!("alpha/{}", WORLD_META_FILE), b"()"),
+        (&long_name, b"stone"),
+    ]);
+    let (dir, result) = extract_archive(
+        "extract-tar-gz",
+        "alpha.tar.gz",
+        &archive,
+        &CopyProgress::default(),
+    );
+    let checked = check_archive(&dir.join("alpha.tar.gz"));
+    let region = fs::read(
+        dir.join("world")
+            .join(Path::new(&long_name).strip_prefix("alpha").unwrap()),
+    );
+    fs::remove_dir_all(dir).unwrap();
+
+    result.unwrap();
+    checked.unwrap();
+    assert_eq!(region.unwrap(), b"stone");
+}
+
+// A tarball only counts as a world if it has one, found without extracting it
+#[test]
+fn tar_gz_without_world_meta() {
+    let dir = stub_assets("tar-gz-without-meta", &[]);
+    fs::create_dir_all(&dir).unwrap();
+    fs::write(
+        dir.join("photos.tar.gz"),
+        tar_gz_archive(&[("photos/cat.png", b"meow")]),
+    )
+    .unwrap();
+    let checked = check_archive(&dir.join("photos.tar.gz"));
+    fs::remove_dir_all(dir).unwrap();
+
+    assert_eq!(checked.unwrap_err().kind(), ErrorKind::InvalidData);
+}
+
+#[test]
+fn extract_skips_escaping_entries() {
+    for (file, archive) in [
+        (
+            "alpha.zip",
+            zip_archive(&[
+                (WORLD_META_FILE, b"()", false),
+                ("../escaped", b"", false),
+                ("/escaped", b"", false),
+            ]),
+        ),
+        (
+            "alpha.tar.gz",
+            tar_gz_archive(&[
+                (WORLD_META_FILE, b"()"),
+                ("../escaped", b""),
+                ("/escaped", b""),
+            ]),
+        ),
+    ] {
+        let progress = CopyProgress::default();
+        let (dir, result) = extract_archive("extract-escaping", file, &archive, &progress);
+        // Where they would have landed, beside and above the partial world
+        let escaped = dir.join("escaped").exists() || Path::new("/escaped").exists();
+        let meta = dir.join("world").join(WORLD_META_FILE).is_file();
+        fs::remove_dir_all(dir).unwrap();
+
+        result.unwrap();
+        assert!(!escaped, "{}", file);
+        assert!(meta, "{}", file);
+        assert_eq!(
+            *progress.skipped.lock().unwrap(),
+            [PathBuf::from("../escaped"), PathBuf::from("/escaped")],
+            "{}",
+            file
+        );
+    }
+}
+
+// Whatever was written before the archive ran out is cleaned up
+#[test]
+fn extract_truncated_archives() {
+    let region = (0..100_000u32)
+        .flat_map(|n| n.wrapping_mul(2654435761).to_le_bytes())
+        .collect::<Vec<_>>();
+    for (file, archive) in [
+        (
+            "alpha.zip",
+            zip_archive(&[(WORLD_META_FILE, b"()", false), ("region", &region, true)]),
+        ),
+        (
+            "alpha.tar.gz",
+            tar_gz_archive(&[(WORLD_META_FILE, b"()"), ("region", &region)]),
+        ),
+    ] {
+        let truncated = &archive[..archive.len() / 2];
+        let (dir, result) = extract_archive(
+            "extract-truncated",
+            file,
+            truncated,
+            &CopyProgress::default(),
+        );
+        let checked = check_archive(&dir.join(file));
+        let partial = dir.join(".world").exists();
+        let world = dir.join("world").exists();
+        fs::remove_dir_all(dir).unwrap();
+
+        assert!(result.is_err(), "{}", file);
+        // The zip's directory is at its end, but a tarball's world.ron comes before the cut
+        assert_eq!(checked.is_err(), file.ends_with(".zip"), "{}", file);
+        assert!(!partial, "{}", file);
+        assert!(!world, "{}", file);
+    }
+}
+
+#[test]
+fn extract_zip_with_bad_crc() {
+    let mut archive = zip_archive(&[(WORLD_META_FILE, b"()", false)]);
+    // The last byte of the stored data
+    let data_end = 30 + WORLD_META_FILE.len() + 1;
+    archive[data_end] ^= 0xff;
+    let (dir, result) = extract_archive(
+        "extract-bad-crc",
+        "alpha.zip",
+        &archive,
+        &CopyProgress::default(),
+    );
+    let partial = dir.join(".world").exists();
+    let world = dir.join("world").exists();
+    fs::remove_dir_all(dir).unwrap();
+
+    assert_eq!(result.unwrap_err().kind(), ErrorKind::InvalidData);
+    assert!(!partial);
+    assert!(!world);
+}
+
+#[test]
+fn cancelled_extract() {
+    let archive = zip_archive(&[(WORLD_META_FILE, b"()", false)]);
+    let progress = CopyProgress::default();
+    progress.cancel.store(true, Ordering::Relaxed);
+    let (dir, result) = extract_archive("extract-cancelled", "alpha.zip", &archive, &progress);
+    let partial = dir.join(".world").exists();
+    let world = dir.join("world").exists();
+    fs::remove_dir_all(dir).unwrap();
+
+    assert_eq!(result.unwrap_err().kind(), ErrorKind::Interrupted);
+    assert!(!partial);
+    assert!(!world);
+}
+
+// Worlds picked in the world manager go together, and come back together
+#[test]
+fn delete_selected_worlds_and_undo() {
+    let dir = stub_assets(
+        "delete-selected",
+        &[
+            &format!("worlds/alpha/{}", WORLD_META_FILE),
+            &format!("worlds/beta/{}", WORLD_META_FILE),
+            &format!("worlds/gamma/{}", WORLD_META_FILE),
+        ],
+    );
+    let mut app = menu_app_over(MenuPlugin::default(), FileAssetIo::new(&dir, false));
+    open_main_menu(&mut app);
+    click(&mut app, "menu.play");
+    click(&mut app, "menu.manage_worlds");
+    run(&mut app, 4);
+    let listed = |app: &mut App| {
+        let mut worlds = top_menu_buttons(app)
+            .into_iter()
+            .map(|(_, label)| label)
+            .filter(|label| ["alpha", "beta", "gamma"].contains(&label.as_str()))
+            .collect::<Vec<_>>();
+        worlds.sort();
+        worlds
+    };
+    assert_eq!(listed(&mut app), ["alpha", "beta", "gamma"]);
+
+    click(&mut app, "alpha");
+    click(&mut app, "beta");
+    click(&mut app, "menu.delete_selected");
+    assert_eq!(top_menu_title(&app), "menu.delete_worlds");
+    click(&mut app, "menu.delete");
+    run(&mut app, 4);
+    let after_delete = listed(&mut app);
+    let deleted_on_disk = ["alpha", "beta"].map(|world| dir.join("worlds").join(world).exists());
+    let selection_cleared = app.world.resource::<WorldSelection>().0.is_empty();
+
+    // The undo is on the toast, outside the menus
+    let undo_e = app
+        .world
+        .query_filtered::<(Entity, &AccessibleLabel), With<Button>>()
+        .iter(&app.world)
+        .find(|(_, label)| label.name == "menu.undo")
+        .map(|(undo_e, _)| undo_e)
+        .unwrap();
+    *app.world.get_mut::<Interaction>(undo_e).unwrap() = Interaction::Clicked;
+    run(&mut app, 1);
+    release_pointer(&mut app);
+    run(&mut app, 4);
+    let after_undo = listed(&mut app);
+    let restored_on_disk = ["alpha", "beta"].map(|world| dir.join("worlds").join(world).exists());
+    fs::remove_dir_all(dir).unwrap();
+
+    assert_eq!(top_menu_title(&app), "menu.manage_worlds");
+    assert_eq!(after_delete, ["gamma"]);
+    assert_eq!(deleted_on_disk, [false, false]);
+    assert!(selection_cleared);
+    assert_eq!(after_undo, ["alpha", "beta", "gamma"]);
+    assert_eq!(restored_on_disk, [true, true]);
+}
+
+#[test]
+fn click_into_sub_menu() {
+    let mut app = menu_app();
+    open_main_menu(&mut app);
+    assert_eq!(menu_depth(&app), 1);
+
+    click(&mut app, "menu.play");
+    assert_eq!(menu_depth(&app), 2);
+    assert_eq!(top_menu_title(&app), "menu.choose_world");
+}
+
+#[test]
+fn click_back() {
+    let mut app = menu_app();
+    open_main_menu(&mut app);
+    click(&mut app, "menu.play");
+
+    click(&mut app, "menu.back");
+    assert_eq!(menu_depth(&app), 1);
+    assert_eq!(state(&app), GameState::Menu);
+}
+
+#[test]
+fn press_activates_on_release() {
+    let mut app = menu_app();
+    open_main_menu(&mut app);
+
+    press_button(&mut app, "menu.play");
+    run(&mut app, 4);
+    assert_eq!(menu_depth(&app), 1);
+
+    release_pointer(&mut app);
+    run(&mut app, 4);
+    assert_eq!(menu_depth(&app), 2);
+}
+
+// Both buttons are pressed and released in one frame, but only one menu can be pushed
+#[test]
+fn two_menu_clicks_in_one_frame() {
+    let mut app = menu_app();
+    open_main_menu(&mut app);
+    for label in ["menu.play", "menu.settings"] {
+        let button_e = find_button(&mut app, label);
+        *app.world.get_mut::<Interaction>(button_e).unwrap() = Interaction::Clicked;
+    }
+
+    release_pointer(&mut app);
+    run(&mut app, 4);
+    assert_eq!(state(&app), GameState::Menu);
+    assert_eq!(menu_depth(&app), 2);
+    assert!(matches!(
+        top_menu_title(&app).as_str(),
+        "menu.choose_world" | "menu.settings"
+    ));
+}
+
+// The stub assets only have one game, so choosing it is skipped
+#[test]
+fn create_world_from_only_game() {
+    let mut app = menu_app();
+    open_main_menu(&mut app);
+    click(&mut app, "menu.play");
+
+    click(&mut app, "menu.create_world");
+    assert_eq!(menu_depth(&app), 3);
+    assert_eq!(top_menu_title(&app), "menu.new_world");
+
+    click(&mut app, "menu.back");
+    assert_eq!(menu_depth(&app), 2);
+    assert_eq!(top_menu_title(&app), "menu.choose_world");
+}
+
+struct CustomRan;
+
+// The button is built the way a mod outside the crate would build it
+#[test]
+fn custom_button_runs_handler() {
+    let mut app = menu_app();
+    open_main_menu(&mut app);
+    app.world
+        .resource_mut::<MenuActionHandlers>()
+        .register("test.custom", |world| world.insert_resource(CustomRan));
+    let mut menu: MenuBuilder =
+        ron::from_str("(title: (text: \"custom\", size: Heading), buttons: [])").unwrap();
+    menu.buttons
+        .push(MenuButtonsBuilder::Static(MenuButtonRow::new(vec![
+            MenuButton::custom("test.custom", "test.custom"),
+        ])));
+    app.world
+        .resource_mut::<Events<OpenMenuPath>>()
+        .send(OpenMenuPath(vec![menu]));
+    run(&mut app, 6);
+    assert_eq!(top_menu_title(&app), "custom");
+
+    click(&mut app, "test.custom");
+    assert!(app.world.contains_resource::<CustomRan>());
+}
+
+// Like a mod's entry, added to a menu it didn't build
+#[test]
+fn appended_button_runs_handler() {
+    let mut app = menu_app();
+    open_main_menu(&mut app);
+    app.world
+        .resource_mut::<MenuActionHandlers>()
+        .register("test.appended", |world| world.insert_resource(CustomRan));
+
+    let menu_e = app.world.resource::<MenuEs>().top().unwrap();
+    AppendMenuButton {
+        menu: menu_e,
+        row: MenuButtonRow::new(vec![MenuButton::custom("test.appended", "test.appended")]),
+    }
+    .write(&mut app.world);
+    run(&mut app, 1);
+
+    click(&mut app, "test.appended");
+    assert!(app.world.contains_resource::<CustomRan>());
+}
+
+// Another project's branding, built in code
+#[test]
+fn configured_root_menu() {
+    let about = MenuBuilder::new("brand.about")
+        .with_row(MenuButtonRow::new(vec![MenuButton::back("menu.back")]));
+    let root = MenuBuilder::new("ignored").with_row(MenuButtonRow::new(vec![
+        MenuButton::menu("brand.about", about),
+        MenuButton::quit("menu.quit"),
+    ]));
+    let mut app = menu_app_with(MenuPlugin::new(root, "brand"));
+    open_main_menu(&mut app);
+    assert_eq!(top_menu_title(&app), "brand");
+
+    click(&mut app, "brand.about");
+    assert_eq!(top_menu_title(&app), "brand.about");
+    click(&mut app, "menu.back");
+    assert_eq!(menu_depth(&app), 1);
+}
+
+#[test]
+fn click_play() {
+    let mut app = menu_app();
+    open_main_menu(&mut app);
+    click(&mut app, "menu.play");
+
+    click(&mut app, "alpha");
+    assert_eq!(state(&app), GameState::Game);
+    assert_eq!(menu_depth(&app), 0);
+}
+
+// The cached menu gets a button of its own, which only shows if the second visit uses the cache
+#[test]
+fn settings_menu_is_cached() {
+    let reads = Arc::new(AtomicUsize::new(0));
+    let mut app = menu_app_over(
+        MenuPlugin::default(),
+        CountingAssetIo(
+            MemoryAssetIo(vec!["worlds/alpha".into(), "games/vanilla".into()]),
+            reads.clone(),
+        ),
+    );
+    open_main_menu(&mut app);
+    click(&mut app, "menu.settings");
+    assert_eq!(top_menu_title(&app), "menu.settings");
+    click(&mut app, "menu.back");
+
+    let settings_button = find_button(&mut app, "menu.settings");
+    match app.world.get::<Action>(settings_button).unwrap() {
+        Action::Menu(settings) => settings
+            .cache
+            .0
+            .lock()
+            .unwrap()
+            .as_mut()
+            .expect("Settings wasn't cached")
+            .items
+            .push(MenuItem::Buttons(MenuButtonRow::new(vec![
+                MenuButton::new("test.cached", Action::Resume),
+            ]))),
+        _ => panic!("Settings isn't a menu"),
+    }
+
+    let reads_before = reads.load(Ordering::Relaxed);
+    click(&mut app, "menu.settings");
+    assert_eq!(top_menu_title(&app), "menu.settings");
+    assert!(top_menu_buttons(&mut app)
+        .iter()
+        .any(|(_, label)| label == "test.cached"));
+    assert_eq!(reads.load(Ordering::Relaxed), reads_before);
+}
+
+// Like a Settings hotkey pressed mid-game
+#[test]
+fn open_menu_path_from_game() {
+    let mut app = menu_app();
+    open_main_menu(&mut app);
+    click(&mut app, "menu.play");
+    click(&mut app, "alpha");
+    assert_eq!(state(&app), GameState::Game);
+
+    app.world
+        .resource_mut::<Events<OpenMenuPath>>()
+        .send(OpenMenuPath(vec![settings_menu()]));
+    run(&mut app, 10);
+    assert_eq!(state(&app), GameState::Menu);
+    assert_eq!(menu_depth(&app), 2);
+    assert_eq!(top_menu_title(&app), "menu.settings");
+
+    click(&mut app, "menu.back");
+    assert_eq!(top_menu_title(&app), "menu.paused");
+    click(&mut app, "menu.resume");
+    assert_eq!(state(&app), GameState::Game);
+}
+
+#[test]
+fn one_ui_camera_after_a_game() {
+    let mut app = menu_app();
+    open_main_menu(&mut app);
+    assert_eq!(ui_cameras(&mut app), 1);
+
+    play_and_return(&mut app);
+    assert_eq!(ui_cameras(&mut app), 1);
+}
+
+#[test]
+fn host_ui_camera() {
+    let mut app = menu_app();
+    let host_camera_e = app
+        .world
+        .spawn()
+        .insert_bundle(UiCameraBundle::default())
+        .id();
+    open_main_menu(&mut app);
+    assert_eq!(ui_cameras(&mut app), 1);
+
+    play_and_return(&mut app);
+    assert_eq!(ui_cameras(&mut app), 1);
+    assert!(app.world.get_entity(host_camera_e).is_some());
+}
+
+// Nothing said where to go from `GameState::Buffer`, so it gives up and goes back
+#[test]
+fn buffer_without_state_times_out() {
+    let mut app = menu_app();
+    open_main_menu(&mut app);
+    app.world
+        .resource_mut::<State<GameState>>()
+        .push(GameState::Buffer)
+        .unwrap();
+
+    for _ in 0..BUFFER_TIMEOUT_FRAMES {
+        app.update();
+        assert_eq!(state(&app), GameState::Buffer);
+    }
+    app.update();
+    assert_eq!(state(&app), GameState::Menu);
+    assert_eq!(menu_depth(&app), 1);
+
+    // Each stay in Buffer gets the full timeout
+    app.world
+        .resource_mut::<State<GameState>>()
+        .push(GameState::Buffer)
+        .unwrap();
+    for _ in 0..BUFFER_TIMEOUT_FRAMES {
+        app.update();
+        assert_eq!(state(&app), GameState::Buffer);
+    }
+    app.update();
+    assert_eq!(state(&app), GameState::Menu);
+    assert_eq!(menu_depth(&app), 1);
+}
+
+#[test]
+fn menu_without_next_menu_over_menus() {
+    let mut app = menu_app();
+    open_main_menu(&mut app);
+    click(&mut app, "menu.play");
+
+    app.world.insert_resource(BufferedState(GameState::Menu));
+    app.world
+        .resource_mut::<State<GameState>>()
+        .push(GameState::Buffer)
+        .unwrap();
+    run(&mut app, 4);
+    assert_eq!(state(&app), GameState::Menu);
+    assert_eq!(menu_depth(&app), 2);
+    assert_eq!(top_menu_title(&app), "menu.choose_world");
+
+    click(&mut app, "menu.back");
+    assert_eq!(menu_depth(&app), 1);
+}
+
+#[test]
+fn menu_without_next_menu_or_menus() {
+    let mut app = menu_app();
+    app.world
+        .resource_mut::<State<GameState>>()
+        .overwrite_set(GameState::Menu)
+        .unwrap();
+    run(&mut app, 3);
+    assert_eq!(state(&app), GameState::Menu);
+    assert_eq!(menu_depth(&app), 1);
+
+    click(&mut app, "menu.play");
+    assert_eq!(menu_depth(&app), 2);
+}
+
+// Menu exits left to `exit_menus`
+struct MenuExits(usize);
+
+// Leaves the menu state from inside the frame, like a Back press would
+fn exit_menus(mut exits: ResMut<MenuExits>, mut state: ResMut<State<GameState>>) {
+    if exits.0 > 0 && state.pop().is_ok() {
+        exits.0 -= 1;
+    }
+}
+
+// As if the menu state were left twice in quick succession, the first time before the menu being
+// left had spawned
+#[test]
+fn rapid_menu_exits() {
+    let mut app = menu_app();
+    app.insert_resource(MenuExits(0))
+        .add_system_set(SystemSet::on_update(GameState::Menu).with_system(exit_menus));
+    open_main_menu(&mut app);
+    click(&mut app, "menu.play");
+    assert_eq!(menu_depth(&app), 2);
+
+    // Stands in for a menu whose spawn commands haven't applied
+    let unspawned_e = app.world.spawn().id();
+    let top_e = app.world.resource::<MenuEs>().last().unwrap().entity;
+    app.world.despawn(top_e);
+    app.world
+        .resource_mut::<MenuEs>()
+        .last_mut()
+        .unwrap()
+        .entity = unspawned_e;
+
+    app.world.resource_mut::<MenuExits>().0 = 2;
+    run(&mut app, 3);
+    assert_eq!(app.world.resource::<MenuExits>().0, 0);
+    assert_eq!(state(&app), GameState::MainMenu);
+    assert!(app.world.get_resource::<MenuEs>().is_none());
+    assert!(app.world.get_entity(unspawned_e).is_none());
+}
+
+#[test]
+fn focus_announces_label() {
+    let mut app = menu_app();
+    open_main_menu(&mut app);
+    let mut announcements = app
+        .world
+        .resource::<Events<MenuAnnouncement>>()
+        .get_reader();
+    announcements
+        .iter(app.world.resource::<Events<MenuAnnouncement>>())
+        .for_each(drop);
+
+    let down = app.world.resource::<Settings>().key_bindings.down;
+    app.world.resource_mut::<Input<KeyCode>>().press(down);
+    run(&mut app, 1);
+    app.world.resource_mut::<Input<KeyCode>>().release(down);
+    app.world.resource_mut::<Input<KeyCode>>().clear();
+    // `Focused` is added with commands, so it's announced the frame after
+    run(&mut app, 1);
+
+    let focused = app
+        .world
+        .query_filtered::<&AccessibleLabel, With<Focused>>()
+        .iter(&app.world)
+        .next()
+        .unwrap()
+        .name
+        .clone();
+    let announced = announcements
+        .iter(app.world.resource::<Events<MenuAnnouncement>>())
+        .map(|announcement| (announcement.role, announcement.name.clone()))
+        .collect::<Vec<_>>();
+    assert!(
+        matches!(announced.as_slice(), [(AccessRole::Button, name)] if *name == focused),
+        "{:?}",
+        announced
+    );
+}
+
+// Only the menus follow `MenuScale`, and sizes that other systems set are left to them
+#[test]
+fn scale_menu_nodes() {
+    let mut app = menu_app();
+    open_main_menu(&mut app);
+    let host_e = app
+        .world
+        .spawn()
+        .insert_bundle(NodeBundle {
+            style: Style {
+                size: Size::new(Val::Px(100.), Val::Px(50.)),
+                ..default()
+            },
+            ..default()
+        })
+        .id();
+    run(&mut app, 2);
+
+    let button_e = find_button(&mut app, "menu.play");
+    let height = match app.world.get::<Style>(button_e).unwrap().size.height {
+        Val::Px(height) => height,
+        height => panic!("button height is {:?}", height),
+    };
+    app.world.get_mut::<Style>(button_e).unwrap().size.width = Val::Px(123.);
+    app.world.resource_mut::<MenuScale>().0 = 2.;
+    run(&mut app, 1);
+
+    let size = app.world.get::<Style>(button_e).unwrap().size;
+    assert_eq!(size.width, Val::Px(123.));
+    assert_eq!(size.height, Val::Px(height * 2.));
+    assert_eq!(
+        app.world.get::<Style>(host_e).unwrap().size,
+        Size::new(Val::Px(100.), Val::Px(50.))
+    );
+}
+
+// Once a long menu settles, idle frames leave its colors alone, so change detection stays quiet
+#[test]
+fn idle_menu_keeps_colors() {
+    let mut app = menu_app();
+    open_main_menu(&mut app);
+    let rows = (0..200)
+        .map(|index| format!("Static((buttons: [(text: \"{}\", action: Back)]))", index))
+        .collect::<Vec<_>>();
+    let menu: MenuBuilder = ron::from_str(&format!(
+        "(title: (text: \"buttons\", size: Heading), buttons: [{}])",
+        rows.join(", ")
+    ))
+    .unwrap();
+    app.world
+        .resource_mut::<Events<OpenMenuPath>>()
+        .send(OpenMenuPath(vec![menu]));
+    run(&mut app, 40);
+    assert_eq!(menu_depth(&app), 2);
+    let buttons = app
+        .world
+        .query_filtered::<(), With<Button>>()
+        .iter(&app.world)
+        .count();
+    assert!(buttons >= 200, "{}", buttons);
+
+    let mut changed_colors =
+        IntoSystem::into_system(|colors: Query<(), (Changed<UiColor>, With<Button>)>| {
+            colors.iter().count()
+        });
+    changed_colors.initialize(&mut app.world);
+    changed_colors.run((), &mut app.world);
+    run(&mut app, 1);
+    assert_eq!(changed_colors.run((), &mut app.world), 0);
+}
+
+const MAIN_MENU_TREE: &str = r#"menu "voxmod"
+  list Continue from worlds by LastPlayedDescending
+  "menu.play" -> Menu("menu.choose_world")
+    menu "menu.choose_world"
+      text input "menu.search"
+      list Play from worlds by ModifiedDescending in 2 columns
+      "menu.create_world" -> Menu("menu.choose_game")
+        menu "menu.choose_game"
+          list CreateWorld from games by NameAscending with categories
+          "menu.back" -> Back
+      "menu.manage_worlds" -> Menu("menu.manage_worlds")
+        menu "menu.manage_worlds"
+          text input "menu.search"
+          list Manage from worlds by ModifiedDescending
+          "menu.delete_selected" -> DeleteSelectedWorlds
+          "menu.back" -> CloseWorldManager
+      "menu.back" -> Back
+  "menu.edit" -> Menu("menu.choose_edit_world")
+    menu "menu.choose_edit_world"
+      text input "menu.search"
+      list Edit from worlds by ModifiedDescending
+      "menu.back" -> Back
+  "menu.settings" -> Menu("menu.settings")
+    menu "menu.settings"
+      slider "settings.master_volume" 0..1
+      slider "settings.music_volume" 0..1
+      toggle "settings.fullscreen" Fullscreen
+      toggle "settings.vsync" Vsync
+      toggle "settings.confirm_on_quit" ConfirmQuit
+      "" -> Setting(Language)
+      "" -> Setting(RenderDistance)
+      "" -> Setting(Theme)
+      "menu.controls" -> Menu("menu.controls")
+        menu "menu.controls"
+          "" -> Setting(KeyBinding(Up))
+          "" -> Setting(KeyBinding(Down))
+          "" -> Setting(KeyBinding(Left))
+          "" -> Setting(KeyBinding(Right))
+          "" -> Setting(KeyBinding(First))
+          "" -> Setting(KeyBinding(Last))
+          "" -> Setting(KeyBinding(PageUp))
+          "" -> Setting(KeyBinding(PageDown))
+          "" -> Setting(KeyBinding(Activate))
+          "" -> Setting(KeyBinding(Back))
+          "menu.back" -> Back
+      "menu.back" -> CloseSettings
+  "menu.about" -> Menu("menu.about")
+    menu "menu.about"
+      paragraph text/credits.txt
+      "menu.back" -> Back
+  "menu.quit" -> Quit
+"#;
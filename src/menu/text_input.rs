@@ -0,0 +1,250 @@
+use bevy::prelude::*;
+
+use crate::{locale::Locale, state::GameState};
+
+use super::{
+    focus::{Focusable, Focused},
+    in_menu, Fonts, MenuEs, MenuTheme,
+};
+
+pub struct TextInputPlugin;
+
+impl Plugin for TextInputPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_system_set(
+            SystemSet::on_update(GameState::Menu)
+                .with_system(focus_text_input)
+                .with_system(follow_focus.after(focus_text_input))
+                .with_system(type_text_input.after(follow_focus))
+                .with_system(update_text_input.after(type_text_input)),
+        );
+    }
+}
+
+#[derive(Clone, Copy, Eq, PartialEq)]
+pub enum TextInputField {
+    WorldName,
+    Seed,
+    Search,
+}
+
+pub type TextInputValidator = fn(&str, &Locale) -> Result<(), String>;
+
+#[derive(Clone, Component)]
+pub struct MenuTextInput {
+    pub field: TextInputField,
+    pub placeholder: String,
+    pub validate: Option<TextInputValidator>,
+    pub value: String,
+    pub error: Option<String>,
+    pub focused: bool,
+}
+
+const TEXT_INPUT_MAX_LEN: usize = 64;
+const TEXT_INPUT_CARET: char = '|';
+const TEXT_INPUT_PLACEHOLDER_COLOR: Color = Color::GRAY;
+const TEXT_INPUT_ERROR_SIZE: f32 = 30.;
+const TEXT_INPUT_ERROR_COLOR: Color = Color::rgb(1., 0.3, 0.3);
+
+impl MenuTextInput {
+    pub fn new(
+        field: TextInputField,
+        placeholder: impl Into<String>,
+        validate: Option<TextInputValidator>,
+    ) -> Self {
+        Self {
+            field,
+            placeholder: placeholder.into(),
+            validate,
+            value: String::new(),
+            error: None,
+            focused: false,
+        }
+    }
+
+    pub fn spawn(&self, parent: &mut ChildBuilder, fonts: &Fonts, theme: &MenuTheme) {
+        let mut text = None;
+        let mut error = None;
+
+        parent
+            .spawn_bundle(NodeBundle {
+                style: Style {
+                    flex_direction: FlexDirection::ColumnReverse,
+                    align_items: AlignItems::Center,
+                    align_self: AlignSelf::Center,
+                    flex_shrink: 0.,
+                    margin: theme.item_margin,
+                    size: Size::new(theme.button_size.width, Val::Auto),
+                    ..default()
+                },
+                color: Color::NONE.into(),
+                ..default()
+            })
+            .with_children(|parent| {
+                parent
+                    .spawn_bundle(NodeBundle {
+                        style: Style {
+                            align_items: AlignItems::Center,
+                            justify_content: JustifyContent::Center,
+                            size: Size::new(Val::Percent(100.), theme.button_size.height),
+                            ..default()
+                        },
+                        color: theme.button_color.into(),
+                        ..default()
+                    })
+                    .with_children(|parent| {
+                        text = Some(
+                            parent
+                                .spawn_bundle(TextBundle {
+                                    text: Text::with_section(
+                                        String::new(),
+                                        TextStyle {
+                                            font: fonts.font.clone(),
+                                            font_size: theme.button_text_size,
+                                            color: theme.button_text_color,
+                                        },
+                                        default(),
+                                    ),
+                                    ..default()
+                                })
+                                .id(),
+                        );
+                    });
+
+                error = Some(
+                    parent
+                        .spawn_bundle(TextBundle {
+                            text: Text::with_section(
+                                String::new(),
+                                TextStyle {
+                                    font: fonts.font.clone(),
+                                    font_size: TEXT_INPUT_ERROR_SIZE,
+                                    color: TEXT_INPUT_ERROR_COLOR,
+                                },
+                                default(),
+                            ),
+                            ..default()
+                        })
+                        .id(),
+                );
+            })
+            .insert_bundle((
+                self.clone(),
+                TextInputParts {
+                    text: text.unwrap(),
+                    error: error.unwrap(),
+                },
+                Interaction::default(),
+                Focusable,
+            ));
+    }
+
+    fn edit(&mut self, locale: &Locale, edit: impl FnOnce(&mut String)) {
+        edit(&mut self.value);
+        self.error = match self.validate {
+            Some(validate) if !self.value.is_empty() => validate(&self.value, locale).err(),
+            _ => None,
+        };
+    }
+}
+
+#[derive(Component)]
+struct TextInputParts {
+    text: Entity,
+    error: Entity,
+}
+
+type TextInputClicks<'w, 's, 'a> =
+    Query<'w, 's, (Entity, &'a Interaction), (Changed<Interaction>, With<MenuTextInput>)>;
+
+fn focus_text_input(clicks: TextInputClicks, mut inputs: Query<(Entity, &mut MenuTextInput)>) {
+    for (clicked_e, interaction) in clicks.iter() {
+        if let Interaction::Clicked = interaction {
+            for (input_e, mut input) in inputs.iter_mut() {
+                if input.focused != (input_e == clicked_e) {
+                    input.focused = input_e == clicked_e;
+                }
+            }
+        }
+    }
+}
+
+fn follow_focus(
+    focused: Query<Entity, (Added<Focused>, With<MenuTextInput>)>,
+    mut inputs: Query<(Entity, &mut MenuTextInput)>,
+) {
+    for focused_e in focused.iter() {
+        for (input_e, mut input) in inputs.iter_mut() {
+            if input.focused != (input_e == focused_e) {
+                input.focused = input_e == focused_e;
+            }
+        }
+    }
+}
+
+fn type_text_input(
+    mut chars: EventReader<ReceivedCharacter>,
+    keys: Res<Input<KeyCode>>,
+    menu_es: Option<Res<MenuEs>>,
+    parents: Query<&Parent>,
+    mut inputs: Query<(Entity, &mut MenuTextInput)>,
+    locale: Res<Locale>,
+) {
+    let chars = chars
+        .iter()
+        .map(|received| received.char)
+        .filter(|char| !char.is_control())
+        .collect::<String>();
+    let backspace = keys.just_pressed(KeyCode::Back);
+    if chars.is_empty() && !backspace {
+        return;
+    }
+
+    let menu_e = match menu_es.as_ref().and_then(|menu_es| menu_es.top()) {
+        Some(menu_e) => menu_e,
+        None => return,
+    };
+
+    for (input_e, mut input) in inputs.iter_mut() {
+        if !input.focused || !in_menu(input_e, menu_e, &parents) {
+            continue;
+        }
+
+        input.edit(&locale, |value| {
+            if backspace {
+                value.pop();
+            }
+
+            for char in chars.chars() {
+                if value.chars().count() < TEXT_INPUT_MAX_LEN {
+                    value.push(char);
+                }
+            }
+        });
+    }
+}
+
+fn update_text_input(
+    inputs: Query<(&MenuTextInput, &TextInputParts), Changed<MenuTextInput>>,
+    mut texts: Query<&mut Text>,
+    theme: Res<MenuTheme>,
+    locale: Res<Locale>,
+) {
+    for (input, parts) in inputs.iter() {
+        let mut text = texts.get_mut(parts.text).unwrap();
+        let section = &mut text.sections[0];
+        if input.value.is_empty() {
+            section.value = locale.get(&input.placeholder);
+            section.style.color = TEXT_INPUT_PLACEHOLDER_COLOR;
+        } else {
+            section.value = input.value.clone();
+            section.style.color = theme.button_text_color;
+        }
+        if input.focused {
+            section.value.push(TEXT_INPUT_CARET);
+        }
+
+        texts.get_mut(parts.error).unwrap().sections[0].value =
+            input.error.clone().unwrap_or_default();
+    }
+}
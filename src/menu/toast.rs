@@ -0,0 +1,197 @@
+use bevy::prelude::*;
+
+use crate::{locale::Locale, state::GameState};
+
+use super::{scale::MenuNode, Action, Fonts, MenuButton, MenuButtonRow, MenuTheme};
+
+pub struct ToastPlugin;
+
+impl Plugin for ToastPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_event::<MenuToast>()
+            .add_system_set(
+                SystemSet::on_update(GameState::Menu)
+                    .with_system(spawn_toasts)
+                    .with_system(fade_toasts),
+            )
+            .add_system_set(SystemSet::on_enter(GameState::LoadingWorld).with_system(clear_toasts));
+    }
+}
+
+#[derive(Clone, Copy)]
+pub enum ToastSeverity {
+    Info,
+    Warning,
+    Error,
+}
+
+impl ToastSeverity {
+    fn color(self) -> Color {
+        match self {
+            ToastSeverity::Info => Color::WHITE,
+            ToastSeverity::Warning => Color::rgb(1., 0.85, 0.3),
+            ToastSeverity::Error => Color::rgb(1., 0.3, 0.3),
+        }
+    }
+}
+
+pub struct MenuToast {
+    pub message: String,
+    pub severity: ToastSeverity,
+    // A button's label and what it does, like undoing what the toast reports
+    action: Option<(String, Action)>,
+}
+
+impl MenuToast {
+    pub fn new(severity: ToastSeverity, message: impl Into<String>) -> Self {
+        Self {
+            message: message.into(),
+            severity,
+            action: None,
+        }
+    }
+
+    pub(super) fn with_action(mut self, label: impl Into<String>, action: Action) -> Self {
+        self.action = Some((label.into(), action));
+        self
+    }
+}
+
+const TOAST_SECS: f32 = 4.;
+// Long enough to reach the button
+const TOAST_ACTION_SECS: f32 = 8.;
+const TOAST_FADE_SECS: f32 = 0.5;
+const TOAST_TEXT_SIZE: f32 = 30.;
+const TOAST_PADDING: f32 = 10.;
+const TOAST_BACKGROUND_COLOR: Color = Color::rgba(0., 0., 0., 0.75);
+
+#[derive(Component)]
+struct ToastContainer;
+
+#[derive(Component)]
+struct Toast {
+    timer: Timer,
+    text: Entity,
+}
+
+fn spawn_toasts(
+    mut commands: Commands,
+    mut toasts: EventReader<MenuToast>,
+    containers: Query<Entity, With<ToastContainer>>,
+    fonts: Res<Fonts>,
+    theme: Res<MenuTheme>,
+    locale: Res<Locale>,
+) {
+    let mut container_e = containers.get_single().ok();
+
+    for toast in toasts.iter() {
+        match toast.severity {
+            ToastSeverity::Info => info!("{}", toast.message),
+            ToastSeverity::Warning => warn!("{}", toast.message),
+            ToastSeverity::Error => error!("{}", toast.message),
+        }
+
+        let container_e = *container_e.get_or_insert_with(|| {
+            commands
+                .spawn_bundle(NodeBundle {
+                    style: Style {
+                        flex_direction: FlexDirection::Column,
+                        align_items: AlignItems::Center,
+                        position_type: PositionType::Absolute,
+                        position: Rect {
+                            bottom: Val::Px(TOAST_PADDING * 2.),
+                            ..default()
+                        },
+                        size: Size::new(Val::Percent(100.), Val::Auto),
+                        ..default()
+                    },
+                    color: Color::NONE.into(),
+                    ..default()
+                })
+                .insert_bundle((ToastContainer, MenuNode))
+                .id()
+        });
+
+        commands.entity(container_e).with_children(|parent| {
+            let mut toast_node = parent.spawn_bundle(NodeBundle {
+                style: Style {
+                    align_items: AlignItems::Center,
+                    margin: Rect::all(Val::Px(TOAST_PADDING / 2.)),
+                    padding: Rect::all(Val::Px(TOAST_PADDING)),
+                    ..default()
+                },
+                color: TOAST_BACKGROUND_COLOR.into(),
+                ..default()
+            });
+
+            let mut text = None;
+            toast_node.with_children(|parent| {
+                text = Some(
+                    parent
+                        .spawn_bundle(TextBundle {
+                            text: Text::with_section(
+                                toast.message.clone(),
+                                TextStyle {
+                                    font: fonts.font.clone(),
+                                    font_size: TOAST_TEXT_SIZE,
+                                    color: toast.severity.color(),
+                                },
+                                default(),
+                            ),
+                            ..default()
+                        })
+                        .id(),
+                );
+
+                if let Some((label, action)) = &toast.action {
+                    let mut row = MenuButtonRow::new(vec![MenuButton {
+                        small: true,
+                        ..MenuButton::new(label.clone(), action.clone())
+                    }]);
+                    row.layout.fit = true;
+                    row.spawn(parent, &fonts, &theme, &locale);
+                }
+            });
+            toast_node.insert(Toast {
+                timer: Timer::from_seconds(
+                    if toast.action.is_some() {
+                        TOAST_ACTION_SECS
+                    } else {
+                        TOAST_SECS
+                    },
+                    false,
+                ),
+                text: text.unwrap(),
+            });
+        });
+    }
+}
+
+fn fade_toasts(
+    mut commands: Commands,
+    time: Res<Time>,
+    mut toasts: Query<(Entity, &mut Toast, &mut UiColor)>,
+    mut texts: Query<&mut Text>,
+) {
+    for (toast_e, mut toast, mut color) in toasts.iter_mut() {
+        toast.timer.tick(time.delta());
+        if toast.timer.finished() {
+            commands.entity(toast_e).despawn_recursive();
+            continue;
+        }
+
+        let remaining = toast.timer.duration().as_secs_f32() - toast.timer.elapsed_secs();
+        let alpha = (remaining / TOAST_FADE_SECS).min(1.);
+        color.0.set_a(TOAST_BACKGROUND_COLOR.a() * alpha);
+        texts.get_mut(toast.text).unwrap().sections[0]
+            .style
+            .color
+            .set_a(alpha);
+    }
+}
+
+fn clear_toasts(mut commands: Commands, containers: Query<Entity, With<ToastContainer>>) {
+    for container_e in containers.iter() {
+        commands.entity(container_e).despawn_recursive();
+    }
+}
@@ -0,0 +1,237 @@
+use bevy::prelude::*;
+use serde::Deserialize;
+
+use crate::{locale::Locale, settings::Settings, state::GameState};
+
+use super::{
+    focus::{self, Focusable, Focused, NavInput},
+    Fonts, MenuAction, MenuSounds, MenuTheme,
+};
+
+pub struct TogglePlugin;
+
+impl Plugin for TogglePlugin {
+    fn build(&self, app: &mut App) {
+        app.add_system_set(
+            SystemSet::on_update(GameState::Menu)
+                .with_system(click_toggle)
+                .with_system(activate_toggle.after(focus::read_nav_input))
+                .with_system(update_toggle.after(click_toggle).after(activate_toggle))
+                .with_system(color_toggle),
+        );
+    }
+}
+
+#[derive(Clone, Copy, Debug, Deserialize)]
+pub enum ToggleAction {
+    Fullscreen,
+    Vsync,
+    ConfirmQuit,
+}
+
+impl ToggleAction {
+    fn get(self, settings: &Settings) -> bool {
+        match self {
+            ToggleAction::Fullscreen => settings.fullscreen,
+            ToggleAction::Vsync => settings.vsync,
+            ToggleAction::ConfirmQuit => settings.confirm_on_quit,
+        }
+    }
+
+    fn set(self, settings: &mut Settings, on: bool) {
+        match self {
+            ToggleAction::Fullscreen => settings.fullscreen = on,
+            ToggleAction::Vsync => settings.vsync = on,
+            ToggleAction::ConfirmQuit => settings.confirm_on_quit = on,
+        }
+    }
+}
+
+#[derive(Clone, Component)]
+pub struct MenuToggle {
+    pub label: String,
+    pub action: ToggleAction,
+}
+
+const TOGGLE_BOX_SIZE: f32 = 30.;
+const TOGGLE_MARK_SIZE: f32 = 18.;
+
+impl MenuToggle {
+    pub fn spawn(&self, parent: &mut ChildBuilder, fonts: &Fonts, theme: &MenuTheme) {
+        let mut label = None;
+        let mut mark = None;
+
+        parent
+            .spawn_bundle(NodeBundle {
+                style: Style {
+                    align_items: AlignItems::Center,
+                    justify_content: JustifyContent::Center,
+                    align_self: AlignSelf::Center,
+                    flex_shrink: 0.,
+                    margin: theme.item_margin,
+                    size: Size::new(theme.button_size.width, theme.button_size.height),
+                    ..default()
+                },
+                color: theme.button_color.into(),
+                ..default()
+            })
+            .with_children(|parent| {
+                label = Some(
+                    parent
+                        .spawn_bundle(TextBundle {
+                            style: Style {
+                                margin: Rect {
+                                    right: Val::Px(theme.button_icon_margin),
+                                    ..default()
+                                },
+                                ..default()
+                            },
+                            text: Text::with_section(
+                                String::new(),
+                                TextStyle {
+                                    font: fonts.font.clone(),
+                                    font_size: theme.button_text_size,
+                                    color: theme.button_text_color,
+                                },
+                                default(),
+                            ),
+                            ..default()
+                        })
+                        .id(),
+                );
+
+                parent
+                    .spawn_bundle(NodeBundle {
+                        style: Style {
+                            align_items: AlignItems::Center,
+                            justify_content: JustifyContent::Center,
+                            size: Size::new(Val::Px(TOGGLE_BOX_SIZE), Val::Px(TOGGLE_BOX_SIZE)),
+                            ..default()
+                        },
+                        color: theme.button_press_color.into(),
+                        ..default()
+                    })
+                    .with_children(|parent| {
+                        mark = Some(
+                            parent
+                                .spawn_bundle(NodeBundle {
+                                    style: Style {
+                                        size: Size::new(
+                                            Val::Px(TOGGLE_MARK_SIZE),
+                                            Val::Px(TOGGLE_MARK_SIZE),
+                                        ),
+                                        ..default()
+                                    },
+                                    color: theme.button_text_color.into(),
+                                    ..default()
+                                })
+                                .id(),
+                        );
+                    });
+            })
+            .insert_bundle((
+                self.clone(),
+                Interaction::default(),
+                Focusable,
+                ToggleParts {
+                    label: label.unwrap(),
+                    mark: mark.unwrap(),
+                },
+            ));
+    }
+
+    fn flip(
+        &self,
+        settings: &mut Settings,
+        menu_actions: &mut EventWriter<MenuAction>,
+        audio: &Audio,
+        sounds: &MenuSounds,
+    ) {
+        let on = !self.action.get(settings);
+        self.action.set(settings, on);
+        menu_actions.send(MenuAction::Toggle {
+            toggle: self.action,
+            on,
+        });
+        audio.play_with_settings(
+            sounds.click.clone(),
+            PlaybackSettings::ONCE.with_volume(settings.master_volume),
+        );
+    }
+}
+
+#[derive(Component)]
+struct ToggleParts {
+    label: Entity,
+    mark: Entity,
+}
+
+type ToggleClicks<'w, 's, 'a> =
+    Query<'w, 's, (&'a MenuToggle, &'a Interaction), Changed<Interaction>>;
+
+fn click_toggle(
+    toggles: ToggleClicks,
+    mut settings: ResMut<Settings>,
+    mut menu_actions: EventWriter<MenuAction>,
+    audio: Res<Audio>,
+    sounds: Res<MenuSounds>,
+) {
+    for (toggle, interaction) in toggles.iter() {
+        if let Interaction::Clicked = interaction {
+            toggle.flip(&mut settings, &mut menu_actions, &audio, &sounds);
+        }
+    }
+}
+
+fn activate_toggle(
+    nav: Res<NavInput>,
+    toggles: Query<&MenuToggle, With<Focused>>,
+    mut settings: ResMut<Settings>,
+    mut menu_actions: EventWriter<MenuAction>,
+    audio: Res<Audio>,
+    sounds: Res<MenuSounds>,
+) {
+    if !nav.activate {
+        return;
+    }
+
+    for toggle in toggles.iter() {
+        toggle.flip(&mut settings, &mut menu_actions, &audio, &sounds);
+    }
+}
+
+fn update_toggle(
+    settings: Res<Settings>,
+    locale: Res<Locale>,
+    toggles: Query<(&MenuToggle, &ToggleParts, ChangeTrackers<MenuToggle>)>,
+    mut texts: Query<&mut Text>,
+    mut styles: Query<&mut Style>,
+) {
+    for (toggle, parts, toggle_tracker) in toggles.iter() {
+        if !settings.is_changed() && !locale.is_changed() && !toggle_tracker.is_added() {
+            continue;
+        }
+
+        texts.get_mut(parts.label).unwrap().sections[0].value = locale.get(&toggle.label);
+        styles.get_mut(parts.mark).unwrap().display = if toggle.action.get(&settings) {
+            Display::Flex
+        } else {
+            Display::None
+        };
+    }
+}
+
+fn color_toggle(
+    mut toggles: Query<(&Interaction, Option<&Focused>, &mut UiColor), With<MenuToggle>>,
+    theme: Res<MenuTheme>,
+) {
+    for (interaction, focused, mut color) in toggles.iter_mut() {
+        let new_color = match (interaction, focused) {
+            (Interaction::None, None) => theme.button_color,
+            _ => theme.button_hover_color,
+        };
+        if color.0 != new_color {
+            color.0 = new_color;
+        }
+    }
+}
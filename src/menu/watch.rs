@@ -0,0 +1,136 @@
+use std::{
+    path::PathBuf,
+    sync::{Arc, Mutex},
+    time::{Duration, Instant},
+};
+
+use bevy::prelude::*;
+use notify::{event::ModifyKind, Event, EventKind, RecommendedWatcher, RecursiveMode, Watcher};
+
+use super::{asset_fs_path, in_menu, AssetListSource, MenuEs, Rebuild};
+
+// Rebuilds the asset lists in the top menu when their directories change outside the app
+pub struct WatchPlugin;
+
+impl Plugin for WatchPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_system(watch_asset_dirs)
+            .add_system(rebuild_watched_lists.after(watch_asset_dirs));
+    }
+}
+
+// A burst of changes, like a world being copied in, waits to settle before rebuilding
+const WATCH_DEBOUNCE: Duration = Duration::from_millis(500);
+
+struct AssetDirWatcher {
+    // Stops watching when dropped
+    _watcher: RecommendedWatcher,
+    dirs: Vec<PathBuf>,
+    // Pushed back by each change in a burst
+    changed: Arc<Mutex<Option<Instant>>>,
+}
+
+impl AssetDirWatcher {
+    fn new(dirs: Vec<PathBuf>) -> notify::Result<Self> {
+        let changed = Arc::new(Mutex::new(None));
+        let event_changed = changed.clone();
+        let mut watcher = notify::recommended_watcher(move |event| match event {
+            Ok(event) if changes_listing(&event) => {
+                *event_changed.lock().unwrap() = Some(Instant::now());
+            }
+            Ok(_) => (),
+            Err(err) => warn!("Couldn't watch the asset directories: {}", err),
+        })?;
+        for dir in &dirs {
+            watcher.watch(dir, RecursiveMode::NonRecursive)?;
+        }
+
+        Ok(Self {
+            _watcher: watcher,
+            dirs,
+            changed,
+        })
+    }
+}
+
+// Hidden entries, like a world being duplicated, aren't listed
+fn changes_listing(event: &Event) -> bool {
+    matches!(
+        event.kind,
+        EventKind::Create(_) | EventKind::Remove(_) | EventKind::Modify(ModifyKind::Name(_))
+    ) && event.paths.iter().any(|path| {
+        path.file_name()
+            .is_some_and(|name| !name.to_string_lossy().starts_with('.'))
+    })
+}
+
+fn watch_asset_dirs(
+    mut commands: Commands,
+    menu_es: Option<Res<MenuEs>>,
+    sources: Query<(Entity, &AssetListSource)>,
+    added: Query<(), Added<AssetListSource>>,
+    parents: Query<&Parent>,
+    asset_server: Res<AssetServer>,
+    watcher: Option<Res<AssetDirWatcher>>,
+) {
+    let (menu_es, menu_e) = match menu_es
+        .as_ref()
+        .and_then(|menu_es| Some((menu_es, menu_es.top()?)))
+    {
+        Some(menu) => menu,
+        None => {
+            if watcher.is_some() {
+                commands.remove_resource::<AssetDirWatcher>();
+            }
+            return;
+        }
+    };
+
+    if !menu_es.is_changed() && added.is_empty() {
+        return;
+    }
+
+    let mut dirs = sources
+        .iter()
+        .filter(|(list_e, _)| in_menu(*list_e, menu_e, &parents))
+        .flat_map(|(_, source)| source.dirs.iter())
+        .filter_map(|dir| asset_fs_path(&asset_server, dir))
+        .filter(|dir| dir.is_dir())
+        .collect::<Vec<_>>();
+    dirs.sort();
+    dirs.dedup();
+
+    if watcher.as_ref().map(|watcher| &watcher.dirs) == Some(&dirs) {
+        return;
+    }
+
+    if dirs.is_empty() {
+        commands.remove_resource::<AssetDirWatcher>();
+        return;
+    }
+
+    match AssetDirWatcher::new(dirs) {
+        Ok(watcher) => commands.insert_resource(watcher),
+        Err(err) => {
+            warn!("Couldn't watch the asset directories: {}", err);
+            commands.remove_resource::<AssetDirWatcher>();
+        }
+    }
+}
+
+fn rebuild_watched_lists(
+    watcher: Option<Res<AssetDirWatcher>>,
+    mut rebuilds: EventWriter<Rebuild>,
+) {
+    let watcher = match watcher {
+        Some(watcher) => watcher,
+        None => return,
+    };
+
+    let mut changed = watcher.changed.lock().unwrap();
+    if changed.is_some_and(|changed| changed.elapsed() >= WATCH_DEBOUNCE) {
+        *changed = None;
+        debug!("Asset directories changed, rebuilding");
+        rebuilds.send(Rebuild::Dynamic);
+    }
+}
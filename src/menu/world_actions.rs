@@ -0,0 +1,778 @@
+use std::{
+    collections::HashSet,
+    fs,
+    io::{self, ErrorKind},
+    path::{Path, PathBuf},
+    sync::{atomic::Ordering, Arc},
+    time::{Duration, SystemTime},
+    vec,
+};
+
+use bevy::{
+    prelude::*,
+    tasks::{AsyncComputeTaskPool, Task},
+};
+use futures_lite::future::{block_on, poll_once};
+
+use crate::{
+    archive::{self, archive_stem},
+    locale::Locale,
+    settings::Settings,
+    state::GameState,
+    world::{copy_dir, CopyProgress, WorldMeta},
+};
+
+use super::{
+    actions::button_action,
+    asset_fs_path, asset_name,
+    asset_rows::{AssetButtonAction, AssetSort},
+    text_input::{MenuTextInput, TextInputField},
+    toast::{MenuToast, ToastSeverity},
+    Action, MenuAction, MenuBuilder, MenuButton, MenuButtonRow, MenuButtonsBuilder, MenuPaths,
+    MenuTitle, MenuTitleSize, Rebuild,
+};
+
+// Creates, renames, copies and trashes worlds on disk
+pub struct WorldActionsPlugin;
+
+impl Plugin for WorldActionsPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<WorldSelection>()
+            .add_system(purge_trash)
+            .add_system_set(
+                SystemSet::on_update(GameState::Menu)
+                    .with_system(poll_world_copy.after(button_action))
+                    .with_system(update_copy_bar)
+                    .with_system(show_world_selection),
+            );
+    }
+}
+
+pub fn new_world_menu(game: PathBuf) -> MenuBuilder {
+    MenuBuilder {
+        title: MenuTitle {
+            text: "menu.new_world".to_string(),
+            size: MenuTitleSize::Heading,
+            font: None,
+        },
+        buttons: vec![
+            MenuButtonsBuilder::GameDetails { game: game.clone() },
+            MenuButtonsBuilder::TextInput(MenuTextInput::new(
+                TextInputField::WorldName,
+                "menu.world_name",
+                Some(validate_world_name),
+            )),
+            MenuButtonsBuilder::TextInput(MenuTextInput::new(
+                TextInputField::Seed,
+                "menu.seed",
+                None,
+            )),
+            MenuButtonsBuilder::Static(MenuButtonRow::new(vec![MenuButton::new(
+                "menu.create",
+                Action::CreateWorld(game),
+            )])),
+            MenuButtonsBuilder::Static(MenuButtonRow::new(vec![MenuButton::new(
+                "menu.back",
+                Action::Back,
+            )])),
+        ],
+        default_focus: None,
+        layout: default(),
+        cache: default(),
+    }
+}
+
+pub fn manage_worlds_menu() -> MenuBuilder {
+    MenuBuilder {
+        title: MenuTitle {
+            text: "menu.manage_worlds".to_string(),
+            size: MenuTitleSize::Heading,
+            font: None,
+        },
+        buttons: vec![
+            MenuButtonsBuilder::Search {
+                placeholder: "menu.search".to_string(),
+            },
+            MenuButtonsBuilder::PerAsset {
+                action: AssetButtonAction::Manage,
+                sort: AssetSort::ModifiedDescending,
+                page_size: None,
+                categories: false,
+                category: None,
+            },
+            MenuButtonsBuilder::Static(MenuButtonRow::new(vec![MenuButton::new(
+                "menu.delete_selected",
+                Action::DeleteSelectedWorlds,
+            )])),
+            MenuButtonsBuilder::Static(MenuButtonRow::new(vec![MenuButton::new(
+                "menu.back",
+                Action::CloseWorldManager,
+            )])),
+        ],
+        default_focus: None,
+        layout: default(),
+        cache: default(),
+    }
+}
+
+pub fn delete_worlds_menu(worlds: Vec<PathBuf>) -> MenuBuilder {
+    MenuBuilder {
+        title: MenuTitle {
+            text: "menu.delete_worlds".to_string(),
+            size: MenuTitleSize::Heading,
+            font: None,
+        },
+        buttons: vec![
+            MenuButtonsBuilder::Static(MenuButtonRow::new(vec![MenuButton::new(
+                "menu.delete",
+                Action::DeleteWorlds(worlds),
+            )])),
+            MenuButtonsBuilder::Static(MenuButtonRow::new(vec![MenuButton::new(
+                "menu.cancel",
+                Action::Back,
+            )])),
+        ],
+        // Cancel, so a stray confirm doesn't delete anything
+        default_focus: Some(1),
+        layout: default(),
+        cache: default(),
+    }
+}
+
+pub fn rename_world_menu(world: PathBuf) -> MenuBuilder {
+    MenuBuilder {
+        title: MenuTitle {
+            text: "menu.rename_world".to_string(),
+            size: MenuTitleSize::Heading,
+            font: None,
+        },
+        buttons: vec![
+            MenuButtonsBuilder::TextInput(MenuTextInput {
+                value: asset_name(&world),
+                ..MenuTextInput::new(
+                    TextInputField::WorldName,
+                    "menu.world_name",
+                    Some(validate_world_name),
+                )
+            }),
+            MenuButtonsBuilder::Static(MenuButtonRow::new(vec![MenuButton::new(
+                "menu.rename",
+                Action::RenameWorld(world),
+            )])),
+            MenuButtonsBuilder::Static(MenuButtonRow::new(vec![MenuButton::new(
+                "menu.back",
+                Action::Back,
+            )])),
+        ],
+        default_focus: None,
+        layout: default(),
+        cache: default(),
+    }
+}
+
+const ILLEGAL_WORLD_NAME_CHARS: &[char] = &['/', '\\', ':', '*', '?', '"', '<', '>', '|'];
+
+// Windows won't create files with these names, even with an extension
+const RESERVED_WORLD_NAMES: &[&str] = &[
+    "CON", "PRN", "AUX", "NUL", "COM1", "COM2", "COM3", "COM4", "COM5", "COM6", "COM7", "COM8",
+    "COM9", "LPT1", "LPT2", "LPT3", "LPT4", "LPT5", "LPT6", "LPT7", "LPT8", "LPT9",
+];
+pub const CASE_INSENSITIVE_FS: bool = cfg!(any(windows, target_os = "macos"));
+
+pub enum WorldNameError {
+    Empty,
+    IllegalChar(char),
+    Reserved(String),
+    // Windows drops trailing dots and spaces, so the folder wouldn't match the name
+    BadEnd,
+    // Hidden folders aren't listed
+    Hidden,
+    Taken,
+}
+
+impl WorldNameError {
+    fn message(&self, locale: &Locale) -> String {
+        match self {
+            WorldNameError::Empty => locale.get("menu.world_name.empty"),
+            WorldNameError::IllegalChar(char) => locale
+                .get("menu.world_name.illegal_char")
+                .replace("{char}", &char.to_string()),
+            WorldNameError::Reserved(name) => locale
+                .get("menu.world_name.reserved")
+                .replace("{name}", name),
+            WorldNameError::BadEnd => locale.get("menu.world_name.bad_end"),
+            WorldNameError::Hidden => locale.get("menu.world_name.hidden"),
+            WorldNameError::Taken => locale.get("menu.world_name.taken"),
+        }
+    }
+}
+
+// Collisions are only checked given the worlds folder, skipping the world being renamed
+pub fn sanitize_world_name(
+    name: &str,
+    worlds_path: Option<&Path>,
+    current: Option<&str>,
+) -> Result<(), WorldNameError> {
+    if name.trim().is_empty() {
+        return Err(WorldNameError::Empty);
+    }
+
+    if let Some(char) = name
+        .chars()
+        .find(|char| ILLEGAL_WORLD_NAME_CHARS.contains(char) || char.is_control())
+    {
+        return Err(WorldNameError::IllegalChar(char));
+    }
+
+    let stem = name.split('.').next().unwrap_or_default().trim_end();
+    if let Some(reserved) = RESERVED_WORLD_NAMES
+        .iter()
+        .find(|reserved| reserved.eq_ignore_ascii_case(stem))
+    {
+        return Err(WorldNameError::Reserved(reserved.to_string()));
+    }
+
+    if name.ends_with(['.', ' ']) {
+        return Err(WorldNameError::BadEnd);
+    }
+
+    if name.starts_with('.') {
+        return Err(WorldNameError::Hidden);
+    }
+
+    let same = |other: &str| match CASE_INSENSITIVE_FS {
+        true => other.to_lowercase() == name.to_lowercase(),
+        false => other == name,
+    };
+    let taken = worlds_path
+        .and_then(|worlds_path| fs::read_dir(worlds_path).ok())
+        .into_iter()
+        .flatten()
+        .filter_map(|entry| Some(entry.ok()?.file_name().to_string_lossy().into_owned()))
+        .any(|other| Some(other.as_str()) != current && same(&other));
+    if taken {
+        return Err(WorldNameError::Taken);
+    }
+
+    Ok(())
+}
+
+fn validate_world_name(name: &str, locale: &Locale) -> Result<(), String> {
+    sanitize_world_name(name, None, None).map_err(|err| err.message(locale))
+}
+
+pub fn create_world(
+    asset_server: &AssetServer,
+    paths: &MenuPaths,
+    game: &Path,
+    name: &str,
+    seed: u64,
+    locale: &Locale,
+) -> Result<PathBuf, String> {
+    validate_world_name(name, locale)?;
+
+    let world = paths
+        .writable(AssetButtonAction::Play.assets_path(paths))
+        .join(name);
+    let world_path = asset_fs_path(asset_server, &world)
+        .ok_or_else(|| "Worlds can only be created on the filesystem".to_string())?;
+    if let Some(worlds_path) = world_path.parent() {
+        sanitize_world_name(name, Some(worlds_path), None).map_err(|err| err.message(locale))?;
+        fs::create_dir_all(worlds_path).map_err(|err| format!("Couldn't create world: {}", err))?;
+    }
+    fs::create_dir(&world_path).map_err(|err| match err.kind() {
+        ErrorKind::AlreadyExists => WorldNameError::Taken.message(locale),
+        _ => format!("Couldn't create world: {}", err),
+    })?;
+
+    let meta = WorldMeta {
+        game: game.to_owned(),
+        seed,
+        ..default()
+    };
+    if let Err(err) = meta.save(&world_path) {
+        if let Err(err) = fs::remove_dir_all(&world_path) {
+            error!("Couldn't clean up world {}: {}", world_path.display(), err);
+        }
+        return Err(err);
+    }
+
+    info!(
+        "Created world {} from {} with seed {}",
+        world.display(),
+        game.display(),
+        seed
+    );
+    Ok(world)
+}
+
+pub fn rename_world(
+    asset_server: &AssetServer,
+    world: &Path,
+    name: &str,
+    locale: &Locale,
+) -> Result<PathBuf, String> {
+    validate_world_name(name, locale)?;
+
+    let renamed = world.with_file_name(name);
+    if renamed == world {
+        return Ok(renamed);
+    }
+
+    let world_path = asset_fs_path(asset_server, world)
+        .ok_or_else(|| "Worlds can only be renamed on the filesystem".to_string())?;
+    let renamed_path = world_path.with_file_name(name);
+    sanitize_world_name(name, world_path.parent(), Some(&asset_name(world)))
+        .map_err(|err| err.message(locale))?;
+    fs::rename(&world_path, &renamed_path)
+        .map_err(|err| format!("Couldn't rename world: {}", err))?;
+
+    info!("Renamed world {} to {}", world.display(), renamed.display());
+    Ok(renamed)
+}
+
+fn unix_secs(time: SystemTime) -> u64 {
+    time.duration_since(SystemTime::UNIX_EPOCH)
+        .map_or(0, |since| since.as_secs())
+}
+
+// Moves the world into the trash, under a name starting with when it was deleted, so
+// `purge_trash` knows when it expires. Returns where it went.
+pub fn trash_world(
+    asset_server: &AssetServer,
+    paths: &MenuPaths,
+    world: &Path,
+) -> Result<PathBuf, String> {
+    let world_path = asset_fs_path(asset_server, world)
+        .ok_or_else(|| "Worlds can only be deleted on the filesystem".to_string())?;
+    let err = |err| format!("Couldn't delete world {}: {}", asset_name(world), err);
+    // A trash beside the world, so it's on the same drive and moving it there is a rename
+    let trash_path = world_path
+        .parent()
+        .unwrap_or(Path::new(""))
+        .join(&paths.trash);
+    fs::create_dir_all(&trash_path).map_err(err)?;
+
+    let stamp = unix_secs(SystemTime::now());
+    let trashed_path = (1..)
+        .map(|n| match n {
+            1 => format!("{}-{}", stamp, asset_name(world)),
+            _ => format!("{}-{} {}", stamp, asset_name(world), n),
+        })
+        .map(|trashed_name| trash_path.join(trashed_name))
+        .find(|trashed_path| !trashed_path.exists())
+        .unwrap();
+    fs::rename(&world_path, &trashed_path).map_err(err)?;
+
+    info!(
+        "Moved world {} to {}",
+        world.display(),
+        trashed_path.display()
+    );
+    Ok(trashed_path)
+}
+
+pub fn restore_world(
+    asset_server: &AssetServer,
+    world: &Path,
+    trashed: &Path,
+) -> Result<(), String> {
+    let world_path = asset_fs_path(asset_server, world)
+        .ok_or_else(|| "Worlds can only be restored on the filesystem".to_string())?;
+    if world_path.exists() {
+        return Err(format!(
+            "Couldn't restore world {}: a world with that name exists",
+            asset_name(world)
+        ));
+    }
+    fs::rename(trashed, &world_path)
+        .map_err(|err| format!("Couldn't restore world {}: {}", asset_name(world), err))?;
+
+    info!("Restored world {}", world.display());
+    Ok(())
+}
+
+const TRASH_PURGE_INTERVAL: Duration = Duration::from_secs(60 * 60);
+
+// Purges the trash once at startup and then every `TRASH_PURGE_INTERVAL`
+fn purge_trash(
+    mut timer: Local<Option<Timer>>,
+    time: Res<Time>,
+    asset_server: Res<AssetServer>,
+    paths: Res<MenuPaths>,
+    settings: Res<Settings>,
+    thread_pool: Res<AsyncComputeTaskPool>,
+) {
+    match &mut *timer {
+        Some(timer) => {
+            if !timer.tick(time.delta()).just_finished() {
+                return;
+            }
+        }
+        None => *timer = Some(Timer::new(TRASH_PURGE_INTERVAL, true)),
+    }
+
+    if settings.trash_retention_days == 0 {
+        return;
+    }
+
+    let mut trash_paths = Vec::new();
+    for dir in paths.overlay(&paths.worlds) {
+        if let Some(dir_path) = asset_fs_path(&asset_server, &dir) {
+            let trash_path = dir_path.join(&paths.trash);
+            if !trash_paths.contains(&trash_path) {
+                trash_paths.push(trash_path);
+            }
+        }
+    }
+    let retention_days = settings.trash_retention_days;
+
+    thread_pool
+        .spawn(async move {
+            purge_expired(
+                &trash_paths,
+                retention_days,
+                unix_secs(SystemTime::now()),
+            )
+        })
+        .detach();
+}
+
+// Deletes worlds that were put in these trashes more than `retention_days` before `now`. A
+// retention of 0 never purges.
+pub fn purge_expired(trash_paths: &[PathBuf], retention_days: u32, now: u64) {
+    if retention_days == 0 {
+        return;
+    }
+    let expiry = u64::from(retention_days) * 24 * 60 * 60;
+
+    for trash_path in trash_paths {
+        let entries = match fs::read_dir(trash_path) {
+            Ok(entries) => entries,
+            Err(_) => continue,
+        };
+
+        for entry in entries.filter_map(Result::ok) {
+            let name = entry.file_name();
+            let stamp = match name
+                .to_str()
+                .and_then(|name| name.split_once('-'))
+                .and_then(|(stamp, _)| stamp.parse::<u64>().ok())
+            {
+                Some(stamp) => stamp,
+                // Not put there by `trash_world`
+                None => continue,
+            };
+            if now.saturating_sub(stamp) < expiry {
+                continue;
+            }
+
+            match fs::remove_dir_all(entry.path()) {
+                Ok(()) => info!("Purged world {}", entry.path().display()),
+                Err(err) => warn!("Couldn't purge world {}: {}", entry.path().display(), err),
+            }
+        }
+    }
+}
+
+#[derive(Clone, Copy)]
+enum WorldCopyKind {
+    Duplicate,
+    Extract,
+}
+
+// A world duplication or extraction running in the background. The copy goes under a hidden name
+// until it's done, so world lists never show it half-copied. `world` is the archive when
+// extracting.
+pub struct WorldCopy {
+    world: PathBuf,
+    copy: PathBuf,
+    kind: WorldCopyKind,
+    pub progress: Arc<CopyProgress>,
+    task: Task<io::Result<()>>,
+    result: Option<io::Result<()>>,
+}
+
+pub fn duplicate_world(
+    asset_server: &AssetServer,
+    paths: &MenuPaths,
+    thread_pool: &AsyncComputeTaskPool,
+    world: &Path,
+) -> Result<WorldCopy, String> {
+    let world_path = asset_fs_path(asset_server, world)
+        .ok_or_else(|| "Worlds can only be duplicated on the filesystem".to_string())?;
+    // Copies of bundled worlds go to the writable location
+    let copies = paths.writable(AssetButtonAction::Play.assets_path(paths));
+    let copies_path = asset_fs_path(asset_server, &copies)
+        .ok_or_else(|| "Worlds can only be duplicated on the filesystem".to_string())?;
+    fs::create_dir_all(&copies_path).map_err(|err| format!("Couldn't duplicate world: {}", err))?;
+
+    let name = asset_name(world);
+    let (copy, copy_path) = (1..)
+        .map(|n| match n {
+            1 => format!("{} copy", name),
+            _ => format!("{} copy {}", name, n),
+        })
+        .map(|copy_name| (copies.join(&copy_name), copies_path.join(&copy_name)))
+        .find(|(_, copy_path)| !copy_path.exists())
+        .unwrap();
+
+    let partial_path = copies_path.join(format!(".{}", asset_name(&copy)));
+
+    let progress = Arc::new(CopyProgress::default());
+    let task_progress = progress.clone();
+    let task = thread_pool.spawn(async move {
+        duplicate_into(&world_path, &partial_path, &copy_path, &task_progress)
+    });
+
+    Ok(WorldCopy {
+        world: world.to_path_buf(),
+        copy,
+        kind: WorldCopyKind::Duplicate,
+        progress,
+        task,
+        result: None,
+    })
+}
+
+pub fn extract_world(
+    asset_server: &AssetServer,
+    paths: &MenuPaths,
+    thread_pool: &AsyncComputeTaskPool,
+    archive: &Path,
+) -> Result<WorldCopy, String> {
+    let archive_path = asset_fs_path(asset_server, archive)
+        .ok_or_else(|| "Worlds can only be extracted on the filesystem".to_string())?;
+    let worlds = paths.writable(AssetButtonAction::Play.assets_path(paths));
+    let worlds_path = asset_fs_path(asset_server, &worlds)
+        .ok_or_else(|| "Worlds can only be extracted on the filesystem".to_string())?;
+    fs::create_dir_all(&worlds_path).map_err(|err| format!("Couldn't extract world: {}", err))?;
+
+    let name = archive_stem(archive);
+    let (world, world_path) = (1..)
+        .map(|n| match n {
+            1 => name.clone(),
+            _ => format!("{} {}", name, n),
+        })
+        .map(|world_name| (worlds.join(&world_name), worlds_path.join(&world_name)))
+        .find(|(_, world_path)| !world_path.exists())
+        .unwrap();
+
+    let partial_path = worlds_path.join(format!(".{}", asset_name(&world)));
+
+    let progress = Arc::new(CopyProgress::default());
+    let task_progress = progress.clone();
+    let task = thread_pool.spawn(async move {
+        extract_into(&archive_path, &partial_path, &world_path, &task_progress)
+    });
+
+    Ok(WorldCopy {
+        world: archive.to_path_buf(),
+        copy: world,
+        kind: WorldCopyKind::Extract,
+        progress,
+        task,
+        result: None,
+    })
+}
+
+// What's left of a copy that failed or was cancelled is removed, so it isn't mistaken for a world
+fn clean_up_partial(partial_path: &Path, result: io::Result<()>) -> io::Result<()> {
+    if result.is_err() && partial_path.exists() {
+        if let Err(err) = fs::remove_dir_all(partial_path) {
+            error!(
+                "Couldn't clean up world {}: {}",
+                partial_path.display(),
+                err
+            );
+        }
+    }
+    result
+}
+
+pub fn duplicate_into(
+    world_path: &Path,
+    partial_path: &Path,
+    copy_path: &Path,
+    progress: &CopyProgress,
+) -> io::Result<()> {
+    clean_up_partial(
+        partial_path,
+        copy_dir(world_path, partial_path, progress)
+            .and_then(|()| fs::rename(partial_path, copy_path)),
+    )
+}
+
+pub fn extract_into(
+    archive_path: &Path,
+    partial_path: &Path,
+    world_path: &Path,
+    progress: &CopyProgress,
+) -> io::Result<()> {
+    clean_up_partial(
+        partial_path,
+        archive::extract_world(archive_path, partial_path, world_path, progress),
+    )
+}
+
+pub fn copying_world_menu(title: &str) -> MenuBuilder {
+    MenuBuilder {
+        title: MenuTitle {
+            text: title.to_string(),
+            size: MenuTitleSize::Heading,
+            font: None,
+        },
+        buttons: vec![
+            MenuButtonsBuilder::CopyProgress,
+            MenuButtonsBuilder::Static(MenuButtonRow::new(vec![MenuButton::new(
+                "menu.cancel",
+                Action::CancelCopy,
+            )])),
+        ],
+        default_focus: None,
+        layout: default(),
+        cache: default(),
+    }
+}
+
+#[derive(Component)]
+pub struct CopyBar;
+
+fn update_copy_bar(world_copy: Option<Res<WorldCopy>>, mut bars: Query<&mut Style, With<CopyBar>>) {
+    let world_copy = match world_copy {
+        Some(world_copy) => world_copy,
+        None => return,
+    };
+
+    for mut style in bars.iter_mut() {
+        style.size.width = Val::Percent(world_copy.progress.fraction() * 100.);
+    }
+}
+
+// Closes the progress menu once the copy finishes or stops after being cancelled
+pub fn poll_world_copy(
+    mut commands: Commands,
+    world_copy: Option<ResMut<WorldCopy>>,
+    mut toasts: EventWriter<MenuToast>,
+    mut menu_actions: EventWriter<MenuAction>,
+    mut rebuilds: EventWriter<Rebuild>,
+    mut state: ResMut<State<GameState>>,
+    locale: Res<Locale>,
+) {
+    let mut world_copy = match world_copy {
+        Some(world_copy) => world_copy,
+        None => return,
+    };
+
+    // The progress menu may still be on its way in
+    if world_copy.result.is_none() {
+        world_copy.result = block_on(poll_once(&mut world_copy.task));
+    }
+    if world_copy.result.is_none() || state.pop().is_err() {
+        return;
+    }
+
+    let (done, failed) = match world_copy.kind {
+        WorldCopyKind::Duplicate => ("Duplicated", "menu.duplicate_failed"),
+        WorldCopyKind::Extract => ("Extracted", "menu.extract_failed"),
+    };
+    match world_copy.result.take().unwrap() {
+        Ok(()) => {
+            let files = world_copy.progress.files.load(Ordering::Relaxed);
+            let skipped = world_copy.progress.skipped.lock().unwrap().len();
+            info!(
+                "{} world {} to {}, copying {} files and skipping {}",
+                done,
+                world_copy.world.display(),
+                world_copy.copy.display(),
+                files,
+                skipped
+            );
+            let name = asset_name(&world_copy.copy);
+            let files = locale.count("menu.files", files);
+            toasts.send(if skipped == 0 {
+                MenuToast::new(
+                    ToastSeverity::Info,
+                    locale
+                        .get("menu.world_copied")
+                        .replace("{name}", &name)
+                        .replace("{files}", &files),
+                )
+            } else {
+                MenuToast::new(
+                    ToastSeverity::Warning,
+                    locale
+                        .get("menu.world_copied_skipped")
+                        .replace("{name}", &name)
+                        .replace("{files}", &files)
+                        .replace(
+                            "{skipped}",
+                            &locale.count("menu.unreadable_skipped", skipped),
+                        ),
+                )
+            });
+            menu_actions.send(match world_copy.kind {
+                WorldCopyKind::Duplicate => MenuAction::DuplicateWorld {
+                    world: world_copy.world.clone(),
+                    copy: world_copy.copy.clone(),
+                },
+                WorldCopyKind::Extract => MenuAction::ImportWorld {
+                    archive: world_copy.world.clone(),
+                    world: world_copy.copy.clone(),
+                },
+            });
+        }
+        Err(err) if err.kind() == ErrorKind::Interrupted => (),
+        Err(err) => toasts.send(MenuToast::new(
+            ToastSeverity::Error,
+            locale.get(failed).replace("{error}", &err.to_string()),
+        )),
+    }
+
+    rebuilds.send(Rebuild::Dynamic);
+    commands.remove_resource::<WorldCopy>();
+}
+
+pub fn toggle_favorite(asset_server: &AssetServer, world: &Path) -> Result<(), String> {
+    let world_path = asset_fs_path(asset_server, world)
+        .ok_or_else(|| "Worlds can only be pinned on the filesystem".to_string())?;
+    let mut meta = WorldMeta::load(&world_path)?;
+    meta.favorite = !meta.favorite;
+    meta.save(&world_path)?;
+
+    info!(
+        "{} world {}",
+        if meta.favorite { "Pinned" } else { "Unpinned" },
+        world.display()
+    );
+    Ok(())
+}
+
+// Worlds picked in the world manager, cleared when it's left
+#[derive(Default)]
+pub struct WorldSelection(pub HashSet<PathBuf>);
+
+#[derive(Component)]
+pub struct WorldCheckbox(pub PathBuf);
+
+const WORLD_CHECKED: &str = "[x]";
+const WORLD_UNCHECKED: &str = "[ ]";
+
+pub fn show_world_selection(
+    selection: Res<WorldSelection>,
+    mut texts: Query<(&mut Text, &WorldCheckbox, ChangeTrackers<WorldCheckbox>)>,
+) {
+    for (mut text, checkbox, checkbox_tracker) in texts.iter_mut() {
+        if selection.is_changed() || checkbox_tracker.is_added() {
+            text.sections[0].value = format!(
+                "{} {}",
+                if selection.0.contains(&checkbox.0) {
+                    WORLD_CHECKED
+                } else {
+                    WORLD_UNCHECKED
+                },
+                asset_name(&checkbox.0)
+            );
+        }
+    }
+}
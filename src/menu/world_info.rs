@@ -0,0 +1,215 @@
+use std::{
+    collections::HashMap,
+    path::{Path, PathBuf},
+};
+
+use bevy::{prelude::*, ui::FocusPolicy};
+
+use crate::{
+    locale::Locale,
+    state::GameState,
+    world::{dir_size, WorldMeta},
+};
+
+use super::{
+    asset_fs_path, asset_name, format_date, format_play_time, Action, Fonts, MenuEs, MenuTheme,
+};
+
+pub struct WorldInfoPlugin;
+
+impl Plugin for WorldInfoPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<WorldSizes>()
+            .add_system_set(SystemSet::on_update(GameState::Menu).with_system(show_world_info))
+            // Worlds grow while they're played
+            .add_system_set(
+                SystemSet::on_enter(GameState::MainMenu).with_system(clear_world_sizes),
+            );
+    }
+}
+
+// Sizes on disk, since adding up a whole world on every hover would be slow
+#[derive(Default)]
+struct WorldSizes(HashMap<PathBuf, u64>);
+
+impl WorldSizes {
+    fn get(&mut self, world_path: &Path) -> u64 {
+        *self
+            .0
+            .entry(world_path.to_owned())
+            .or_insert_with_key(|world_path| dir_size(world_path))
+    }
+}
+
+fn clear_world_sizes(mut sizes: ResMut<WorldSizes>) {
+    sizes.0.clear();
+}
+
+#[derive(Component)]
+struct WorldInfoPanel {
+    world: Option<PathBuf>,
+    text: Entity,
+}
+
+const WORLD_INFO_MARGIN: f32 = 2.;
+const WORLD_INFO_PADDING: f32 = 10.;
+
+fn format_size(bytes: u64) -> String {
+    const UNITS: [&str; 4] = ["KB", "MB", "GB", "TB"];
+
+    if bytes < 1000 {
+        return format!("{} B", bytes);
+    }
+
+    let mut size = bytes as f64 / 1000.;
+    let mut unit = 0;
+    while size >= 1000. && unit < UNITS.len() - 1 {
+        size /= 1000.;
+        unit += 1;
+    }
+    format!("{:.1} {}", size, UNITS[unit])
+}
+
+fn world_info(
+    world: &Path,
+    asset_server: &AssetServer,
+    locale: &Locale,
+    sizes: &mut WorldSizes,
+) -> String {
+    let mut info = vec![asset_name(world)];
+    let world_path = match asset_fs_path(asset_server, world) {
+        Some(world_path) => world_path,
+        None => return info.join("\n"),
+    };
+
+    match WorldMeta::load(&world_path) {
+        Ok(meta) => {
+            info.push(
+                locale
+                    .get("world.seed")
+                    .replace("{seed}", &meta.seed.to_string()),
+            );
+            if let Some(last_played) = meta.last_played {
+                info.push(
+                    locale
+                        .get("world.last_played")
+                        .replace("{date}", &format_date(last_played))
+                        .replace("{play_time}", &format_play_time(meta.play_time)),
+                );
+            }
+        }
+        Err(err) => warn!("{}, showing {} without details", err, world.display()),
+    }
+    info.push(
+        locale
+            .get("world.size")
+            .replace("{size}", &format_size(sizes.get(&world_path))),
+    );
+    info.join("\n")
+}
+
+fn spawn_world_info_panel(
+    commands: &mut Commands,
+    menu_e: Entity,
+    world: PathBuf,
+    info: String,
+    fonts: &Fonts,
+    theme: &MenuTheme,
+) {
+    let text_e = commands
+        .spawn_bundle(TextBundle {
+            style: Style {
+                size: Size::new(Val::Px(theme.paragraph_width), Val::Auto),
+                ..default()
+            },
+            focus_policy: FocusPolicy::Pass,
+            text: Text::with_section(
+                info,
+                TextStyle {
+                    font: fonts.font.clone(),
+                    font_size: theme.paragraph_text_size,
+                    color: theme.button_text_color,
+                },
+                default(),
+            ),
+            ..default()
+        })
+        .id();
+
+    let panel_e = commands
+        .spawn_bundle(NodeBundle {
+            style: Style {
+                position_type: PositionType::Absolute,
+                position: Rect {
+                    right: Val::Percent(WORLD_INFO_MARGIN),
+                    bottom: Val::Percent(50.),
+                    ..default()
+                },
+                padding: Rect::all(Val::Px(WORLD_INFO_PADDING)),
+                ..default()
+            },
+            color: theme.button_color.into(),
+            focus_policy: FocusPolicy::Pass,
+            ..default()
+        })
+        .insert(WorldInfoPanel {
+            world: Some(world),
+            text: text_e,
+        })
+        .add_child(text_e)
+        .id();
+    commands.entity(menu_e).add_child(panel_e);
+}
+
+type WorldHovers<'w, 's, 'a> = Query<'w, 's, (&'a Action, &'a Interaction), Changed<Interaction>>;
+
+#[allow(clippy::too_many_arguments)]
+fn show_world_info(
+    mut commands: Commands,
+    hovers: WorldHovers,
+    menu_es: Option<Res<MenuEs>>,
+    mut panels: Query<(&mut WorldInfoPanel, &mut Style, &Parent)>,
+    mut texts: Query<&mut Text>,
+    asset_server: Res<AssetServer>,
+    locale: Res<Locale>,
+    fonts: Res<Fonts>,
+    theme: Res<MenuTheme>,
+    mut sizes: ResMut<WorldSizes>,
+) {
+    let menu_e = match menu_es.as_ref().and_then(|menu_es| menu_es.last()) {
+        Some(menu) => menu.entity,
+        None => return,
+    };
+
+    for (action, interaction) in hovers.iter() {
+        let world = match action {
+            Action::Play(world) => world,
+            _ => continue,
+        };
+        let panel = panels.iter_mut().find(|(_, _, parent)| ***parent == menu_e);
+
+        match (interaction, panel) {
+            (Interaction::None, Some((mut panel, mut style, _))) => {
+                if panel.world.as_ref() == Some(world) {
+                    panel.world = None;
+                    style.display = Display::None;
+                }
+            }
+            (Interaction::None, None) => (),
+            (_, Some((mut panel, mut style, _))) => {
+                texts.get_mut(panel.text).unwrap().sections[0].value =
+                    world_info(world, &asset_server, &locale, &mut sizes);
+                panel.world = Some(world.clone());
+                style.display = Display::Flex;
+            }
+            (_, None) => spawn_world_info_panel(
+                &mut commands,
+                menu_e,
+                world.clone(),
+                world_info(world, &asset_server, &locale, &mut sizes),
+                &fonts,
+                &theme,
+            ),
+        }
+    }
+}
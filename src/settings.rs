@@ -0,0 +1,370 @@
+use std::{fs, io::ErrorKind};
+
+use bevy::{
+    app::AppExit,
+    prelude::*,
+    window::{PresentMode, WindowMode},
+    winit::WinitWindows,
+};
+use serde::{Deserialize, Serialize};
+
+const SETTINGS_PATH: &str = "settings.ron";
+
+pub struct SettingsPlugin;
+
+impl Plugin for SettingsPlugin {
+    fn build(&self, app: &mut App) {
+        let settings = Settings::load();
+        app.insert_resource(SavedSettings(settings.clone()))
+            .insert_resource(settings)
+            .add_startup_system(restore_window)
+            .add_system(apply_window_settings)
+            .add_system_to_stage(CoreStage::Last, save_window);
+    }
+}
+
+#[derive(Clone, Deserialize, PartialEq, Serialize)]
+#[serde(default)]
+pub struct Settings {
+    pub master_volume: f32,
+    pub music_volume: f32,
+    pub fullscreen: bool,
+    pub vsync: bool,
+    pub render_distance: u32,
+    pub language: String,
+    pub key_bindings: MenuKeyBindings,
+    pub theme: ThemePreset,
+    // Asks before quitting from the main menu
+    pub confirm_on_quit: bool,
+    // How long deleted worlds stay in the trash before they're purged. 0 keeps them until the trash
+    // is emptied by hand, rather than making deletes permanent.
+    pub trash_retention_days: u32,
+    // Logical size and physical position of the window when the app last exited
+    pub window_size: Option<Vec2>,
+    pub window_position: Option<IVec2>,
+}
+
+impl Default for Settings {
+    fn default() -> Self {
+        Self {
+            master_volume: 1.,
+            music_volume: 1.,
+            fullscreen: false,
+            vsync: true,
+            render_distance: 4,
+            language: "en".to_string(),
+            key_bindings: default(),
+            theme: default(),
+            confirm_on_quit: true,
+            trash_retention_days: 30,
+            window_size: None,
+            window_position: None,
+        }
+    }
+}
+
+// Built-in looks for the menus
+#[derive(Clone, Copy, Debug, Default, Deserialize, PartialEq, Serialize)]
+pub enum ThemePreset {
+    #[default]
+    Default,
+    HighContrast,
+    Dark,
+}
+
+impl ThemePreset {
+    pub fn label(self) -> &'static str {
+        match self {
+            ThemePreset::Default => "settings.theme.default",
+            ThemePreset::HighContrast => "settings.theme.high_contrast",
+            ThemePreset::Dark => "settings.theme.dark",
+        }
+    }
+
+    pub fn next(self) -> Self {
+        match self {
+            ThemePreset::Default => ThemePreset::HighContrast,
+            ThemePreset::HighContrast => ThemePreset::Dark,
+            ThemePreset::Dark => ThemePreset::Default,
+        }
+    }
+}
+
+#[derive(Clone, Copy, Debug, Deserialize, PartialEq)]
+pub enum MenuKey {
+    Up,
+    Down,
+    Left,
+    Right,
+    First,
+    Last,
+    PageUp,
+    PageDown,
+    Activate,
+    Back,
+}
+
+impl MenuKey {
+    pub const ALL: [MenuKey; 10] = [
+        MenuKey::Up,
+        MenuKey::Down,
+        MenuKey::Left,
+        MenuKey::Right,
+        MenuKey::First,
+        MenuKey::Last,
+        MenuKey::PageUp,
+        MenuKey::PageDown,
+        MenuKey::Activate,
+        MenuKey::Back,
+    ];
+
+    pub fn label(self) -> &'static str {
+        match self {
+            MenuKey::Up => "settings.key.up",
+            MenuKey::Down => "settings.key.down",
+            MenuKey::Left => "settings.key.left",
+            MenuKey::Right => "settings.key.right",
+            MenuKey::First => "settings.key.first",
+            MenuKey::Last => "settings.key.last",
+            MenuKey::PageUp => "settings.key.page_up",
+            MenuKey::PageDown => "settings.key.page_down",
+            MenuKey::Activate => "settings.key.activate",
+            MenuKey::Back => "settings.key.back",
+        }
+    }
+}
+
+// Gamepads keep their fixed buttons
+#[derive(Clone, Deserialize, PartialEq, Serialize)]
+#[serde(default)]
+pub struct MenuKeyBindings {
+    pub up: KeyCode,
+    pub down: KeyCode,
+    pub left: KeyCode,
+    pub right: KeyCode,
+    pub first: KeyCode,
+    pub last: KeyCode,
+    pub page_up: KeyCode,
+    pub page_down: KeyCode,
+    pub activate: KeyCode,
+    pub back: KeyCode,
+}
+
+impl Default for MenuKeyBindings {
+    fn default() -> Self {
+        Self {
+            up: KeyCode::Up,
+            down: KeyCode::Down,
+            left: KeyCode::Left,
+            right: KeyCode::Right,
+            first: KeyCode::Home,
+            last: KeyCode::End,
+            page_up: KeyCode::PageUp,
+            page_down: KeyCode::PageDown,
+            activate: KeyCode::Return,
+            back: KeyCode::Escape,
+        }
+    }
+}
+
+impl MenuKeyBindings {
+    pub fn get(&self, key: MenuKey) -> KeyCode {
+        match key {
+            MenuKey::Up => self.up,
+            MenuKey::Down => self.down,
+            MenuKey::Left => self.left,
+            MenuKey::Right => self.right,
+            MenuKey::First => self.first,
+            MenuKey::Last => self.last,
+            MenuKey::PageUp => self.page_up,
+            MenuKey::PageDown => self.page_down,
+            MenuKey::Activate => self.activate,
+            MenuKey::Back => self.back,
+        }
+    }
+
+    pub fn set(&mut self, key: MenuKey, key_code: KeyCode) {
+        *match key {
+            MenuKey::Up => &mut self.up,
+            MenuKey::Down => &mut self.down,
+            MenuKey::Left => &mut self.left,
+            MenuKey::Right => &mut self.right,
+            MenuKey::First => &mut self.first,
+            MenuKey::Last => &mut self.last,
+            MenuKey::PageUp => &mut self.page_up,
+            MenuKey::PageDown => &mut self.page_down,
+            MenuKey::Activate => &mut self.activate,
+            MenuKey::Back => &mut self.back,
+        } = key_code;
+    }
+
+    // The other keys bound to the same key code as `key`
+    pub fn conflicts(&self, key: MenuKey) -> Vec<MenuKey> {
+        MenuKey::ALL
+            .into_iter()
+            .filter(|other| *other != key && self.get(*other) == self.get(key))
+            .collect()
+    }
+}
+
+// As last loaded or saved, to tell whether `Settings` has unsaved changes
+pub struct SavedSettings(pub Settings);
+
+impl Settings {
+    fn load() -> Self {
+        match fs::read_to_string(SETTINGS_PATH) {
+            Ok(settings) => ron::from_str(&settings).unwrap_or_else(|err| {
+                warn!("Couldn't parse {}, using defaults: {}", SETTINGS_PATH, err);
+                default()
+            }),
+            Err(err) => {
+                if err.kind() != ErrorKind::NotFound {
+                    warn!("Couldn't read {}, using defaults: {}", SETTINGS_PATH, err);
+                }
+                default()
+            }
+        }
+    }
+
+    pub fn save(&self) {
+        let settings = match ron::ser::to_string_pretty(self, default()) {
+            Ok(settings) => settings,
+            Err(err) => {
+                error!("Couldn't serialize settings: {}", err);
+                return;
+            }
+        };
+
+        if let Err(err) = fs::write(SETTINGS_PATH, settings) {
+            error!("Couldn't write {}: {}", SETTINGS_PATH, err);
+        }
+    }
+}
+
+fn restore_window(
+    mut windows: ResMut<Windows>,
+    winit_windows: Option<NonSend<WinitWindows>>,
+    settings: Res<Settings>,
+) {
+    let window = match windows.get_primary_mut() {
+        Some(window) => window,
+        None => return,
+    };
+
+    if let Some(size) = settings.window_size {
+        window.set_resolution(size.x, size.y);
+    }
+
+    if let Some(position) = settings.window_position {
+        restore_position(window, winit_windows.as_deref(), &settings, position);
+    }
+
+    // After the windowed placement, so fullscreen goes to the monitor the window was on. Set here
+    // instead of left to `apply_window_settings`, so the menu is first laid out at the right size.
+    if settings.fullscreen {
+        window.set_mode(WindowMode::BorderlessFullscreen);
+    }
+}
+
+fn restore_position(
+    window: &mut Window,
+    winit_windows: Option<&WinitWindows>,
+    settings: &Settings,
+    position: IVec2,
+) {
+    // Monitors are only known to winit
+    let monitors = winit_windows
+        .and_then(|winit_windows| winit_windows.get_window(window.id()))
+        .map(|winit_window| {
+            winit_window
+                .available_monitors()
+                .map(|monitor| {
+                    let (position, size) = (monitor.position(), monitor.size());
+                    (
+                        IVec2::new(position.x, position.y),
+                        IVec2::new(size.width as i32, size.height as i32),
+                    )
+                })
+                .collect::<Vec<_>>()
+        })
+        .unwrap_or_default();
+    let size = settings
+        .window_size
+        .unwrap_or_else(|| Vec2::new(window.width(), window.height()))
+        * window.scale_factor() as f32;
+    window.set_position(clamp_to_monitors(position, size.as_ivec2(), &monitors));
+}
+
+// Follows the settings as they change, so unsaved changes in the settings menu can be previewed
+fn apply_window_settings(settings: Res<Settings>, mut windows: ResMut<Windows>) {
+    if !settings.is_changed() {
+        return;
+    }
+
+    let window = match windows.get_primary_mut() {
+        Some(window) => window,
+        None => return,
+    };
+
+    let mode = if settings.fullscreen {
+        WindowMode::BorderlessFullscreen
+    } else {
+        WindowMode::Windowed
+    };
+    if window.mode() != mode {
+        window.set_mode(mode);
+    }
+
+    let present_mode = if settings.vsync {
+        PresentMode::Fifo
+    } else {
+        PresentMode::Immediate
+    };
+    if window.present_mode() != present_mode {
+        window.set_present_mode(present_mode);
+    }
+}
+
+// Moves a window that isn't on any of `monitors` onto the first one, and keeps it inside it
+fn clamp_to_monitors(position: IVec2, size: IVec2, monitors: &[(IVec2, IVec2)]) -> IVec2 {
+    let (monitor_position, monitor_size) = match monitors
+        .iter()
+        .find(|(monitor_position, monitor_size)| {
+            position.cmpge(*monitor_position).all()
+                && position.cmplt(*monitor_position + *monitor_size).all()
+        })
+        .or_else(|| monitors.first())
+    {
+        Some(monitor) => *monitor,
+        None => return position,
+    };
+
+    position
+        .min(monitor_position + monitor_size - size)
+        .max(monitor_position)
+}
+
+fn save_window(
+    mut exits: EventReader<AppExit>,
+    windows: Res<Windows>,
+    mut saved_settings: ResMut<SavedSettings>,
+) {
+    if exits.iter().count() == 0 {
+        return;
+    }
+
+    let window = match windows.get_primary() {
+        Some(window) => window,
+        None => return,
+    };
+
+    // A fullscreen window's size is the monitor's, so the windowed placement is kept
+    if window.mode() != WindowMode::Windowed {
+        return;
+    }
+
+    // Only what was saved is written, so unsaved changes in the settings menu stay discarded
+    saved_settings.0.window_size = Some(Vec2::new(window.width(), window.height()));
+    saved_settings.0.window_position = window.position();
+    saved_settings.0.save();
+}
@@ -5,8 +5,13 @@ pub struct StatePlugin;
 impl Plugin for StatePlugin {
     fn build(&self, app: &mut App) {
         app.add_state(GameState::Loading)
+            .init_resource::<BufferFrames>()
             .add_system_set(SystemSet::on_enter(GameState::Buffer).with_system(push_state))
-            .add_system_set(SystemSet::on_resume(GameState::Buffer).with_system(pop_state));
+            .add_system_set(SystemSet::on_resume(GameState::Buffer).with_system(pop_state))
+            .add_system_set(SystemSet::on_update(GameState::Buffer).with_system(guard_buffer))
+            .add_system_set(SystemSet::on_exit(GameState::Buffer).with_system(reset_buffer_frames))
+            .add_system_set(SystemSet::on_enter(GameState::Edit).with_system(enter_edit))
+            .add_system_set(SystemSet::on_exit(GameState::Game).with_system(exit_edit));
     }
 }
 
@@ -16,7 +21,13 @@ pub enum GameState {
     MainMenu,
     Menu,
     Buffer,
+    // Between the menus and `Game`, until `LoadProgress` completes
+    LoadingWorld,
     Game,
+    // Over `Game`, which is frozen beneath the pause menu
+    Paused,
+    // Loads the world like `Game`, but with `EditMode` set
+    Edit,
 }
 
 #[derive(Deref)]
@@ -24,17 +35,67 @@ pub struct BufferedState(pub GameState);
 
 pub struct OpeningGame;
 
+pub struct EditMode;
+
 fn push_state(
     mut commands: Commands,
-    buffered_state: Res<BufferedState>,
+    buffered_state: Option<Res<BufferedState>>,
     mut state: ResMut<State<GameState>>,
 ) {
-    state.push(buffered_state.clone()).unwrap();
+    // Left to `guard_buffer`
+    let buffered_state = match buffered_state {
+        Some(buffered_state) => buffered_state,
+        None => return,
+    };
+
+    // Whatever was queued first goes ahead instead, and if that leaves the game in
+    // `GameState::Buffer`, `guard_buffer` pops back to the menu below
+    if let Err(err) = state.push(buffered_state.clone()) {
+        warn!("Couldn't push {:?}: {}", **buffered_state, err);
+    }
     commands.remove_resource::<BufferedState>();
 }
 
-#[allow(unused_must_use)]
+pub const BUFFER_TIMEOUT_FRAMES: u32 = 2;
+
+// Frames spent in `GameState::Buffer` without a `BufferedState`, counted from each entry
+#[derive(Default)]
+struct BufferFrames(u32);
+
+// `GameState::Buffer` should be left in the frame it's entered
+fn guard_buffer(
+    buffered_state: Option<Res<BufferedState>>,
+    mut state: ResMut<State<GameState>>,
+    mut frames: ResMut<BufferFrames>,
+) {
+    if buffered_state.is_some() {
+        frames.0 = 0;
+        return;
+    }
+
+    frames.0 += 1;
+    if frames.0 <= BUFFER_TIMEOUT_FRAMES {
+        return;
+    }
+
+    // Popping keeps the menus beneath, where replacing with `MainMenu` would stack a second main
+    // menu on top of them
+    warn!(
+        "Stuck in {:?} without a BufferedState, returning to the menu below it",
+        GameState::Buffer
+    );
+    pop_buffer(&mut state);
+}
+
+fn reset_buffer_frames(mut frames: ResMut<BufferFrames>) {
+    frames.0 = 0;
+}
+
 fn pop_state(mut state: ResMut<State<GameState>>) {
+    pop_buffer(&mut state);
+}
+
+fn pop_buffer(state: &mut State<GameState>) {
     if let Err(err) = state.pop() {
         if let StateError::StateAlreadyQueued = err {
         } else {
@@ -42,3 +103,15 @@ fn pop_state(mut state: ResMut<State<GameState>>) {
         }
     }
 }
+
+fn enter_edit(mut commands: Commands, mut state: ResMut<State<GameState>>) {
+    if let Err(err) = state.replace(GameState::LoadingWorld) {
+        error!("Couldn't load the world to edit: {}", err);
+        return;
+    }
+    commands.insert_resource(EditMode);
+}
+
+fn exit_edit(mut commands: Commands) {
+    commands.remove_resource::<EditMode>();
+}
@@ -0,0 +1,308 @@
+use std::{
+    fs::{self, File},
+    io::{self, ErrorKind, Read, Write},
+    path::{Path, PathBuf},
+    sync::{
+        atomic::{AtomicBool, AtomicU64, AtomicUsize, Ordering},
+        Mutex,
+    },
+    time::{Instant, SystemTime},
+};
+
+use bevy::{
+    prelude::*,
+    tasks::{AsyncComputeTaskPool, Task},
+};
+use futures_lite::future::{block_on, poll_once};
+use serde::{Deserialize, Serialize};
+
+use crate::state::GameState;
+
+pub struct WorldPlugin;
+
+impl Plugin for WorldPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<LoadProgress>()
+            .add_system_set(SystemSet::on_enter(GameState::LoadingWorld).with_system(check_world))
+            .add_system_set(
+                SystemSet::on_update(GameState::LoadingWorld).with_system(poll_world_check),
+            )
+            .add_system_set(
+                SystemSet::on_exit(GameState::LoadingWorld).with_system(reset_load_progress),
+            )
+            .add_system_set(SystemSet::on_exit(GameState::Game).with_system(close_world));
+    }
+}
+
+pub const WORLD_META_FILE: &str = "world.ron";
+// Written by the game when saving, shown in the world list
+pub const WORLD_THUMBNAIL_FILE: &str = "thumbnail.png";
+
+#[derive(Default, Deserialize, Serialize)]
+pub struct WorldMeta {
+    pub game: PathBuf,
+    pub seed: u64,
+    #[serde(default)]
+    pub last_played: Option<u64>,
+    #[serde(default)]
+    pub play_time: u64,
+    // Pinned above the rest of the world list
+    #[serde(default)]
+    pub favorite: bool,
+}
+
+impl WorldMeta {
+    pub fn load(world: &Path) -> Result<Self, String> {
+        let meta = fs::read_to_string(world.join(WORLD_META_FILE))
+            .map_err(|err| format!("Couldn't read world metadata: {}", err))?;
+        ron::from_str(&meta).map_err(|err| format!("Couldn't parse world metadata: {}", err))
+    }
+
+    pub fn save(&self, world: &Path) -> Result<(), String> {
+        let meta = ron::ser::to_string_pretty(self, default())
+            .map_err(|err| format!("Couldn't serialize world metadata: {}", err))?;
+        fs::write(world.join(WORLD_META_FILE), meta)
+            .map_err(|err| format!("Couldn't write world metadata: {}", err))
+    }
+}
+
+// Shared with a `copy_dir` running in the background, which fills it in as it goes
+#[derive(Default)]
+pub struct CopyProgress {
+    pub total_bytes: AtomicU64,
+    pub copied_bytes: AtomicU64,
+    pub files: AtomicUsize,
+    // Files that couldn't be read, which the copy goes on without
+    pub skipped: Mutex<Vec<PathBuf>>,
+    pub cancel: AtomicBool,
+}
+
+impl CopyProgress {
+    pub fn fraction(&self) -> f32 {
+        match self.total_bytes.load(Ordering::Relaxed) {
+            0 => 0.,
+            total => self.copied_bytes.load(Ordering::Relaxed) as f32 / total as f32,
+        }
+    }
+
+    pub fn check_cancel(&self) -> io::Result<()> {
+        if self.cancel.load(Ordering::Relaxed) {
+            return Err(io::Error::new(ErrorKind::Interrupted, "Cancelled"));
+        }
+        Ok(())
+    }
+
+    fn skip(&self, path: &Path, err: io::Error) {
+        warn!("Couldn't read {}, skipping it: {}", path.display(), err);
+        self.skipped.lock().unwrap().push(path.to_path_buf());
+    }
+}
+
+const COPY_CHUNK_SIZE: usize = 64 * 1024;
+
+// Streams files over in chunks, so memory stays bounded however big they are. Stops with
+// `ErrorKind::Interrupted` once cancelled, leaving the partial copy behind.
+pub fn copy_dir(from: &Path, to: &Path, progress: &CopyProgress) -> io::Result<()> {
+    progress
+        .total_bytes
+        .store(dir_size(from), Ordering::Relaxed);
+    copy_tree(from, to, progress, &mut vec![0; COPY_CHUNK_SIZE])
+}
+
+pub fn dir_size(dir: &Path) -> u64 {
+    fs::read_dir(dir)
+        .into_iter()
+        .flatten()
+        .filter_map(Result::ok)
+        .map(|entry| match entry.file_type() {
+            Ok(file_type) if file_type.is_dir() => dir_size(&entry.path()),
+            _ => entry.metadata().map_or(0, |metadata| metadata.len()),
+        })
+        .sum()
+}
+
+fn copy_tree(from: &Path, to: &Path, progress: &CopyProgress, chunk: &mut [u8]) -> io::Result<()> {
+    fs::create_dir(to)?;
+    for entry in fs::read_dir(from)? {
+        progress.check_cancel()?;
+
+        let entry = entry?;
+        let to = to.join(entry.file_name());
+        if entry.file_type()?.is_dir() {
+            copy_tree(&entry.path(), &to, progress, chunk)?;
+        } else {
+            copy_file(&entry.path(), &to, progress, chunk)?;
+        }
+    }
+    Ok(())
+}
+
+// Only reading is skipped past, since a failed write would fail for the rest of the copy too
+fn copy_file(from: &Path, to: &Path, progress: &CopyProgress, chunk: &mut [u8]) -> io::Result<()> {
+    let mut reader = match File::open(from) {
+        Ok(reader) => reader,
+        Err(err) => {
+            progress.skip(from, err);
+            return Ok(());
+        }
+    };
+    let mut writer = File::create(to)?;
+
+    loop {
+        progress.check_cancel()?;
+
+        let read = match reader.read(chunk) {
+            Ok(0) => break,
+            Ok(read) => read,
+            Err(err) if err.kind() == ErrorKind::Interrupted => continue,
+            Err(err) => {
+                drop(writer);
+                fs::remove_file(to)?;
+                progress.skip(from, err);
+                return Ok(());
+            }
+        };
+        writer.write_all(&chunk[..read])?;
+        progress
+            .copied_bytes
+            .fetch_add(read as u64, Ordering::Relaxed);
+    }
+
+    progress.files.fetch_add(1, Ordering::Relaxed);
+    Ok(())
+}
+
+const FNV_OFFSET_BASIS: u64 = 0xcbf29ce484222325;
+const FNV_PRIME: u64 = 0x100000001b3;
+
+pub fn parse_seed(seed: &str) -> u64 {
+    let seed = seed.trim();
+    if seed.is_empty() {
+        return rand::random();
+    }
+
+    seed.parse().unwrap_or_else(|_| {
+        seed.bytes().fold(FNV_OFFSET_BASIS, |hash, byte| {
+            (hash ^ byte as u64).wrapping_mul(FNV_PRIME)
+        })
+    })
+}
+
+// The asset path of the world being played or edited, for the game to load
+#[derive(Deref)]
+pub struct CurrentWorld(pub PathBuf);
+
+pub struct OpenWorld {
+    path: PathBuf,
+    opened: Instant,
+}
+
+impl OpenWorld {
+    pub fn open(path: PathBuf) -> Self {
+        // Metadata that can't be loaded is left as it is, for `check_world` to report, since
+        // saving over it would lose the world's game and seed
+        match WorldMeta::load(&path) {
+            Ok(mut meta) => {
+                meta.last_played = SystemTime::now()
+                    .duration_since(SystemTime::UNIX_EPOCH)
+                    .ok()
+                    .map(|since_epoch| since_epoch.as_secs());
+                if let Err(err) = meta.save(&path) {
+                    error!("{}", err);
+                }
+            }
+            Err(err) => warn!("{}, not updating {}", err, path.display()),
+        }
+
+        Self {
+            path,
+            opened: Instant::now(),
+        }
+    }
+}
+
+fn close_world(mut commands: Commands, world: Option<Res<OpenWorld>>) {
+    commands.remove_resource::<CurrentWorld>();
+
+    let world = match world {
+        Some(world) => world,
+        None => return,
+    };
+
+    match WorldMeta::load(&world.path) {
+        Ok(mut meta) => {
+            meta.play_time += world.opened.elapsed().as_secs();
+            if let Err(err) = meta.save(&world.path) {
+                error!("{}", err);
+            }
+        }
+        Err(err) => error!("{}", err),
+    }
+
+    commands.remove_resource::<OpenWorld>();
+}
+
+// Steps registered in `on_enter(GameState::LoadingWorld)`, finished before the game starts
+#[derive(Default)]
+pub struct LoadProgress {
+    pub done: u32,
+    pub total: u32,
+    pub error: Option<String>,
+}
+
+impl LoadProgress {
+    pub fn fraction(&self) -> f32 {
+        if self.total == 0 {
+            1.
+        } else {
+            self.done as f32 / self.total as f32
+        }
+    }
+
+    pub fn is_complete(&self) -> bool {
+        self.done >= self.total
+    }
+}
+
+struct WorldCheck(Task<Result<(), String>>);
+
+fn check_world(
+    mut commands: Commands,
+    world: Option<Res<OpenWorld>>,
+    thread_pool: Res<AsyncComputeTaskPool>,
+    mut progress: ResMut<LoadProgress>,
+) {
+    let path = match world {
+        Some(world) => world.path.clone(),
+        None => return,
+    };
+
+    progress.total += 1;
+    commands.insert_resource(WorldCheck(
+        thread_pool.spawn(async move { WorldMeta::load(&path).map(|_| ()) }),
+    ));
+}
+
+fn poll_world_check(
+    mut commands: Commands,
+    check: Option<ResMut<WorldCheck>>,
+    mut progress: ResMut<LoadProgress>,
+) {
+    let mut check = match check {
+        Some(check) => check,
+        None => return,
+    };
+
+    if let Some(result) = block_on(poll_once(&mut check.0)) {
+        match result {
+            Ok(()) => progress.done += 1,
+            Err(err) => progress.error = Some(err),
+        }
+        commands.remove_resource::<WorldCheck>();
+    }
+}
+
+fn reset_load_progress(mut commands: Commands, mut progress: ResMut<LoadProgress>) {
+    *progress = default();
+    commands.remove_resource::<WorldCheck>();
+}
@@ -0,0 +1,40 @@
+// Only what's public, the way a mod in another crate adds to the menus
+use bevy::{ecs::system::Command, prelude::*};
+use voxmod::menu::*;
+
+#[test]
+fn append_outside_a_menu() {
+    let mut world = World::new();
+    let mut handlers = MenuActionHandlers::default();
+    handlers.register("mod.entry", |_| ());
+    world.insert_resource(handlers);
+
+    let not_menu_e = world.spawn().id();
+    AppendMenuButton {
+        menu: not_menu_e,
+        row: MenuButtonRow::new(vec![MenuButton::custom("mod.entry", "mod.entry")]),
+    }
+    .write(&mut world);
+    assert!(world.get::<Children>(not_menu_e).is_none());
+}
+
+// Menus with lists, sliders and the rest come from RON, like the built-in main menu
+#[test]
+fn configure_root_menu() {
+    let settings: MenuBuilder = ron::from_str(
+        "(
+            title: (text: \"brand.settings\", size: Heading),
+            buttons: [
+                Slider(label: \"settings.music_volume\", min: 0, max: 1, value_action: MusicVolume),
+                Static((buttons: [(text: \"menu.back\", action: Back)])),
+            ],
+        )",
+    )
+    .unwrap();
+    let root = MenuBuilder::new("brand").with_row(MenuButtonRow::new(vec![
+        MenuButton::menu("menu.settings", settings),
+        MenuButton::custom("brand.credits", "brand.credits"),
+        MenuButton::quit("menu.quit"),
+    ]));
+    MenuPlugin::new(root, "brand");
+}